@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS update_users_updated_at ON users;
+DROP FUNCTION IF EXISTS update_updated_at_column();
+DROP TABLE IF EXISTS login_sessions;
+DROP TABLE IF EXISTS users;
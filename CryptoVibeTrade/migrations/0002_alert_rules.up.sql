@@ -0,0 +1,37 @@
+-- Per-user price alert rules and the log of fired alerts.
+
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    symbol VARCHAR(20) NOT NULL,
+    interval VARCHAR(10) NOT NULL DEFAULT '1m',
+    condition JSONB NOT NULL,
+    webhook_url TEXT,
+    cooldown_secs INTEGER DEFAULT 300 NOT NULL CHECK (cooldown_secs >= 0),
+    enabled BOOLEAN DEFAULT TRUE NOT NULL,
+    last_triggered_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_rules_user_id ON alert_rules(user_id);
+CREATE INDEX IF NOT EXISTS idx_alert_rules_enabled ON alert_rules(enabled);
+
+DROP TRIGGER IF EXISTS update_alert_rules_updated_at ON alert_rules;
+CREATE TRIGGER update_alert_rules_updated_at
+    BEFORE UPDATE ON alert_rules
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS alert_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule_id UUID NOT NULL REFERENCES alert_rules(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    intensity SMALLINT NOT NULL,
+    observed DOUBLE PRECISION NOT NULL,
+    webhook_status INTEGER,
+    triggered_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_events_user_id ON alert_events(user_id, triggered_at DESC);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS board_revisions;
+DROP TRIGGER IF EXISTS update_boards_updated_at ON boards;
+DROP TABLE IF EXISTS boards;
@@ -0,0 +1,29 @@
+-- Saved whiteboards and their revision history.
+
+CREATE TABLE IF NOT EXISTS boards (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(200) NOT NULL,
+    strokes JSONB DEFAULT '[]'::jsonb NOT NULL,
+    revision INTEGER DEFAULT 0 NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_boards_user_id ON boards(user_id, updated_at DESC);
+
+DROP TRIGGER IF EXISTS update_boards_updated_at ON boards;
+CREATE TRIGGER update_boards_updated_at
+    BEFORE UPDATE ON boards
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS board_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    board_id UUID NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    strokes JSONB NOT NULL,
+    stroke_count INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    UNIQUE (board_id, revision)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE board_revisions DROP COLUMN IF EXISTS charts;
+ALTER TABLE boards DROP COLUMN IF EXISTS charts;
@@ -0,0 +1,4 @@
+-- Live chart widgets placed on whiteboards, saved alongside the strokes.
+
+ALTER TABLE boards ADD COLUMN IF NOT EXISTS charts JSONB DEFAULT '[]'::jsonb NOT NULL;
+ALTER TABLE board_revisions ADD COLUMN IF NOT EXISTS charts JSONB DEFAULT '[]'::jsonb NOT NULL;
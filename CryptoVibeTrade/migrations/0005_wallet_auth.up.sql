@@ -0,0 +1,21 @@
+-- Solana wallets proven by signature, and the one-time sign-in challenges.
+
+CREATE TABLE IF NOT EXISTS user_wallets (
+    address VARCHAR(44) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    last_signed_in_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_wallets_user_id ON user_wallets(user_id);
+
+CREATE TABLE IF NOT EXISTS wallet_nonces (
+    nonce TEXT PRIMARY KEY,
+    wallet VARCHAR(44) NOT NULL,
+    message TEXT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_nonces_expires_at ON wallet_nonces(expires_at);
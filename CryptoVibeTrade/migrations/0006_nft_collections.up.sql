@@ -0,0 +1,9 @@
+-- Admin allow-list of verified NFT collections and the features they unlock.
+
+CREATE TABLE IF NOT EXISTS nft_collections (
+    address VARCHAR(44) PRIMARY KEY,
+    label VARCHAR(100) NOT NULL,
+    features JSONB DEFAULT '[]'::jsonb NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
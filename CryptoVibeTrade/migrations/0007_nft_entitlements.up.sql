@@ -0,0 +1,13 @@
+-- Verified NFT entitlements per user and wallet, re-checked in the background.
+
+CREATE TABLE IF NOT EXISTS nft_entitlements (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    wallet VARCHAR(44) NOT NULL REFERENCES user_wallets(address) ON DELETE CASCADE,
+    collections JSONB DEFAULT '[]'::jsonb NOT NULL,
+    features JSONB DEFAULT '[]'::jsonb NOT NULL,
+    verified_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (user_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_nft_entitlements_verified_at ON nft_entitlements(verified_at);
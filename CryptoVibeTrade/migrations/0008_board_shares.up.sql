@@ -0,0 +1,10 @@
+-- Users a board's owner invited into the board's live whiteboard room.
+
+CREATE TABLE IF NOT EXISTS board_shares (
+    board_id UUID NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (board_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_board_shares_user_id ON board_shares(user_id);
@@ -0,0 +1,1281 @@
+use crate::market_snapshot::{snapshot_for_asset, MarketSnapshot};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// =====================
+// Analysis request + prompts
+// =====================
+
+/// Which Fenrir persona answers the request. Each mode has its own
+/// system prompt and a default provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisMode {
+    /// Risk / on-chain analysis (admin dashboard, Grok by default)
+    Risk,
+    /// Technical analysis of an asset (public page, Gemini by default)
+    Technical,
+    /// Free-form market question with context (GLM by default)
+    General,
+}
+
+impl AnalysisMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisMode::Risk => "risk",
+            AnalysisMode::Technical => "technical",
+            AnalysisMode::General => "general",
+        }
+    }
+
+    fn default_provider(&self) -> &'static str {
+        match self {
+            AnalysisMode::Risk => "grok",
+            AnalysisMode::Technical => "gemini",
+            AnalysisMode::General => "glm",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisRequest {
+    pub mode: AnalysisMode,
+    pub prompt: String,
+    pub asset: Option<String>,
+    pub indicators: Option<String>,
+    pub selected_text: Option<String>,
+    pub context: Option<String>,
+    #[serde(default)]
+    pub include_screenshot: bool,
+    /// Force a specific provider instead of the configured one. Set
+    /// server-side only; a client choosing it could reach Grok or the mock
+    /// past the mode's access checks.
+    #[serde(skip)]
+    pub provider: Option<String>,
+    /// Live numbers for `asset`, attached server-side by `analyze` /
+    /// `analyze_stream`. Never accepted from clients.
+    #[serde(skip)]
+    pub market: Option<MarketSnapshot>,
+}
+
+impl AnalysisRequest {
+    pub fn risk(prompt: String, selected_text: String, include_screenshot: bool) -> Self {
+        AnalysisRequest {
+            mode: AnalysisMode::Risk,
+            prompt,
+            asset: None,
+            indicators: None,
+            selected_text: Some(selected_text),
+            context: None,
+            include_screenshot,
+            provider: None,
+            market: None,
+        }
+    }
+
+    pub fn technical(prompt: String, asset: String, indicators: String) -> Self {
+        AnalysisRequest {
+            mode: AnalysisMode::Technical,
+            prompt,
+            asset: Some(asset),
+            indicators: Some(indicators),
+            selected_text: None,
+            context: None,
+            include_screenshot: false,
+            provider: None,
+            market: None,
+        }
+    }
+
+    pub fn general(prompt: String, context: String) -> Self {
+        AnalysisRequest {
+            mode: AnalysisMode::General,
+            prompt,
+            asset: None,
+            indicators: None,
+            selected_text: None,
+            context: Some(context),
+            include_screenshot: false,
+            provider: None,
+            market: None,
+        }
+    }
+
+    pub fn asset(&self) -> &str {
+        self.asset.as_deref().unwrap_or("BTC")
+    }
+
+    pub fn indicators(&self) -> &str {
+        self.indicators
+            .as_deref()
+            .unwrap_or("Ichimoku Cloud, RSI, MACD")
+    }
+
+    pub fn selected_text(&self) -> &str {
+        self.selected_text.as_deref().unwrap_or("")
+    }
+
+    pub fn context(&self) -> &str {
+        self.context.as_deref().unwrap_or("")
+    }
+
+    /// Attach a market snapshot for the request's asset when the mode
+    /// analyses one and the market data service has candles for it.
+    pub async fn with_market_snapshot(mut self) -> Self {
+        let wants_market = self.mode == AnalysisMode::Technical || self.asset.is_some();
+        if self.market.is_none() && wants_market {
+            self.market = snapshot_for_asset(self.asset()).await;
+        }
+        self
+    }
+
+    fn market_block(&self) -> String {
+        match &self.market {
+            Some(market) => format!(
+                "\n\nBase every level and number on this data; do not invent prices.\n{}",
+                market.to_prompt()
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// Build the system + user prompt for this request. All persona
+    /// wording lives here so providers only deal with transport.
+    pub fn build_prompt(&self) -> Prompt {
+        match self.mode {
+            AnalysisMode::Risk => {
+                let system = "You're a traditional professional of risk analysis and on chain analyst using blockchain protocols and explorers official free apis and really calculating the risk and possible PnL. Provide detailed analysis with specific numbers, calculations, and risk assessments.".to_string();
+                let user = if self.include_screenshot {
+                    format!("{}(Screenshot attached) Help me out, explain me wtf is all of this, what am I doing, should I continue? Analyze the screenshot and provide detailed risk assessment.", self.selected_text())
+                } else {
+                    format!("Selected text: {}\nQuestion: {}", self.selected_text(), self.prompt)
+                };
+                Prompt { system, user }
+            }
+            AnalysisMode::Technical => {
+                let system = format!(
+                    "You are Fenrir AI, a Senior Technical Analyst specializing in cryptocurrency markets. \
+                    Current asset: {}. \
+                    Indicators on screen: {}. \
+                    Provide a concise, direct technical analysis. \
+                    Use bullet points. \
+                    Be specific about support/resistance levels. \
+                    End with DYOR. \
+                    Keep response under 150 words for voice synthesis.{}",
+                    self.asset(),
+                    self.indicators(),
+                    self.market_block()
+                );
+                Prompt {
+                    system,
+                    user: self.prompt.trim().to_string(),
+                }
+            }
+            AnalysisMode::General => {
+                let system = format!(
+                    "You are Fenrir AI, a professional cryptocurrency analyst. Provide detailed technical analysis with specific insights about market trends, support/resistance levels, and risk assessment. Be concise but thorough.{}",
+                    self.market_block()
+                );
+                Prompt {
+                    system,
+                    user: format!("Context: {}\n\nQuestion: {}", self.context(), self.prompt),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub system: String,
+    pub user: String,
+}
+
+// =====================
+// Provider trait
+// =====================
+
+pub trait AiProvider: Send + Sync {
+    /// Registry key, e.g. "grok"
+    fn name(&self) -> &'static str;
+
+    /// Human readable name used in fallback messages
+    fn display_name(&self) -> &'static str;
+
+    /// False when no API key is configured; `analyze` then answers with
+    /// `demo_response` instead of calling the network.
+    fn is_configured(&self) -> bool;
+
+    /// Placeholder text, or a configuration error for providers that must
+    /// not answer without a key.
+    fn demo_response(&self, request: &AnalysisRequest) -> Result<String>;
+
+    /// `request` is passed alongside the rendered prompt for providers that
+    /// work from the structured fields (the mock provider).
+    fn complete<'a>(
+        &'a self,
+        request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// Stream the completion as text deltas. Providers without a streaming
+    /// API fall back to sending the whole completion as one delta.
+    fn stream<'a>(
+        &'a self,
+        request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+        tx: &'a mpsc::Sender<StreamEvent>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let text = self.complete(request, prompt).await?;
+            send_delta(tx, text).await
+        })
+    }
+}
+
+/// One message of a streamed analysis, forwarded to the browser as SSE.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StreamEvent {
+    Delta(String),
+    Done,
+    Error(String),
+}
+
+async fn send_delta(tx: &mpsc::Sender<StreamEvent>, text: String) -> Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    tx.send(StreamEvent::Delta(text))
+        .await
+        .map_err(|_| anyhow!("stream closed by client"))
+}
+
+// =====================
+// Grok (xAI)
+// =====================
+
+pub struct GrokProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    base_url: String,
+    model: String,
+}
+
+impl GrokProvider {
+    pub fn from_env(client: reqwest::Client) -> Self {
+        // Try GROK_API_KEY first (from .zshrc), fall back to XAI_API_KEY
+        let api_key = env::var("GROK_API_KEY")
+            .or_else(|_| env::var("XAI_API_KEY"))
+            .ok()
+            .filter(|key| !key.is_empty() && key != "demo_key");
+
+        GrokProvider {
+            client,
+            api_key,
+            base_url: env::var("XAI_BASE_URL").unwrap_or_else(|_| "https://api.x.ai/v1".to_string()),
+            model: env::var("XAI_MODEL").unwrap_or_else(|_| "grok-beta".to_string()),
+        }
+    }
+}
+
+impl AiProvider for GrokProvider {
+    fn name(&self) -> &'static str {
+        "grok"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Grok"
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    fn demo_response(&self, request: &AnalysisRequest) -> Result<String> {
+        Ok(format!(
+            "🐺 Fenrir Grok Analysis\n\n⚠️ Please add GROK_API_KEY to .env file or export it in your shell\nGet from: https://x.ai/\n\nAdd to .env: GROK_API_KEY=your_key_here\nOr export: export GROK_API_KEY=your_key_here\n\nSelected: {}\nPrompt: {}\n\n• Risk analysis requires real API\n• Please configure GROK_API_KEY\n\nDYOR!",
+            request.selected_text(),
+            request.prompt
+        ))
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            chat_completion(
+                &self.client,
+                &self.url(),
+                self.api_key.as_deref().unwrap_or_default(),
+                &self.body(prompt, false),
+            )
+            .await
+        })
+    }
+
+    fn stream<'a>(
+        &'a self,
+        _request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+        tx: &'a mpsc::Sender<StreamEvent>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            chat_completion_stream(
+                &self.client,
+                &self.url(),
+                self.api_key.as_deref().unwrap_or_default(),
+                &self.body(prompt, true),
+                tx,
+            )
+            .await
+        })
+    }
+}
+
+impl GrokProvider {
+    fn url(&self) -> String {
+        format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
+    }
+
+    fn body(&self, prompt: &Prompt, stream: bool) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": prompt.system },
+                { "role": "user", "content": prompt.user }
+            ],
+            "temperature": 0.7,
+            "stream": stream
+        })
+    }
+}
+
+// =====================
+// Gemini (Google)
+// =====================
+
+pub struct GeminiProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl GeminiProvider {
+    pub fn from_env(client: reqwest::Client) -> Self {
+        let api_key = env::var("GEMINI_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty() && key != "demo_key");
+
+        GeminiProvider {
+            client,
+            api_key,
+            // Using Gemini 2.5 Pro (latest best model)
+            model: env::var("GEMINI_MODEL").unwrap_or_else(|_| "gemini-2.5-pro".to_string()),
+        }
+    }
+}
+
+impl AiProvider for GeminiProvider {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Gemini"
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    /// The public Fenrir page must not present placeholder text as analysis.
+    fn demo_response(&self, _request: &AnalysisRequest) -> Result<String> {
+        Err(anyhow!("Gemini is not configured: set GEMINI_API_KEY"))
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let resp = self.send(prompt, "generateContent?").await?;
+
+            let parsed = resp
+                .json::<GeminiResp>()
+                .await
+                .map_err(|e| anyhow!("decode error: {}", e))?;
+
+            Ok(parsed.text())
+        })
+    }
+
+    fn stream<'a>(
+        &'a self,
+        _request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+        tx: &'a mpsc::Sender<StreamEvent>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut resp = self.send(prompt, "streamGenerateContent?alt=sse&").await?;
+            let mut lines = SseDataLines::default();
+
+            while let Some(chunk) = resp
+                .chunk()
+                .await
+                .map_err(|e| anyhow!("network error: {}", e))?
+            {
+                for data in lines.push(&chunk) {
+                    let parsed = serde_json::from_str::<GeminiResp>(&data)
+                        .map_err(|e| anyhow!("decode error: {}", e))?;
+                    send_delta(tx, parsed.text()).await?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl GeminiProvider {
+    /// `method` is the URL tail after the model name, ending in `?` or `&`
+    /// so the API key can be appended.
+    async fn send(&self, prompt: &Prompt, method: &str) -> Result<reqwest::Response> {
+        let body = serde_json::json!({
+            "contents": [{
+                "parts": [{
+                    "text": format!("{}\n\nUser: {}", prompt.system, prompt.user)
+                }]
+            }],
+            "generationConfig": {
+                "temperature": 0.4,
+                "maxOutputTokens": 500
+            }
+        });
+
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:{}key={}",
+            self.model,
+            method,
+            self.api_key.as_deref().unwrap_or_default()
+        );
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("network error: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("provider error: {} {}", status, err));
+        }
+
+        Ok(resp)
+    }
+}
+
+#[derive(Deserialize)]
+struct GeminiResp {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+}
+
+#[derive(Deserialize)]
+struct GeminiCandidate {
+    content: GeminiContent,
+}
+
+#[derive(Deserialize)]
+struct GeminiContent {
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Deserialize)]
+struct GeminiPart {
+    #[serde(default)]
+    text: String,
+}
+
+impl GeminiResp {
+    fn text(&self) -> String {
+        self.candidates
+            .first()
+            .and_then(|c| c.content.parts.first())
+            .map(|p| p.text.clone())
+            .unwrap_or_default()
+    }
+}
+
+// =====================
+// GLM (Zhipu AI)
+// =====================
+
+pub struct GlmProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl GlmProvider {
+    pub fn from_env(client: reqwest::Client) -> Self {
+        let api_key = env::var("GLM_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty() && key != "demo_key");
+
+        GlmProvider {
+            client,
+            api_key,
+            model: env::var("GLM_MODEL").unwrap_or_else(|_| "glm-4".to_string()),
+        }
+    }
+}
+
+impl AiProvider for GlmProvider {
+    fn name(&self) -> &'static str {
+        "glm"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "GLM"
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    fn demo_response(&self, request: &AnalysisRequest) -> Result<String> {
+        Ok(format!(
+            "🤖 GLM AI Analysis (Zhipu AI)\n\n⚠️ Please add GLM_API_KEY to .env file\nGet from: https://open.bigmodel.cn/\n\nContext: {}\nPrompt: {}\n\n• Analysis requires real API\n• Please configure GLM_API_KEY\n\nDYOR!",
+            request.context(),
+            request.prompt
+        ))
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            chat_completion(
+                &self.client,
+                GLM_URL,
+                self.api_key.as_deref().unwrap_or_default(),
+                &self.body(prompt, false),
+            )
+            .await
+        })
+    }
+
+    fn stream<'a>(
+        &'a self,
+        _request: &'a AnalysisRequest,
+        prompt: &'a Prompt,
+        tx: &'a mpsc::Sender<StreamEvent>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            chat_completion_stream(
+                &self.client,
+                GLM_URL,
+                self.api_key.as_deref().unwrap_or_default(),
+                &self.body(prompt, true),
+                tx,
+            )
+            .await
+        })
+    }
+}
+
+const GLM_URL: &str = "https://open.bigmodel.cn/api/paas/v4/chat/completions";
+
+impl GlmProvider {
+    fn body(&self, prompt: &Prompt, stream: bool) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": prompt.system },
+                { "role": "user", "content": prompt.user }
+            ],
+            "temperature": 0.7,
+            "top_p": 0.9,
+            "max_tokens": 1000,
+            "stream": stream
+        })
+    }
+}
+
+// =====================
+// Mock (offline development + CI)
+// =====================
+
+/// How the mock provider answers. `AI_MOCK_BEHAVIOR` sets the default; a
+/// prompt containing `[mock:error]` or `[mock:timeout]` overrides it for a
+/// single request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockBehavior {
+    Ok,
+    Error,
+    Timeout,
+}
+
+impl FromStr for MockBehavior {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ok" => Ok(MockBehavior::Ok),
+            "error" => Ok(MockBehavior::Error),
+            "timeout" => Ok(MockBehavior::Timeout),
+            other => Err(anyhow!("unknown mock behavior: {}", other)),
+        }
+    }
+}
+
+/// Deterministic provider selected with `AI_PROVIDER=mock`. The same
+/// request always produces the same analysis, built from the request's
+/// asset, indicators and selected text, with no network access.
+pub struct MockProvider {
+    behavior: MockBehavior,
+    /// Pause between streamed chunks (`AI_MOCK_DELAY_MS`)
+    chunk_delay: Duration,
+}
+
+impl MockProvider {
+    pub fn new(behavior: MockBehavior, chunk_delay: Duration) -> Self {
+        MockProvider {
+            behavior,
+            chunk_delay,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let behavior = env::var("AI_MOCK_BEHAVIOR")
+            .ok()
+            .and_then(|b| b.parse().ok())
+            .unwrap_or(MockBehavior::Ok);
+        let delay_ms = env::var("AI_MOCK_DELAY_MS")
+            .ok()
+            .and_then(|d| d.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        MockProvider::new(behavior, Duration::from_millis(delay_ms))
+    }
+
+    fn behavior_for(&self, request: &AnalysisRequest) -> MockBehavior {
+        if request.prompt.contains("[mock:error]") {
+            MockBehavior::Error
+        } else if request.prompt.contains("[mock:timeout]") {
+            MockBehavior::Timeout
+        } else {
+            self.behavior
+        }
+    }
+
+    /// Resolve the request according to its behavior: an error, a future
+    /// that never completes (so the registry timeout fires), or the text.
+    async fn respond(&self, request: &AnalysisRequest) -> Result<String> {
+        match self.behavior_for(request) {
+            MockBehavior::Ok => Ok(mock_analysis(request)),
+            MockBehavior::Error => Err(anyhow!("provider error: 503 mock provider unavailable")),
+            MockBehavior::Timeout => std::future::pending().await,
+        }
+    }
+}
+
+impl AiProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Mock"
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    fn demo_response(&self, request: &AnalysisRequest) -> Result<String> {
+        Ok(mock_analysis(request))
+    }
+
+    fn complete<'a>(
+        &'a self,
+        request: &'a AnalysisRequest,
+        _prompt: &'a Prompt,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(self.respond(request))
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a AnalysisRequest,
+        _prompt: &'a Prompt,
+        tx: &'a mpsc::Sender<StreamEvent>,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let text = self.respond(request).await?;
+
+            // Word-sized chunks, like a real token stream
+            for word in text.split_inclusive(' ') {
+                if !self.chunk_delay.is_zero() {
+                    tokio::time::sleep(self.chunk_delay).await;
+                }
+                send_delta(tx, word.to_string()).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// FNV-1a, so mock output is stable across runs and platforms.
+fn mock_seed(request: &AnalysisRequest) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for field in [
+        request.mode.as_str(),
+        request.prompt.as_str(),
+        request.asset(),
+        request.indicators(),
+        request.selected_text(),
+        request.context(),
+    ] {
+        for byte in field.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn mock_analysis(request: &AnalysisRequest) -> String {
+    let seed = mock_seed(request);
+    let bias = ["Bullish", "Bearish", "Neutral"][(seed % 3) as usize];
+    let confidence = 55 + (seed >> 8) % 40;
+
+    let indicator_lines: Vec<String> = request
+        .indicators()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .enumerate()
+        .map(|(i, name)| {
+            let reading = ["confirms the trend", "is diverging", "is flat", "is turning up", "is turning down"]
+                [((seed >> (12 + i * 3)) % 5) as usize];
+            format!("• {}: {}", name, reading)
+        })
+        .collect();
+
+    match request.mode {
+        AnalysisMode::Risk => {
+            let risk = ["Low", "Medium", "High"][((seed >> 4) % 3) as usize];
+            let drawdown = 3 + (seed >> 16) % 25;
+            let upside = 2 + (seed >> 24) % 30;
+            format!(
+                "🐺 Fenrir Risk Analysis (mock)\n\nSelected: {}\n\n• Risk level: {}\n• Estimated max drawdown: -{}%\n• Estimated upside: +{}%\n• Bias: {} ({}% confidence)\n\nDYOR!",
+                request.selected_text(),
+                risk,
+                drawdown,
+                upside,
+                bias,
+                confidence
+            )
+        }
+        AnalysisMode::Technical => {
+            let market_line = request
+                .market
+                .as_ref()
+                .map(|m| {
+                    format!(
+                        "\nLast price: {:.2} ({:+.2}%), RSI(14): {}\n",
+                        m.last_price,
+                        m.change_pct,
+                        m.indicators
+                            .rsi_14
+                            .map(|v| format!("{:.1}", v))
+                            .unwrap_or_else(|| "n/a".to_string())
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "🤖 Fenrir AI Analysis for {} (mock)\n{}\nBias: {} ({}% confidence)\n\n{}\n\nDYOR!",
+                request.asset(),
+                market_line,
+                bias,
+                confidence,
+                indicator_lines.join("\n")
+            )
+        }
+        AnalysisMode::General => format!(
+            "🤖 Fenrir AI (mock)\n\nContext: {}\nQuestion: {}\n\n• Bias: {} ({}% confidence)\n• Wait for confirmation before entry\n\nDYOR!",
+            request.context(),
+            request.prompt,
+            bias,
+            confidence
+        ),
+    }
+}
+
+/// POST an OpenAI-style chat completion (used by Grok and GLM) and return
+/// the first choice's content.
+async fn chat_completion(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: &str,
+    body: &serde_json::Value,
+) -> Result<String> {
+    let resp = client
+        .post(url)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| anyhow!("network error: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let err = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("provider error: {} {}", status, err));
+    }
+
+    #[derive(Deserialize)]
+    struct ChatResp {
+        choices: Vec<Choice>,
+    }
+
+    #[derive(Deserialize)]
+    struct Choice {
+        message: ChoiceMsg,
+    }
+
+    #[derive(Deserialize)]
+    struct ChoiceMsg {
+        content: String,
+    }
+
+    let parsed = resp
+        .json::<ChatResp>()
+        .await
+        .map_err(|e| anyhow!("decode error: {}", e))?;
+
+    Ok(parsed
+        .choices
+        .first()
+        .map(|c| c.message.content.clone())
+        .unwrap_or_default())
+}
+
+/// Streaming variant of `chat_completion`: forwards each
+/// `choices[0].delta.content` as it arrives.
+async fn chat_completion_stream(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: &str,
+    body: &serde_json::Value,
+    tx: &mpsc::Sender<StreamEvent>,
+) -> Result<()> {
+    let mut resp = client
+        .post(url)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .header("Accept", "text/event-stream")
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| anyhow!("network error: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let err = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("provider error: {} {}", status, err));
+    }
+
+    #[derive(Deserialize)]
+    struct ChunkResp {
+        #[serde(default)]
+        choices: Vec<ChunkChoice>,
+    }
+
+    #[derive(Deserialize)]
+    struct ChunkChoice {
+        #[serde(default)]
+        delta: ChunkDelta,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct ChunkDelta {
+        #[serde(default)]
+        content: Option<String>,
+    }
+
+    let mut lines = SseDataLines::default();
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|e| anyhow!("network error: {}", e))?
+    {
+        for data in lines.push(&chunk) {
+            if data == "[DONE]" {
+                return Ok(());
+            }
+            let parsed = serde_json::from_str::<ChunkResp>(&data)
+                .map_err(|e| anyhow!("decode error: {}", e))?;
+            if let Some(text) = parsed
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|c| c.delta.content)
+            {
+                send_delta(tx, text).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a provider's `text/event-stream` body into `data:` payloads.
+/// Bytes are buffered until a full line arrives so multi-byte characters
+/// split across chunks survive.
+#[derive(Default)]
+struct SseDataLines {
+    buf: Vec<u8>,
+}
+
+impl SseDataLines {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(chunk);
+
+        let mut out = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if let Some(data) = line.strip_prefix("data:") {
+                let data = data.trim_start();
+                if !data.is_empty() {
+                    out.push(data.to_string());
+                }
+            }
+        }
+        out
+    }
+}
+
+// =====================
+// Registry
+// =====================
+
+/// Providers by name plus the config that decides which one serves a mode.
+///
+/// Selection order: `request.provider`, then `AI_PROVIDER_<MODE>` (e.g.
+/// `AI_PROVIDER_RISK=glm`), then `AI_PROVIDER`, then the mode default.
+pub struct AiRegistry {
+    providers: HashMap<&'static str, Arc<dyn AiProvider>>,
+    default_provider: Option<String>,
+    mode_overrides: HashMap<AnalysisMode, String>,
+    /// Upper bound for one provider call (`AI_TIMEOUT_SECS`, default 60)
+    timeout: Duration,
+}
+
+impl AiRegistry {
+    pub fn new() -> Self {
+        AiRegistry {
+            providers: HashMap::new(),
+            default_provider: None,
+            mode_overrides: HashMap::new(),
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn from_env() -> Self {
+        let client = reqwest::Client::new();
+        let mut registry = AiRegistry::new();
+        registry.register(Arc::new(GrokProvider::from_env(client.clone())));
+        registry.register(Arc::new(GeminiProvider::from_env(client.clone())));
+        registry.register(Arc::new(GlmProvider::from_env(client)));
+
+        if let Some(secs) = env::var("AI_TIMEOUT_SECS").ok().and_then(|s| s.parse::<u64>().ok()) {
+            registry.timeout = Duration::from_secs(secs);
+        }
+
+        registry.default_provider = env::var("AI_PROVIDER").ok().filter(|p| !p.is_empty());
+        for mode in [AnalysisMode::Risk, AnalysisMode::Technical, AnalysisMode::General] {
+            let key = format!("AI_PROVIDER_{}", mode.as_str().to_uppercase());
+            if let Ok(name) = env::var(key) {
+                if !name.is_empty() {
+                    registry.mode_overrides.insert(mode, name);
+                }
+            }
+        }
+
+        // The mock only exists when it is explicitly selected
+        if registry.selects("mock") {
+            registry.register(Arc::new(MockProvider::from_env()));
+        }
+
+        registry
+    }
+
+    /// Whether `AI_PROVIDER` or any `AI_PROVIDER_<MODE>` names `provider`.
+    fn selects(&self, provider: &str) -> bool {
+        self.default_provider.as_deref() == Some(provider)
+            || self.mode_overrides.values().any(|name| name == provider)
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn AiProvider>) {
+        self.providers.insert(provider.name(), provider);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn AiProvider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn provider_for(&self, request: &AnalysisRequest) -> Result<Arc<dyn AiProvider>> {
+        let name = request
+            .provider
+            .as_deref()
+            .or_else(|| self.mode_overrides.get(&request.mode).map(String::as_str))
+            .or(self.default_provider.as_deref())
+            .unwrap_or_else(|| request.mode.default_provider());
+
+        self.get(name)
+            .ok_or_else(|| anyhow!("unknown AI provider: {}", name))
+    }
+
+    /// Run an analysis through the selected provider, falling back to the
+    /// provider's demo response when it has no API key.
+    pub async fn analyze(&self, request: &AnalysisRequest) -> Result<String> {
+        let provider = self.provider_for(request)?;
+
+        if !provider.is_configured() {
+            return provider.demo_response(request);
+        }
+
+        let prompt = request.build_prompt();
+        let text = tokio::time::timeout(self.timeout, provider.complete(request, &prompt))
+            .await
+            .map_err(|_| self.timeout_error(provider.as_ref()))??;
+        if text.trim().is_empty() {
+            Ok(format!("No response from {}. DYOR!", provider.display_name()))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+impl AiRegistry {
+    /// Streaming counterpart of `analyze`. Always finishes with either
+    /// `StreamEvent::Done` or `StreamEvent::Error`.
+    pub async fn analyze_stream(&self, request: &AnalysisRequest, tx: mpsc::Sender<StreamEvent>) {
+        let result = async {
+            let provider = self.provider_for(request)?;
+
+            if !provider.is_configured() {
+                return send_delta(&tx, provider.demo_response(request)?).await;
+            }
+
+            let prompt = request.build_prompt();
+            tokio::time::timeout(self.timeout, provider.stream(request, &prompt, &tx))
+                .await
+                .map_err(|_| self.timeout_error(provider.as_ref()))?
+        }
+        .await;
+
+        let last = match result {
+            Ok(()) => StreamEvent::Done,
+            Err(e) => StreamEvent::Error(e.to_string()),
+        };
+        let _ = tx.send(last).await;
+    }
+
+    fn timeout_error(&self, provider: &dyn AiProvider) -> anyhow::Error {
+        anyhow!(
+            "{} timed out after {}s",
+            provider.display_name(),
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl Default for AiRegistry {
+    fn default() -> Self {
+        AiRegistry::new()
+    }
+}
+
+static REGISTRY: OnceLock<AiRegistry> = OnceLock::new();
+
+/// Process-wide registry, built from the environment on first use.
+pub fn registry() -> &'static AiRegistry {
+    REGISTRY.get_or_init(AiRegistry::from_env)
+}
+
+/// Single entry point for every AI analysis in the app.
+pub async fn analyze(request: &AnalysisRequest) -> Result<String> {
+    let request = request.clone().with_market_snapshot().await;
+    registry().analyze(&request).await
+}
+
+/// Start a streamed analysis in the background and return its events.
+pub fn analyze_stream(request: AnalysisRequest) -> mpsc::Receiver<StreamEvent> {
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let request = request.with_market_snapshot().await;
+        registry().analyze_stream(&request, tx).await;
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_registry(behavior: MockBehavior) -> AiRegistry {
+        let mut registry = AiRegistry::new().with_timeout(Duration::from_millis(50));
+        registry.register(Arc::new(MockProvider::new(behavior, Duration::ZERO)));
+        registry.default_provider = Some("mock".to_string());
+        registry
+    }
+
+    fn technical(prompt: &str) -> AnalysisRequest {
+        AnalysisRequest::technical(prompt.to_string(), "SOL".to_string(), "RSI, MACD".to_string())
+    }
+
+    async fn collect_stream(registry: &AiRegistry, request: &AnalysisRequest) -> Vec<StreamEvent> {
+        let (tx, mut rx) = mpsc::channel(64);
+        registry.analyze_stream(request, tx).await;
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn mock_behavior_parses() {
+        assert_eq!("ok".parse::<MockBehavior>().unwrap(), MockBehavior::Ok);
+        assert_eq!(" Error ".parse::<MockBehavior>().unwrap(), MockBehavior::Error);
+        assert_eq!("TIMEOUT".parse::<MockBehavior>().unwrap(), MockBehavior::Timeout);
+        assert!("slow".parse::<MockBehavior>().is_err());
+    }
+
+    #[tokio::test]
+    async fn mock_ok_is_deterministic() {
+        let registry = mock_registry(MockBehavior::Ok);
+        let first = registry.analyze(&technical("trend?")).await.unwrap();
+        let second = registry.analyze(&technical("trend?")).await.unwrap();
+        assert_eq!(first, second);
+        assert!(first.contains("Fenrir AI Analysis for SOL (mock)"));
+        assert!(first.contains("• RSI: "));
+        assert!(first.contains("• MACD: "));
+    }
+
+    #[tokio::test]
+    async fn mock_error_surfaces_provider_error() {
+        let registry = mock_registry(MockBehavior::Error);
+        let error = registry.analyze(&technical("trend?")).await.unwrap_err();
+        assert!(error.to_string().contains("503"));
+    }
+
+    #[tokio::test]
+    async fn mock_timeout_hits_registry_timeout() {
+        let registry = mock_registry(MockBehavior::Timeout);
+        let error = registry.analyze(&technical("trend?")).await.unwrap_err();
+        assert!(error.to_string().contains("Mock timed out"));
+    }
+
+    #[tokio::test]
+    async fn prompt_marker_overrides_behavior() {
+        let registry = mock_registry(MockBehavior::Ok);
+        assert!(registry.analyze(&technical("[mock:error]")).await.is_err());
+        assert!(registry.analyze(&technical("[mock:timeout]")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_ok_ends_with_done() {
+        let registry = mock_registry(MockBehavior::Ok);
+        let request = technical("trend?");
+        let events = collect_stream(&registry, &request).await;
+
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+        let text: String = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::Delta(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, registry.analyze(&request).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn stream_error_and_timeout_end_with_error() {
+        for behavior in [MockBehavior::Error, MockBehavior::Timeout] {
+            let registry = mock_registry(behavior);
+            let events = collect_stream(&registry, &technical("trend?")).await;
+            assert_eq!(events.len(), 1);
+            assert!(matches!(&events[0], StreamEvent::Error(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn gemini_without_a_key_is_a_configuration_error() {
+        let mut registry = AiRegistry::new();
+        registry.register(Arc::new(GeminiProvider {
+            client: reqwest::Client::new(),
+            api_key: None,
+            model: "gemini-2.5-pro".to_string(),
+        }));
+
+        let error = registry.analyze(&technical("trend?")).await.unwrap_err();
+        assert!(error.to_string().contains("GEMINI_API_KEY"));
+
+        let events = collect_stream(&registry, &technical("trend?")).await;
+        assert!(matches!(&events[..], [StreamEvent::Error(message)] if message.contains("GEMINI_API_KEY")));
+    }
+
+    #[test]
+    fn clients_cannot_choose_the_provider() {
+        let request: AnalysisRequest = serde_json::from_value(serde_json::json!({
+            "mode": "technical",
+            "prompt": "trend?",
+            "provider": "grok",
+        }))
+        .unwrap();
+        assert_eq!(request.provider, None);
+
+        let mut registry = mock_registry(MockBehavior::Ok);
+        registry.register(Arc::new(GrokProvider::from_env(reqwest::Client::new())));
+        assert_eq!(registry.provider_for(&request).unwrap().name(), "mock");
+    }
+
+    #[test]
+    fn unknown_provider_is_an_error() {
+        let registry = AiRegistry::new();
+        assert!(registry.provider_for(&technical("trend?")).is_err());
+    }
+}
@@ -0,0 +1,755 @@
+use crate::database::Database;
+use crate::indicators::{Indicator, Rsi};
+use crate::market_data::{KlineUpdate, MarketData, SeriesKey};
+use crate::types::{Candle, ChartInvocation, ChartPosition, DragrokEvent, DragrokEventType};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+/// How often rules are re-read from Postgres, so edits made by another
+/// instance are picked up.
+const RELOAD_EVERY: Duration = Duration::from_secs(30);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// =====================
+// Rule conditions
+// =====================
+
+/// What a rule watches. Stored as JSONB, e.g.
+/// `{"kind":"price_above","price":70000}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// Last price crosses above `price`
+    PriceAbove { price: f64 },
+    /// Last price crosses below `price`
+    PriceBelow { price: f64 },
+    /// Close moved at least `percent` (negative = a drop) over the last
+    /// `window` candles
+    PercentChange { percent: f64, window: usize },
+    /// RSI rises above `level`
+    RsiAbove {
+        level: f64,
+        #[serde(default = "default_rsi_period")]
+        period: usize,
+    },
+    /// RSI falls below `level`
+    RsiBelow {
+        level: f64,
+        #[serde(default = "default_rsi_period")]
+        period: usize,
+    },
+    /// Current candle volume is at least `multiplier` times the average of
+    /// the previous `window` candles
+    VolumeSpike { multiplier: f64, window: usize },
+}
+
+fn default_rsi_period() -> usize {
+    14
+}
+
+impl AlertCondition {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            AlertCondition::PriceAbove { price } | AlertCondition::PriceBelow { price } => {
+                if !(price.is_finite() && *price > 0.0) {
+                    return Err(anyhow!("price must be positive"));
+                }
+            }
+            AlertCondition::PercentChange { percent, window } => {
+                if !percent.is_finite() || *percent == 0.0 {
+                    return Err(anyhow!("percent must be non-zero"));
+                }
+                if *window == 0 || *window > 500 {
+                    return Err(anyhow!("window must be between 1 and 500 candles"));
+                }
+            }
+            AlertCondition::RsiAbove { level, period } | AlertCondition::RsiBelow { level, period } => {
+                if !(0.0..=100.0).contains(level) {
+                    return Err(anyhow!("RSI level must be between 0 and 100"));
+                }
+                if *period < 2 || *period > 100 {
+                    return Err(anyhow!("RSI period must be between 2 and 100"));
+                }
+            }
+            AlertCondition::VolumeSpike { multiplier, window } => {
+                if !(multiplier.is_finite() && *multiplier > 1.0) {
+                    return Err(anyhow!("volume multiplier must be greater than 1"));
+                }
+                if *window == 0 || *window > 500 {
+                    return Err(anyhow!("window must be between 1 and 500 candles"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The observed value when the condition currently holds for this
+    /// candle history (oldest first), `None` otherwise.
+    pub fn check(&self, candles: &[Candle]) -> Option<f64> {
+        let last = candles.last()?;
+        match self {
+            AlertCondition::PriceAbove { price } => (last.close > *price).then_some(last.close),
+            AlertCondition::PriceBelow { price } => (last.close < *price).then_some(last.close),
+            AlertCondition::PercentChange { percent, window } => {
+                let base = candles.iter().rev().nth(*window)?;
+                if base.close <= 0.0 {
+                    return None;
+                }
+                let change = (last.close - base.close) / base.close * 100.0;
+                let met = if *percent > 0.0 { change >= *percent } else { change <= *percent };
+                met.then_some(change)
+            }
+            AlertCondition::RsiAbove { level, period } => {
+                let rsi = latest_rsi(candles, *period)?;
+                (rsi > *level).then_some(rsi)
+            }
+            AlertCondition::RsiBelow { level, period } => {
+                let rsi = latest_rsi(candles, *period)?;
+                (rsi < *level).then_some(rsi)
+            }
+            AlertCondition::VolumeSpike { multiplier, window } => {
+                let previous = &candles[..candles.len() - 1];
+                if previous.len() < *window {
+                    return None;
+                }
+                let average = previous.iter().rev().take(*window).map(|c| c.volume).sum::<f64>()
+                    / *window as f64;
+                if average <= 0.0 {
+                    return None;
+                }
+                let ratio = last.volume / average;
+                (ratio >= *multiplier).then_some(ratio)
+            }
+        }
+    }
+
+    /// Dragon reaction for this kind of alert.
+    pub fn event_type(&self) -> DragrokEventType {
+        match self {
+            AlertCondition::PriceAbove { .. } | AlertCondition::PriceBelow { .. } => {
+                DragrokEventType::Alert
+            }
+            AlertCondition::PercentChange { .. } => DragrokEventType::Fire,
+            AlertCondition::RsiAbove { .. } | AlertCondition::RsiBelow { .. } => DragrokEventType::Glow,
+            AlertCondition::VolumeSpike { .. } => DragrokEventType::Roar,
+        }
+    }
+
+    /// 0-100, growing with how far past its threshold the market went.
+    pub fn intensity(&self, observed: f64) -> u8 {
+        let overshoot = match self {
+            AlertCondition::PriceAbove { price } | AlertCondition::PriceBelow { price } => {
+                ((observed - price) / price * 100.0).abs() * 10.0
+            }
+            AlertCondition::PercentChange { percent, .. } => (observed / percent - 1.0).max(0.0) * 50.0,
+            AlertCondition::RsiAbove { level, .. } | AlertCondition::RsiBelow { level, .. } => {
+                (observed - level).abs() * 3.0
+            }
+            AlertCondition::VolumeSpike { multiplier, .. } => (observed / multiplier - 1.0).max(0.0) * 40.0,
+        };
+        (50.0 + overshoot).clamp(50.0, 100.0) as u8
+    }
+
+    /// Indicators the dragon's chart should show for this alert.
+    pub fn chart_indicators(&self) -> Vec<String> {
+        match self {
+            AlertCondition::RsiAbove { .. } | AlertCondition::RsiBelow { .. } => vec!["rsi".to_string()],
+            AlertCondition::VolumeSpike { .. } => vec!["volume".to_string()],
+            _ => vec!["ema".to_string()],
+        }
+    }
+
+    pub fn describe(&self, symbol: &str, observed: f64) -> String {
+        match self {
+            AlertCondition::PriceAbove { price } => {
+                format!("{} crossed above {} (now {:.4})", symbol, price, observed)
+            }
+            AlertCondition::PriceBelow { price } => {
+                format!("{} crossed below {} (now {:.4})", symbol, price, observed)
+            }
+            AlertCondition::PercentChange { window, .. } => {
+                format!("{} moved {:+.2}% over the last {} candles", symbol, observed, window)
+            }
+            AlertCondition::RsiAbove { level, period } => {
+                format!("{} RSI({}) is {:.1}, above {}", symbol, period, observed, level)
+            }
+            AlertCondition::RsiBelow { level, period } => {
+                format!("{} RSI({}) is {:.1}, below {}", symbol, period, observed, level)
+            }
+            AlertCondition::VolumeSpike { window, .. } => {
+                format!("{} volume spike: {:.1}x the {}-candle average", symbol, observed, window)
+            }
+        }
+    }
+}
+
+fn latest_rsi(candles: &[Candle], period: usize) -> Option<f64> {
+    let mut rsi = Rsi::new(period);
+    candles.iter().filter_map(|c| rsi.update(c)).last()
+}
+
+// =====================
+// Rules + fired alerts
+// =====================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub interval: String,
+    pub condition: AlertCondition,
+    pub webhook_url: Option<String>,
+    pub cooldown_secs: i32,
+    pub enabled: bool,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Fields a user supplies when creating a rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAlertRule {
+    pub symbol: String,
+    #[serde(default = "default_interval")]
+    pub interval: String,
+    pub condition: AlertCondition,
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_cooldown")]
+    pub cooldown_secs: i32,
+}
+
+fn default_interval() -> String {
+    "1m".to_string()
+}
+
+fn default_cooldown() -> i32 {
+    300
+}
+
+impl NewAlertRule {
+    pub fn validate(&self) -> Result<()> {
+        self.condition.validate()?;
+        if self.cooldown_secs < 0 {
+            return Err(anyhow!("cooldown must not be negative"));
+        }
+        if let Some(url) = self.webhook_url.as_deref() {
+            parse_webhook_url(url)?;
+        }
+        Ok(())
+    }
+}
+
+// =====================
+// Webhooks
+// =====================
+
+/// Parse a webhook URL: HTTPS only, and no host that obviously points into
+/// our own network. Names are checked again after resolution by
+/// `resolve_webhook`.
+pub fn parse_webhook_url(url: &str) -> Result<reqwest::Url> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| anyhow!("webhook URL is not a valid URL"))?;
+    if parsed.scheme() != "https" {
+        return Err(anyhow!("webhook URL must use https"));
+    }
+
+    let host = webhook_host(&parsed).ok_or_else(|| anyhow!("webhook URL has no host"))?;
+    let internal = match host.parse::<IpAddr>() {
+        Ok(ip) => !is_public_ip(ip),
+        Err(_) => {
+            let name = host.trim_end_matches('.').to_lowercase();
+            name == "localhost"
+                || [".localhost", ".local", ".internal"]
+                    .iter()
+                    .any(|suffix| name.ends_with(suffix))
+        }
+    };
+    if internal {
+        return Err(anyhow!("webhook URL must point to a public host"));
+    }
+    Ok(parsed)
+}
+
+/// Host of `url` without the brackets of an IPv6 literal.
+fn webhook_host(url: &reqwest::Url) -> Option<&str> {
+    url.host_str()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+}
+
+/// Whether `ip` is a globally routable unicast address, i.e. not loopback,
+/// private, link-local (cloud metadata lives at 169.254.169.254), shared,
+/// documentation, multicast or reserved.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && v4.octets()[2] == 0)
+                || (a == 198 && (18..20).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80
+                || (first == 0x2001 && v6.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+/// Resolve a webhook's host and check every address it resolves to.
+/// Returns the URL and the address to connect to.
+pub async fn resolve_webhook(url: &str) -> Result<(reqwest::Url, SocketAddr)> {
+    let parsed = parse_webhook_url(url)?;
+    let host = webhook_host(&parsed).unwrap_or_default().to_string();
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addrs: Vec<SocketAddr> =
+        tokio::time::timeout(WEBHOOK_TIMEOUT, tokio::net::lookup_host((host.as_str(), port)))
+            .await
+            .map_err(|_| anyhow!("resolving {} timed out", host))?
+            .map_err(|e| anyhow!("cannot resolve {}: {}", host, e))?
+            .collect();
+    if addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(anyhow!("webhook host {} resolves to a private address", host));
+    }
+    let addr = addrs
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow!("cannot resolve {}", host))?;
+    Ok((parsed, addr))
+}
+
+/// POST `alert` to `url` and return the HTTP status. The connection goes
+/// to the address checked by `resolve_webhook`, so a second DNS answer
+/// cannot redirect it, and redirects are not followed.
+async fn deliver_webhook(url: &str, alert: &AlertFired) -> Result<u16> {
+    let (url, addr) = resolve_webhook(url).await?;
+    let host = webhook_host(&url).unwrap_or_default().to_string();
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(&host, addr)
+        .build()?;
+    let resp = client.post(url).json(alert).send().await?;
+    Ok(resp.status().as_u16())
+}
+
+/// One delivery: published to subscribers and POSTed to the rule's webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertFired {
+    pub rule_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub interval: String,
+    pub observed: f64,
+    pub event: DragrokEvent,
+    pub triggered_at: DateTime<Utc>,
+}
+
+// =====================
+// Engine
+// =====================
+
+struct RuleState {
+    rule: AlertRule,
+    /// Whether the condition held on the last evaluation. Starts out false,
+    /// so a rule whose condition already holds fires on its first
+    /// evaluation; after that alerts fire on the false -> true edge only.
+    active: bool,
+}
+
+impl RuleState {
+    fn new(rule: AlertRule) -> Self {
+        RuleState { rule, active: false }
+    }
+
+    /// Record this evaluation's result and return the value to fire with,
+    /// if the condition just became true and the rule is not cooling down.
+    fn observe(&mut self, observed: Option<f64>, now: DateTime<Utc>) -> Option<f64> {
+        let was_active = std::mem::replace(&mut self.active, observed.is_some());
+        let observed = observed.filter(|_| !was_active)?;
+
+        let rule = &mut self.rule;
+        let cooling = rule
+            .last_triggered_at
+            .is_some_and(|at| (now - at).num_seconds() < rule.cooldown_secs as i64);
+        if cooling {
+            return None;
+        }
+        rule.last_triggered_at = Some(now);
+        Some(observed)
+    }
+}
+
+pub struct AlertEngine {
+    db: Arc<Database>,
+    rules: RwLock<HashMap<Uuid, RuleState>>,
+    events: broadcast::Sender<AlertFired>,
+}
+
+impl AlertEngine {
+    pub fn new(db: Arc<Database>) -> Self {
+        let (events, _) = broadcast::channel(256);
+        AlertEngine {
+            db,
+            rules: RwLock::new(HashMap::new()),
+            events,
+        }
+    }
+
+    /// Every alert fired from now on, for all users.
+    pub fn subscribe(&self) -> broadcast::Receiver<AlertFired> {
+        self.events.subscribe()
+    }
+
+    /// Re-read enabled rules from the database, keeping the edge state of
+    /// rules that did not change.
+    pub async fn reload(&self) -> Result<()> {
+        let fresh = self.db.enabled_alert_rules().await?;
+        let mut rules = self.rules.write().await;
+        let mut previous = std::mem::take(&mut *rules);
+
+        for rule in fresh {
+            let active = previous
+                .remove(&rule.id)
+                .filter(|state| state.rule.condition == rule.condition && state.rule.symbol == rule.symbol)
+                .is_some_and(|state| state.active);
+            rules.insert(rule.id, RuleState { active, ..RuleState::new(rule) });
+        }
+        Ok(())
+    }
+
+    /// Evaluate every rule on `key` against its candle history.
+    pub async fn evaluate(&self, key: &SeriesKey, candles: &[Candle]) {
+        let now = Utc::now();
+        let mut fired = Vec::new();
+
+        {
+            let mut rules = self.rules.write().await;
+            for state in rules.values_mut() {
+                if state.rule.symbol != key.symbol || state.rule.interval != key.interval {
+                    continue;
+                }
+
+                let observed = state.rule.condition.check(candles);
+                if let Some(observed) = state.observe(observed, now) {
+                    fired.push((state.rule.clone(), observed));
+                }
+            }
+        }
+
+        for (rule, observed) in fired {
+            self.fire(rule, observed, now);
+        }
+    }
+
+    fn fire(&self, rule: AlertRule, observed: f64, now: DateTime<Utc>) {
+        let alert = AlertFired {
+            rule_id: rule.id,
+            user_id: rule.user_id,
+            symbol: rule.symbol.clone(),
+            interval: rule.interval.clone(),
+            observed,
+            event: DragrokEvent {
+                intensity: rule.condition.intensity(observed),
+                event_type: rule.condition.event_type(),
+                message: rule.condition.describe(&rule.symbol, observed),
+                chart_data: Some(ChartInvocation {
+                    symbol: rule.symbol.clone(),
+                    timeframe: rule.interval.clone(),
+                    indicators: rule.condition.chart_indicators(),
+                    position: ChartPosition {
+                        x: 50.0,
+                        y: 50.0,
+                        width: 40.0,
+                        height: 30.0,
+                    },
+                }),
+            },
+            triggered_at: now,
+        };
+
+        tracing::info!("Alert {} fired: {}", rule.id, alert.event.message);
+        crate::events::event_bus().publish_to(rule.user_id, alert.event.clone());
+        // No subscribers is fine; webhooks and the log still happen
+        let _ = self.events.send(alert.clone());
+
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            let webhook_status = match rule.webhook_url.as_deref() {
+                Some(url) => match deliver_webhook(url, &alert).await {
+                    Ok(status) => Some(status as i32),
+                    Err(e) => {
+                        tracing::warn!("Alert webhook {} failed: {}", url, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            if let Err(e) = db.record_alert_event(&alert, webhook_status).await {
+                tracing::warn!("Failed to record alert {}: {}", alert.rule_id, e);
+            }
+        });
+    }
+
+    /// Load rules, then evaluate them on every kline update and reload
+    /// them periodically.
+    pub fn spawn(self: &Arc<Self>, market: &MarketData) {
+        let engine = self.clone();
+        let market = market.clone();
+        tokio::spawn(async move {
+            if let Err(e) = engine.reload().await {
+                tracing::warn!("Loading alert rules failed: {}", e);
+            }
+
+            let mut updates = market.subscribe();
+            let mut reload = tokio::time::interval(RELOAD_EVERY);
+            loop {
+                tokio::select! {
+                    update = updates.recv() => match update {
+                        Ok(KlineUpdate { key, .. }) => {
+                            let candles = market.candles(&key.symbol, &key.interval).await;
+                            engine.evaluate(&key, &candles).await;
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Alert engine skipped {} kline updates", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = reload.tick() => {
+                        if let Err(e) = engine.reload().await {
+                            tracing::warn!("Reloading alert rules failed: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+static ALERTS: OnceLock<Arc<AlertEngine>> = OnceLock::new();
+
+/// Start the process-wide alert engine on the shared market feed.
+pub fn start_alert_engine(db: Arc<Database>, market: &MarketData) -> Arc<AlertEngine> {
+    let engine = ALERTS.get_or_init(|| {
+        let engine = Arc::new(AlertEngine::new(db));
+        engine.spawn(market);
+        engine
+    });
+    engine.clone()
+}
+
+pub fn alert_engine() -> Option<Arc<AlertEngine>> {
+    ALERTS.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(close: f64, volume: f64) -> Candle {
+        Candle {
+            time: 0,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+        }
+    }
+
+    fn closes(values: &[f64]) -> Vec<Candle> {
+        values.iter().map(|&c| candle(c, 1.0)).collect()
+    }
+
+    fn rule(condition: AlertCondition, cooldown_secs: i32) -> AlertRule {
+        AlertRule {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            symbol: "BTCUSDT".to_string(),
+            interval: "1m".to_string(),
+            condition,
+            webhook_url: None,
+            cooldown_secs,
+            enabled: true,
+            last_triggered_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn new_rule(webhook_url: Option<&str>) -> NewAlertRule {
+        NewAlertRule {
+            symbol: "BTCUSDT".to_string(),
+            interval: default_interval(),
+            condition: AlertCondition::PriceAbove { price: 100.0 },
+            webhook_url: webhook_url.map(str::to_string),
+            cooldown_secs: default_cooldown(),
+        }
+    }
+
+    #[test]
+    fn price_conditions() {
+        let above = AlertCondition::PriceAbove { price: 100.0 };
+        let below = AlertCondition::PriceBelow { price: 100.0 };
+        assert_eq!(above.check(&closes(&[90.0, 101.0])), Some(101.0));
+        assert_eq!(above.check(&closes(&[101.0, 100.0])), None);
+        assert_eq!(below.check(&closes(&[99.5])), Some(99.5));
+        assert_eq!(below.check(&closes(&[100.0])), None);
+        assert_eq!(above.check(&[]), None);
+    }
+
+    #[test]
+    fn percent_change_condition() {
+        let rise = AlertCondition::PercentChange { percent: 5.0, window: 2 };
+        let drop = AlertCondition::PercentChange { percent: -5.0, window: 2 };
+        let series = closes(&[100.0, 90.0, 106.0]);
+        assert_eq!(rise.check(&series), Some(6.0));
+        assert_eq!(drop.check(&series), None);
+        assert_eq!(drop.check(&closes(&[100.0, 99.0, 94.0])), Some(-6.0));
+        // Not enough history for the window
+        assert_eq!(rise.check(&closes(&[100.0, 200.0])), None);
+    }
+
+    #[test]
+    fn rsi_conditions() {
+        let rising: Vec<f64> = (1..=20).map(f64::from).collect();
+        let falling: Vec<f64> = (1..=20).rev().map(f64::from).collect();
+        let above = AlertCondition::RsiAbove { level: 70.0, period: 14 };
+        let below = AlertCondition::RsiBelow { level: 30.0, period: 14 };
+        assert_eq!(above.check(&closes(&rising)), Some(100.0));
+        assert_eq!(below.check(&closes(&rising)), None);
+        assert_eq!(below.check(&closes(&falling)), Some(0.0));
+        // Still warming up
+        assert_eq!(above.check(&closes(&rising[..10])), None);
+    }
+
+    #[test]
+    fn volume_spike_condition() {
+        let spike = AlertCondition::VolumeSpike { multiplier: 3.0, window: 3 };
+        let series = vec![candle(1.0, 10.0), candle(1.0, 20.0), candle(1.0, 30.0), candle(1.0, 60.0)];
+        assert_eq!(spike.check(&series), Some(3.0));
+        assert_eq!(spike.check(&series[1..]), None);
+        let quiet = vec![candle(1.0, 0.0), candle(1.0, 0.0), candle(1.0, 0.0), candle(1.0, 5.0)];
+        assert_eq!(spike.check(&quiet), None);
+    }
+
+    #[test]
+    fn conditions_validate() {
+        assert!(AlertCondition::PriceAbove { price: 0.0 }.validate().is_err());
+        assert!(AlertCondition::PercentChange { percent: 0.0, window: 5 }.validate().is_err());
+        assert!(AlertCondition::PercentChange { percent: 2.0, window: 0 }.validate().is_err());
+        assert!(AlertCondition::RsiAbove { level: 120.0, period: 14 }.validate().is_err());
+        assert!(AlertCondition::RsiBelow { level: 30.0, period: 1 }.validate().is_err());
+        assert!(AlertCondition::VolumeSpike { multiplier: 1.0, window: 5 }.validate().is_err());
+        assert!(AlertCondition::VolumeSpike { multiplier: 2.0, window: 5 }.validate().is_ok());
+    }
+
+    #[test]
+    fn condition_json_shape() {
+        let condition: AlertCondition = serde_json::from_str(r#"{"kind":"rsi_below","level":30}"#).unwrap();
+        assert_eq!(condition, AlertCondition::RsiBelow { level: 30.0, period: 14 });
+    }
+
+    #[test]
+    fn webhook_urls_must_be_public_https() {
+        assert!(new_rule(None).validate().is_ok());
+        assert!(new_rule(Some("https://hooks.example.com/alert")).validate().is_ok());
+
+        for url in [
+            "http://hooks.example.com/alert",
+            "ftp://hooks.example.com",
+            "not a url",
+            "https://localhost/hook",
+            "https://api.localhost/hook",
+            "https://metadata.google.internal/",
+            "https://127.0.0.1/hook",
+            "https://10.0.0.5/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(new_rule(Some(url)).validate().is_err(), "{} accepted", url);
+        }
+    }
+
+    #[test]
+    fn public_ip_ranges() {
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{} rejected", ip);
+        }
+        for ip in [
+            "0.0.0.0", "127.0.0.53", "10.1.2.3", "172.16.0.1", "192.168.0.1", "169.254.169.254",
+            "100.64.0.1", "192.0.0.8", "198.18.0.1", "224.0.0.1", "255.255.255.255", "::",
+            "::1", "fe80::1", "fc00::1", "2001:db8::1", "::ffff:10.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} accepted", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_webhook_pins_checked_address() {
+        let (url, addr) = resolve_webhook("https://1.1.1.1/hook").await.unwrap();
+        assert_eq!(url.path(), "/hook");
+        assert_eq!(addr, "1.1.1.1:443".parse().unwrap());
+        assert!(resolve_webhook("https://127.0.0.1:8443/hook").await.is_err());
+    }
+
+    #[test]
+    fn fires_when_condition_already_holds() {
+        let now = Utc::now();
+        let mut state = RuleState::new(rule(AlertCondition::PriceAbove { price: 100.0 }, 0));
+        assert_eq!(state.observe(Some(101.0), now), Some(101.0));
+        assert_eq!(state.rule.last_triggered_at, Some(now));
+    }
+
+    #[test]
+    fn fires_on_rising_edge_only() {
+        let now = Utc::now();
+        let mut state = RuleState::new(rule(AlertCondition::PriceAbove { price: 100.0 }, 0));
+        assert_eq!(state.observe(None, now), None);
+        assert_eq!(state.observe(Some(101.0), now), Some(101.0));
+        assert_eq!(state.observe(Some(102.0), now), None);
+        assert_eq!(state.observe(None, now), None);
+        assert_eq!(state.observe(Some(103.0), now), Some(103.0));
+    }
+
+    #[test]
+    fn cooldown_suppresses_refiring() {
+        let start = Utc::now();
+        let mut state = RuleState::new(rule(AlertCondition::PriceAbove { price: 100.0 }, 60));
+        assert!(state.observe(Some(101.0), start).is_some());
+        assert!(state.observe(None, start).is_none());
+
+        let soon = start + chrono::Duration::seconds(30);
+        assert_eq!(state.observe(Some(101.0), soon), None);
+        assert_eq!(state.rule.last_triggered_at, Some(start));
+
+        let later = start + chrono::Duration::seconds(90);
+        assert!(state.observe(None, later).is_none());
+        assert_eq!(state.observe(Some(101.0), later), Some(101.0));
+    }
+}
@@ -0,0 +1,515 @@
+use crate::board_geometry::{stroke_bounds, Rect, ERASER_SCALE};
+use crate::boards::validate_board_charts;
+use crate::types::{pressure_width, ChartInvocation, Stroke, Tool, TEXT_LINE_HEIGHT};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// `format` tag of exported board documents
+pub const BOARD_FORMAT: &str = "cryptovibetrade.board";
+/// Bumped whenever the document layout changes incompatibly
+pub const BOARD_FORMAT_VERSION: u32 = 1;
+/// Strokes accepted in an imported document
+pub const MAX_IMPORT_STROKES: usize = 10_000;
+
+/// Background painted behind exported SVGs, matching the board
+const SVG_BACKGROUND: &str = "#0a0a0a";
+/// Margin around the drawing in SVG exports
+const SVG_PADDING: f64 = 16.0;
+
+// =====================
+// JSON documents
+// =====================
+
+/// A board as exported to / imported from JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardDocument {
+    pub format: String,
+    pub version: u32,
+    pub name: String,
+    pub exported_at: DateTime<Utc>,
+    pub strokes: Vec<Stroke>,
+    /// Live chart widgets; absent in exports made before charts existed
+    #[serde(default)]
+    pub charts: Vec<ChartInvocation>,
+}
+
+impl BoardDocument {
+    pub fn new(name: String, strokes: Vec<Stroke>, charts: Vec<ChartInvocation>) -> Self {
+        BoardDocument {
+            format: BOARD_FORMAT.to_string(),
+            version: BOARD_FORMAT_VERSION,
+            name,
+            exported_at: Utc::now(),
+            strokes,
+            charts,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Parse an exported document. The format tag and version are checked
+    /// before the strokes, so files from other tools or newer releases get
+    /// a clear message instead of a field error.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("Not valid JSON: {}", e))?;
+
+        match value.get("format").and_then(|f| f.as_str()) {
+            Some(BOARD_FORMAT) => {}
+            Some(other) => return Err(format!("Unsupported document format '{}'", other)),
+            None => return Err("Not a board export (missing 'format')".to_string()),
+        }
+
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| "Board export has no valid 'version'".to_string())?;
+        if version == 0 || version > BOARD_FORMAT_VERSION as u64 {
+            return Err(format!(
+                "Board export version {} is not supported (expected {})",
+                version, BOARD_FORMAT_VERSION
+            ));
+        }
+
+        let document: BoardDocument = serde_json::from_value(value).map_err(|e| format!("Invalid board export: {}", e))?;
+        document.validate()?;
+        Ok(document)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.strokes.len() > MAX_IMPORT_STROKES {
+            return Err(format!("Board export has more than {} strokes", MAX_IMPORT_STROKES));
+        }
+        for (i, stroke) in self.strokes.iter().enumerate() {
+            if stroke.points.is_empty() {
+                return Err(format!("Stroke {} has no points", i + 1));
+            }
+            if stroke.tool == Tool::Select {
+                return Err(format!("Stroke {} uses the select tool", i + 1));
+            }
+            if !(stroke.thickness.is_finite() && stroke.thickness > 0.0) {
+                return Err(format!("Stroke {} has an invalid thickness", i + 1));
+            }
+            if stroke.tool == Tool::Text && stroke.text.is_none() {
+                return Err(format!("Text stroke {} has no text", i + 1));
+            }
+        }
+        validate_board_charts(&self.charts)
+    }
+}
+
+// =====================
+// SVG
+// =====================
+
+/// Bounding box of everything drawn; erasing never grows the drawing.
+pub fn strokes_bounds(strokes: &[Stroke]) -> Option<Rect> {
+    strokes
+        .iter()
+        .filter(|s| s.tool != Tool::Eraser)
+        .filter_map(stroke_bounds)
+        .reduce(|a, b| a.union(&b))
+}
+
+/// Render strokes as a standalone SVG document. The view box wraps the
+/// drawing (or `width` x `height` for an empty board). Eraser strokes mask
+/// everything drawn before them, like `destination-out` on the canvas.
+pub fn strokes_to_svg(strokes: &[Stroke], width: f64, height: f64) -> String {
+    let view = match strokes_bounds(strokes) {
+        Some(bounds) => bounds.inflate(SVG_PADDING),
+        None => Rect { x0: 0.0, y0: 0.0, x1: width, y1: height },
+    };
+    let (x0, y0, w, h) = (view.x0, view.y0, view.width(), view.height());
+
+    let mut defs = String::new();
+    let mut body = String::new();
+    let mut masks = 0;
+
+    for stroke in strokes {
+        if stroke.tool == Tool::Eraser {
+            masks += 1;
+            let _ = write!(
+                defs,
+                r##"<mask id="erase{id}" maskUnits="userSpaceOnUse" x="{x}" y="{y}" width="{w}" height="{h}"><rect x="{x}" y="{y}" width="{w}" height="{h}" fill="#fff"/>{path}</mask>"##,
+                id = masks,
+                x = num(x0),
+                y = num(y0),
+                w = num(w),
+                h = num(h),
+                path = polyline_path(stroke, "#000", stroke.thickness * ERASER_SCALE),
+            );
+            body = format!(r#"<g mask="url(#erase{})">{}</g>"#, masks, body);
+        } else {
+            body.push_str(&stroke_element(stroke));
+        }
+    }
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="{x} {y} {w} {h}">"#,
+        x = num(x0),
+        y = num(y0),
+        w = num(w),
+        h = num(h),
+    );
+    if !defs.is_empty() {
+        let _ = write!(svg, "<defs>{}</defs>", defs);
+    }
+    let _ = write!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+        num(x0),
+        num(y0),
+        num(w),
+        num(h),
+        SVG_BACKGROUND
+    );
+    svg.push_str(&body);
+    svg.push_str("</svg>");
+    svg
+}
+
+fn stroke_element(stroke: &Stroke) -> String {
+    let color = stroke.color.to_string();
+    let (Some(first), Some(last)) = (stroke.points.first(), stroke.points.last()) else {
+        return String::new();
+    };
+    let outline = format!(
+        r#"fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round""#,
+        color,
+        num(stroke.thickness)
+    );
+
+    match stroke.tool {
+        Tool::Pen if stroke.has_pressure() && stroke.points.len() >= 2 => pressure_lines(stroke, &color),
+        Tool::Pen | Tool::Eraser => polyline_path(stroke, &color, stroke.thickness),
+        Tool::Line if stroke.points.len() >= 2 => format!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            num(first.x),
+            num(first.y),
+            num(last.x),
+            num(last.y),
+            outline
+        ),
+        Tool::Rectangle if stroke.points.len() >= 2 => format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            num(first.x.min(last.x)),
+            num(first.y.min(last.y)),
+            num((last.x - first.x).abs()),
+            num((last.y - first.y).abs()),
+            outline
+        ),
+        Tool::Circle if stroke.points.len() >= 2 => format!(
+            r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+            num(first.x),
+            num(first.y),
+            num((last.x - first.x).hypot(last.y - first.y)),
+            outline
+        ),
+        Tool::Text => {
+            let Some(text) = stroke.text.as_ref() else {
+                return String::new();
+            };
+            let size = stroke.font_size();
+            let mut element = format!(
+                r#"<text x="{}" y="{}" font-family="'SF Mono', 'Fira Code', monospace" font-size="{}" fill="{}" dominant-baseline="text-before-edge" xml:space="preserve">"#,
+                num(first.x),
+                num(first.y),
+                num(size),
+                color
+            );
+            for (i, line) in text.lines().enumerate() {
+                let _ = write!(
+                    element,
+                    r#"<tspan x="{}" y="{}">{}</tspan>"#,
+                    num(first.x),
+                    num(first.y + i as f64 * size * TEXT_LINE_HEIGHT),
+                    escape_xml(line)
+                );
+            }
+            element.push_str("</text>");
+            element
+        }
+        // Shapes still being defined by a single point draw nothing
+        _ => String::new(),
+    }
+}
+
+fn polyline_path(stroke: &Stroke, color: &str, width: f64) -> String {
+    let mut d = String::new();
+    for (i, p) in stroke.points.iter().enumerate() {
+        let _ = write!(d, "{}{} {} ", if i == 0 { "M" } else { "L" }, num(p.x), num(p.y));
+    }
+    // A lone point still leaves a dot, as on the canvas
+    if stroke.points.len() == 1 {
+        let _ = write!(d, "l0 0");
+    }
+    format!(
+        r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+        d.trim_end(),
+        color,
+        num(width)
+    )
+}
+
+/// Variable-width pen stroke: one segment per point pair, each as wide as
+/// the average pressure of its ends, matching the canvas.
+fn pressure_lines(stroke: &Stroke, color: &str) -> String {
+    let mut group = format!(r#"<g stroke="{}" stroke-linecap="round">"#, color);
+    for w in stroke.points.windows(2) {
+        let _ = write!(
+            group,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke-width="{}"/>"#,
+            num(w[0].x),
+            num(w[0].y),
+            num(w[1].x),
+            num(w[1].y),
+            num(pressure_width(stroke.thickness, (w[0].pressure + w[1].pressure) / 2.0))
+        );
+    }
+    group.push_str("</g>");
+    group
+}
+
+/// Coordinates with at most two decimals and no trailing zeros.
+fn num(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChartPosition, Color, StrokePoint, DEFAULT_PRESSURE};
+
+    fn point(x: f64, y: f64) -> StrokePoint {
+        StrokePoint { x, y, pressure: DEFAULT_PRESSURE }
+    }
+
+    fn stroke(tool: Tool, color: Color, points: Vec<StrokePoint>) -> Stroke {
+        Stroke { points, color, thickness: 2.0, tool, text: None }
+    }
+
+    fn text(at: StrokePoint, content: &str) -> Stroke {
+        Stroke {
+            text: Some(content.to_string()),
+            ..stroke(Tool::Text, Color::Yellow, vec![at])
+        }
+    }
+
+    fn chart() -> ChartInvocation {
+        ChartInvocation {
+            symbol: "SOLUSDT".to_string(),
+            timeframe: "1h".to_string(),
+            indicators: vec!["ema".to_string(), "rsi".to_string()],
+            position: ChartPosition { x: 5.0, y: 10.0, width: 40.0, height: 30.0 },
+        }
+    }
+
+    const SVG_OPEN: &str = r#"<svg xmlns="http://www.w3.org/2000/svg""#;
+
+    #[test]
+    fn svg_of_empty_board() {
+        assert_eq!(
+            strokes_to_svg(&[], 800.0, 600.0),
+            format!(
+                r##"{} width="800" height="600" viewBox="0 0 800 600"><rect x="0" y="0" width="800" height="600" fill="#0a0a0a"/></svg>"##,
+                SVG_OPEN
+            )
+        );
+    }
+
+    #[test]
+    fn svg_of_line_wraps_drawing() {
+        let line = stroke(Tool::Line, Color::Red, vec![point(10.0, 20.0), point(30.0, 25.0), point(50.0, 60.0)]);
+        assert_eq!(
+            strokes_to_svg(&[line], 800.0, 600.0),
+            format!(
+                r##"{} width="74" height="74" viewBox="-7 3 74 74"><rect x="-7" y="3" width="74" height="74" fill="#0a0a0a"/><line x1="10" y1="20" x2="50" y2="60" fill="none" stroke="#ff3333" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"/></svg>"##,
+                SVG_OPEN
+            )
+        );
+    }
+
+    #[test]
+    fn svg_eraser_masks_only_earlier_strokes() {
+        let strokes = vec![
+            stroke(Tool::Pen, Color::White, vec![point(0.0, 0.0), point(100.0, 0.0)]),
+            stroke(Tool::Eraser, Color::Black, vec![point(50.0, 0.0)]),
+            stroke(Tool::Circle, Color::Red, vec![point(50.0, 0.0), point(53.0, 4.0)]),
+        ];
+        assert_eq!(
+            strokes_to_svg(&strokes, 800.0, 600.0),
+            format!(
+                concat!(
+                    r##"{} width="134" height="44" viewBox="-17 -22 134 44">"##,
+                    r##"<defs><mask id="erase1" maskUnits="userSpaceOnUse" x="-17" y="-22" width="134" height="44">"##,
+                    r##"<rect x="-17" y="-22" width="134" height="44" fill="#fff"/>"##,
+                    r##"<path d="M50 0 l0 0" fill="none" stroke="#000" stroke-width="10" stroke-linecap="round" stroke-linejoin="round"/>"##,
+                    r##"</mask></defs>"##,
+                    r##"<rect x="-17" y="-22" width="134" height="44" fill="#0a0a0a"/>"##,
+                    r##"<g mask="url(#erase1)"><path d="M0 0 L100 0" fill="none" stroke="#ffffff" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"/></g>"##,
+                    r##"<circle cx="50" cy="0" r="5" fill="none" stroke="#ff3333" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"/>"##,
+                    r##"</svg>"##,
+                ),
+                SVG_OPEN
+            )
+        );
+    }
+
+    #[test]
+    fn svg_text_is_escaped_per_line() {
+        let svg = strokes_to_svg(&[text(point(0.0, 0.0), "a<b & \"c\"\nline 2")], 800.0, 600.0);
+        assert!(svg.contains(r##"font-size="16" fill="#ffff33""##), "{}", svg);
+        assert!(
+            svg.contains(r#"<tspan x="0" y="0">a&lt;b &amp; &quot;c&quot;</tspan><tspan x="0" y="20">line 2</tspan></text>"#),
+            "{}",
+            svg
+        );
+    }
+
+    #[test]
+    fn svg_pressure_pen_draws_segments() {
+        let pen = stroke(
+            Tool::Pen,
+            Color::Custom(1, 2, 255),
+            vec![
+                StrokePoint { x: 0.0, y: 0.0, pressure: 0.0 },
+                StrokePoint { x: 10.0, y: 0.0, pressure: 1.0 },
+                StrokePoint { x: 20.0, y: 5.0, pressure: 1.0 },
+            ],
+        );
+        let svg = strokes_to_svg(&[pen], 800.0, 600.0);
+        assert!(
+            svg.contains(concat!(
+                r##"<g stroke="#0102ff" stroke-linecap="round">"##,
+                r##"<line x1="0" y1="0" x2="10" y2="0" stroke-width="2"/>"##,
+                r##"<line x1="10" y1="0" x2="20" y2="5" stroke-width="3.5"/></g>"##,
+            )),
+            "{}",
+            svg
+        );
+    }
+
+    #[test]
+    fn numbers_are_compact() {
+        assert_eq!(num(100.0), "100");
+        assert_eq!(num(2.5), "2.5");
+        assert_eq!(num(1.234), "1.23");
+        assert_eq!(num(-0.001), "0");
+        assert_eq!(num(-7.0), "-7");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let strokes = vec![
+            stroke(
+                Tool::Pen,
+                Color::Green,
+                vec![point(1.5, 2.5), StrokePoint { x: 3.0, y: 4.0, pressure: 0.9 }],
+            ),
+            stroke(Tool::Rectangle, Color::Custom(10, 20, 30), vec![point(0.0, 0.0), point(5.0, 5.0)]),
+            text(point(7.0, 8.0), "SOL ⇡ 5%\nnext line"),
+        ];
+        let document = BoardDocument::new("Plan".to_string(), strokes.clone(), vec![chart()]);
+
+        let parsed = BoardDocument::from_json(&document.to_json()).unwrap();
+        assert_eq!(parsed.format, BOARD_FORMAT);
+        assert_eq!(parsed.version, BOARD_FORMAT_VERSION);
+        assert_eq!(parsed.name, "Plan");
+        assert_eq!(parsed.exported_at, document.exported_at);
+        assert_eq!(parsed.strokes, strokes);
+        assert_eq!(parsed.charts, vec![chart()]);
+    }
+
+    #[test]
+    fn json_without_charts_still_imports() {
+        let json = r#"{"format":"cryptovibetrade.board","version":1,"name":"Old","exported_at":"2024-01-01T00:00:00Z","strokes":[]}"#;
+        let parsed = BoardDocument::from_json(json).unwrap();
+        assert!(parsed.charts.is_empty());
+    }
+
+    fn import_error(document: serde_json::Value) -> String {
+        BoardDocument::from_json(&document.to_string()).unwrap_err()
+    }
+
+    fn exported() -> serde_json::Value {
+        let strokes = vec![stroke(Tool::Pen, Color::White, vec![point(0.0, 0.0)])];
+        serde_json::from_str(&BoardDocument::new("B".to_string(), strokes, Vec::new()).to_json()).unwrap()
+    }
+
+    #[test]
+    fn json_rejects_unknown_versions() {
+        for version in [serde_json::json!(0), serde_json::json!(BOARD_FORMAT_VERSION + 1), serde_json::json!("1")] {
+            let mut document = exported();
+            document["version"] = version.clone();
+            let error = import_error(document);
+            assert!(error.contains("version"), "{} -> {}", version, error);
+        }
+
+        let mut document = exported();
+        document.as_object_mut().unwrap().remove("version");
+        assert_eq!(import_error(document), "Board export has no valid 'version'");
+    }
+
+    #[test]
+    fn json_rejects_other_formats() {
+        assert!(BoardDocument::from_json("not json").unwrap_err().starts_with("Not valid JSON"));
+
+        let mut document = exported();
+        document["format"] = serde_json::json!("excalidraw");
+        assert_eq!(import_error(document), "Unsupported document format 'excalidraw'");
+
+        let mut document = exported();
+        document.as_object_mut().unwrap().remove("format");
+        assert_eq!(import_error(document), "Not a board export (missing 'format')");
+    }
+
+    #[test]
+    fn json_rejects_invalid_strokes_and_charts() {
+        let mut document = exported();
+        document["strokes"][0]["points"] = serde_json::json!([]);
+        assert_eq!(import_error(document), "Stroke 1 has no points");
+
+        let mut document = exported();
+        document["strokes"][0]["thickness"] = serde_json::json!(0.0);
+        assert_eq!(import_error(document), "Stroke 1 has an invalid thickness");
+
+        let mut document = exported();
+        document["strokes"][0]["tool"] = serde_json::json!("Select");
+        assert_eq!(import_error(document), "Stroke 1 uses the select tool");
+
+        let mut document = exported();
+        document["strokes"][0]["tool"] = serde_json::json!("Text");
+        assert_eq!(import_error(document), "Text stroke 1 has no text");
+
+        let mut document = exported();
+        let mut bad = chart();
+        bad.position.x = 150.0;
+        document["charts"] = serde_json::to_value(vec![bad]).unwrap();
+        assert_eq!(import_error(document), "Chart position of SOLUSDT is off the board");
+    }
+}
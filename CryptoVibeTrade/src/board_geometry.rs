@@ -0,0 +1,698 @@
+use crate::types::{pressure_width, Stroke, StrokePoint, Tool};
+
+/// Eraser strokes are drawn this many times wider than their thickness
+pub const ERASER_SCALE: f64 = 5.0;
+/// Zoom limits of the board viewport
+pub const MIN_ZOOM: f64 = 0.2;
+pub const MAX_ZOOM: f64 = 5.0;
+
+/// Axis-aligned rectangle in board coordinates, `x0 <= x1`, `y0 <= y1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+}
+
+impl Rect {
+    /// Rectangle spanned by two corners in any order.
+    pub fn from_corners(a: StrokePoint, b: StrokePoint) -> Self {
+        Rect {
+            x0: a.x.min(b.x),
+            y0: a.y.min(b.y),
+            x1: a.x.max(b.x),
+            y1: a.y.max(b.y),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> f64 {
+        self.y1 - self.y0
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    pub fn inflate(&self, by: f64) -> Rect {
+        Rect {
+            x0: self.x0 - by,
+            y0: self.y0 - by,
+            x1: self.x1 + by,
+            y1: self.y1 + by,
+        }
+    }
+
+    pub fn contains(&self, p: StrokePoint) -> bool {
+        p.x >= self.x0 && p.x <= self.x1 && p.y >= self.y0 && p.y <= self.y1
+    }
+
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        other.x0 >= self.x0 && other.x1 <= self.x1 && other.y0 >= self.y0 && other.y1 <= self.y1
+    }
+
+    /// Corners clockwise from top-left.
+    pub fn corners(&self) -> [StrokePoint; 4] {
+        [
+            StrokePoint::new(self.x0, self.y0),
+            StrokePoint::new(self.x1, self.y0),
+            StrokePoint::new(self.x1, self.y1),
+            StrokePoint::new(self.x0, self.y1),
+        ]
+    }
+}
+
+// =====================
+// Viewport
+// =====================
+
+/// Pan and zoom of the board: a board point `p` is shown at screen pixel
+/// `p * zoom + offset`, the same transform the canvas applies with
+/// `translate(offset)` followed by `scale(zoom)`. Pointer input, overlays
+/// and drawing all go through this one mapping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Screen position of the board origin, in pixels
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub zoom: f64,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Viewport {
+    /// Viewport at `zoom` (clamped) that shows board point `world` at
+    /// screen pixel `screen`.
+    pub fn anchored(zoom: f64, world: StrokePoint, screen: (f64, f64)) -> Self {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        Viewport {
+            offset_x: screen.0 - world.x * zoom,
+            offset_y: screen.1 - world.y * zoom,
+            zoom,
+        }
+    }
+
+    /// Board point under screen pixel `(x, y)`.
+    pub fn to_world(&self, x: f64, y: f64) -> StrokePoint {
+        StrokePoint::new((x - self.offset_x) / self.zoom, (y - self.offset_y) / self.zoom)
+    }
+
+    /// Screen pixel showing board point `(x, y)`.
+    pub fn to_screen(&self, x: f64, y: f64) -> (f64, f64) {
+        (x * self.zoom + self.offset_x, y * self.zoom + self.offset_y)
+    }
+
+    /// Zoom by `factor`, keeping the board point under `screen` in place.
+    pub fn zoom_at(&mut self, factor: f64, screen: (f64, f64)) {
+        let world = self.to_world(screen.0, screen.1);
+        *self = Viewport::anchored(self.zoom * factor, world, screen);
+    }
+
+    /// Move the board by `(dx, dy)` screen pixels.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.offset_x += dx;
+        self.offset_y += dy;
+    }
+}
+
+fn circle_radius(stroke: &Stroke) -> f64 {
+    match (stroke.points.first(), stroke.points.last()) {
+        (Some(c), Some(e)) => (e.x - c.x).hypot(e.y - c.y),
+        _ => 0.0,
+    }
+}
+
+/// Area a stroke covers on the board, including its line width.
+pub fn stroke_bounds(stroke: &Stroke) -> Option<Rect> {
+    let first = *stroke.points.first()?;
+    if stroke.tool == Tool::Text {
+        let (x, y, w, h) = stroke.text_bounds()?;
+        return Some(Rect { x0: x, y0: y, x1: x + w, y1: y + h });
+    }
+
+    let pad = match stroke.tool {
+        Tool::Eraser => stroke.thickness * ERASER_SCALE / 2.0,
+        _ if stroke.has_pressure() => max_width(stroke) / 2.0,
+        _ => stroke.thickness / 2.0,
+    };
+    if stroke.tool == Tool::Circle {
+        let r = circle_radius(stroke) + pad;
+        return Some(Rect {
+            x0: first.x - r,
+            y0: first.y - r,
+            x1: first.x + r,
+            y1: first.y + r,
+        });
+    }
+
+    let rect = stroke
+        .points
+        .iter()
+        .fold(Rect::from_corners(first, first), |r, p| r.union(&Rect::from_corners(*p, *p)));
+    Some(rect.inflate(pad))
+}
+
+/// Widest point of a pressure-sensitive stroke.
+fn max_width(stroke: &Stroke) -> f64 {
+    let pressure = stroke.points.iter().map(|p| p.pressure).fold(0.0, f32::max);
+    pressure_width(stroke.thickness, pressure)
+}
+
+/// Distance from `p` to the segment `a`-`b`.
+pub fn distance_to_segment(p: StrokePoint, a: StrokePoint, b: StrokePoint) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    (p.x - (a.x + t * dx)).hypot(p.y - (a.y + t * dy))
+}
+
+/// Whether `p` lies on the drawn outline of `stroke`, within `tolerance`
+/// board units beyond half its line width. Shapes are hit on their outline
+/// only, so strokes inside a rectangle stay selectable. Eraser strokes are
+/// never hit.
+pub fn hit_test(stroke: &Stroke, p: StrokePoint, tolerance: f64) -> bool {
+    let width = if stroke.has_pressure() { max_width(stroke) } else { stroke.thickness };
+    let reach = width / 2.0 + tolerance;
+    let (Some(&first), Some(&last)) = (stroke.points.first(), stroke.points.last()) else {
+        return false;
+    };
+
+    match stroke.tool {
+        Tool::Eraser | Tool::Select => false,
+        Tool::Text => stroke.text_contains(p.x, p.y, tolerance),
+        Tool::Pen => {
+            if stroke.points.len() == 1 {
+                return (p.x - first.x).hypot(p.y - first.y) <= reach;
+            }
+            stroke
+                .points
+                .windows(2)
+                .any(|w| distance_to_segment(p, w[0], w[1]) <= reach)
+        }
+        Tool::Line => distance_to_segment(p, first, last) <= reach,
+        Tool::Rectangle => {
+            let corners = Rect::from_corners(first, last).corners();
+            (0..4).any(|i| distance_to_segment(p, corners[i], corners[(i + 1) % 4]) <= reach)
+        }
+        Tool::Circle => ((p.x - first.x).hypot(p.y - first.y) - circle_radius(stroke)).abs() <= reach,
+    }
+}
+
+/// Topmost stroke hit at `p`, as an index into `strokes`.
+pub fn topmost_hit(strokes: &[Stroke], p: StrokePoint, tolerance: f64) -> Option<usize> {
+    strokes.iter().rposition(|s| hit_test(s, p, tolerance))
+}
+
+/// Strokes lying entirely inside `band`, for rubber-band selection.
+pub fn strokes_in_rect(strokes: &[Stroke], band: &Rect) -> Vec<usize> {
+    strokes
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !matches!(s.tool, Tool::Eraser | Tool::Select))
+        .filter(|(_, s)| stroke_bounds(s).is_some_and(|b| band.contains_rect(&b)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Combined bounds of the strokes at `indices`.
+pub fn selection_bounds(strokes: &[Stroke], indices: &[usize]) -> Option<Rect> {
+    indices
+        .iter()
+        .filter_map(|&i| strokes.get(i).and_then(stroke_bounds))
+        .reduce(|a, b| a.union(&b))
+}
+
+pub fn translate(stroke: &mut Stroke, dx: f64, dy: f64) {
+    for p in stroke.points.iter_mut() {
+        p.x += dx;
+        p.y += dy;
+    }
+}
+
+/// Scale a stroke's geometry about `origin`. Text scales its font with the
+/// vertical factor; circles keep the centre-to-edge layout and so stay round.
+pub fn scale(stroke: &mut Stroke, origin: StrokePoint, sx: f64, sy: f64) {
+    for p in stroke.points.iter_mut() {
+        p.x = origin.x + (p.x - origin.x) * sx;
+        p.y = origin.y + (p.y - origin.y) * sy;
+    }
+    if stroke.tool == Tool::Text {
+        stroke.thickness = (stroke.thickness * sy.abs()).max(0.5);
+    }
+}
+
+// =====================
+// Freehand filtering
+// =====================
+
+fn lerp(a: StrokePoint, b: StrokePoint, t: f64) -> StrokePoint {
+    StrokePoint::with_pressure(
+        a.x + (b.x - a.x) * t,
+        a.y + (b.y - a.y) * t,
+        a.pressure + (b.pressure - a.pressure) * t as f32,
+    )
+}
+
+/// Chaikin corner cutting: each pass replaces every segment with points at
+/// 1/4 and 3/4 of its length, rounding off jitter. Endpoints are kept.
+pub fn chaikin(points: &[StrokePoint], iterations: usize) -> Vec<StrokePoint> {
+    let mut current = points.to_vec();
+    for _ in 0..iterations {
+        if current.len() < 3 {
+            break;
+        }
+        let mut next = Vec::with_capacity(current.len() * 2);
+        next.push(current[0]);
+        for w in current.windows(2) {
+            next.push(lerp(w[0], w[1], 0.25));
+            next.push(lerp(w[0], w[1], 0.75));
+        }
+        next.push(current[current.len() - 1]);
+        current = next;
+    }
+    current
+}
+
+/// Pressure difference from the straight-line interpolation that still
+/// keeps a point during decimation
+const PRESSURE_TOLERANCE: f32 = 0.08;
+
+/// Ramer-Douglas-Peucker decimation: drop points that lie within
+/// `tolerance` of the line through their neighbours and whose pressure is
+/// close to what that line would interpolate. Endpoints are kept.
+pub fn decimate(points: &[StrokePoint], tolerance: f64) -> Vec<StrokePoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // Explicit stack instead of recursion; long strokes have thousands of points
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        if end <= start + 1 {
+            continue;
+        }
+        let (a, b) = (points[start], points[end]);
+        let span = (end - start) as f64;
+
+        // Farthest point off the line, else the one whose pressure strays most
+        let (mut far, mut far_distance) = (None, tolerance);
+        let (mut off, mut off_pressure) = (None, PRESSURE_TOLERANCE);
+        for (i, p) in points.iter().enumerate().take(end).skip(start + 1) {
+            let distance = distance_to_segment(*p, a, b);
+            if distance > far_distance {
+                (far, far_distance) = (Some(i), distance);
+            }
+            let deviation = (p.pressure - lerp(a, b, (i - start) as f64 / span).pressure).abs();
+            if deviation > off_pressure {
+                (off, off_pressure) = (Some(i), deviation);
+            }
+        }
+
+        if let Some(i) = far.or(off) {
+            keep[i] = true;
+            ranges.push((start, i));
+            ranges.push((i, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(p, keep)| keep.then_some(*p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Color;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * (1.0 + a.abs().max(b.abs()))
+    }
+
+    fn viewports() -> Vec<Viewport> {
+        let mut all = vec![Viewport::default()];
+        for zoom in [MIN_ZOOM, 0.5, 1.0, 1.7, 3.25, MAX_ZOOM] {
+            for (offset_x, offset_y) in [(0.0, 0.0), (-250.5, 80.0), (1234.0, -987.25)] {
+                all.push(Viewport { offset_x, offset_y, zoom });
+            }
+        }
+        all
+    }
+
+    const SCREEN_POINTS: [(f64, f64); 4] = [(0.0, 0.0), (400.0, 300.0), (-35.5, 812.25), (1920.0, 1080.0)];
+
+    #[test]
+    fn screen_world_screen_round_trip() {
+        for view in viewports() {
+            for (x, y) in SCREEN_POINTS {
+                let world = view.to_world(x, y);
+                let (sx, sy) = view.to_screen(world.x, world.y);
+                assert!(close(sx, x) && close(sy, y), "{:?}: ({}, {}) -> ({}, {})", view, x, y, sx, sy);
+
+                let back = view.to_world(sx, sy);
+                assert!(close(back.x, world.x) && close(back.y, world.y), "{:?}", view);
+            }
+        }
+    }
+
+    #[test]
+    fn to_screen_matches_canvas_transform() {
+        let view = Viewport { offset_x: 10.0, offset_y: -20.0, zoom: 2.0 };
+        assert_eq!(view.to_screen(0.0, 0.0), (10.0, -20.0));
+        assert_eq!(view.to_screen(5.0, 5.0), (20.0, -10.0));
+        let world = view.to_world(20.0, -10.0);
+        assert_eq!((world.x, world.y), (5.0, 5.0));
+    }
+
+    #[test]
+    fn zoom_at_keeps_point_under_cursor() {
+        for view in viewports() {
+            for factor in [0.1, 0.8, 1.0, 1.25, 3.0, 50.0] {
+                for cursor in SCREEN_POINTS {
+                    let before = view.to_world(cursor.0, cursor.1);
+                    let mut zoomed = view;
+                    zoomed.zoom_at(factor, cursor);
+
+                    let after = zoomed.to_world(cursor.0, cursor.1);
+                    assert!(
+                        close(after.x, before.x) && close(after.y, before.y),
+                        "{:?} x{} at {:?}: {:?} -> {:?}",
+                        view,
+                        factor,
+                        cursor,
+                        before,
+                        after
+                    );
+                    let expected = (view.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+                    assert!(close(zoomed.zoom, expected));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_in_then_out_restores_viewport() {
+        let start = Viewport { offset_x: -120.0, offset_y: 45.5, zoom: 1.3 };
+        let mut view = start;
+        view.zoom_at(2.0, (300.0, 200.0));
+        view.zoom_at(0.5, (300.0, 200.0));
+        assert!(close(view.zoom, start.zoom));
+        assert!(close(view.offset_x, start.offset_x) && close(view.offset_y, start.offset_y));
+    }
+
+    #[test]
+    fn anchored_clamps_zoom_and_pins_point() {
+        let world = StrokePoint::new(40.0, -15.0);
+        for zoom in [0.01, 0.75, 2.0, 100.0] {
+            let view = Viewport::anchored(zoom, world, (640.0, 360.0));
+            assert!((MIN_ZOOM..=MAX_ZOOM).contains(&view.zoom));
+            let (sx, sy) = view.to_screen(world.x, world.y);
+            assert!(close(sx, 640.0) && close(sy, 360.0));
+        }
+    }
+
+    #[test]
+    fn pan_moves_every_point_by_the_same_pixels() {
+        for view in viewports() {
+            let mut panned = view;
+            panned.pan(-33.0, 12.5);
+            for (x, y) in SCREEN_POINTS {
+                let world = view.to_world(x, y);
+                let (sx, sy) = panned.to_screen(world.x, world.y);
+                assert!(close(sx, x - 33.0) && close(sy, y + 12.5));
+            }
+        }
+    }
+
+    fn text(x: f64, y: f64, content: &str) -> Stroke {
+        Stroke::new_text(StrokePoint::new(x, y), content.to_string(), Color::Yellow, 2.0)
+    }
+
+    #[test]
+    fn text_bounds_follow_the_longest_line() {
+        // Font size 16: five glyphs of 9.6 wide, two lines of 20 high
+        assert_eq!(
+            stroke_bounds(&text(100.0, 50.0, "Buy\nzone!")),
+            Some(Rect { x0: 100.0, y0: 50.0, x1: 148.0, y1: 90.0 })
+        );
+        let empty = Stroke { text: None, ..text(100.0, 50.0, "") };
+        assert_eq!(stroke_bounds(&empty), None);
+    }
+
+    #[test]
+    fn text_is_hit_anywhere_in_its_box() {
+        let label = text(100.0, 50.0, "Buy\nzone!");
+        // Unlike shapes, the inside of a text box counts
+        assert!(hit_test(&label, StrokePoint::new(124.0, 70.0), 0.0));
+        assert!(hit_test(&label, StrokePoint::new(150.0, 88.0), 3.0));
+        assert!(!hit_test(&label, StrokePoint::new(152.0, 70.0), 3.0));
+        assert!(!hit_test(&label, StrokePoint::new(120.0, 46.0), 3.0));
+        assert!(!hit_test(&Stroke { text: None, ..label }, StrokePoint::new(124.0, 70.0), 3.0));
+    }
+
+    /// A stroke 4 wide, so 2 on each side of its path
+    fn shape(tool: Tool, points: &[(f64, f64)]) -> Stroke {
+        Stroke {
+            points: points.iter().map(|&(x, y)| StrokePoint::new(x, y)).collect(),
+            color: Color::White,
+            thickness: 4.0,
+            tool,
+            text: None,
+        }
+    }
+
+    fn hits(stroke: &Stroke, x: f64, y: f64) -> bool {
+        hit_test(stroke, StrokePoint::new(x, y), 1.0)
+    }
+
+    #[test]
+    fn pen_is_hit_within_half_its_width() {
+        let pen = shape(Tool::Pen, &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        assert!(hits(&pen, 5.0, 2.9));
+        assert!(!hits(&pen, 5.0, 3.1));
+        assert!(hits(&pen, 12.9, 5.0));
+        assert!(!hits(&pen, 13.1, 5.0));
+
+        let dot = shape(Tool::Pen, &[(50.0, 50.0)]);
+        assert!(hits(&dot, 52.9, 50.0));
+        assert!(!hits(&dot, 53.1, 50.0));
+
+        // Full pressure draws 1.75x as wide
+        let pressed = Stroke {
+            points: vec![StrokePoint::with_pressure(0.0, 0.0, 1.0), StrokePoint::with_pressure(10.0, 0.0, 1.0)],
+            ..pen
+        };
+        assert!(hits(&pressed, 5.0, 4.4));
+        assert!(!hits(&pressed, 5.0, 4.6));
+    }
+
+    #[test]
+    fn line_is_hit_between_its_ends_only() {
+        // Intermediate points of a line are ignored
+        let line = shape(Tool::Line, &[(0.0, 0.0), (50.0, 40.0), (100.0, 0.0)]);
+        assert!(hits(&line, 50.0, 2.5));
+        assert!(!hits(&line, 50.0, 3.5));
+        assert!(!hits(&line, 50.0, 40.0));
+        assert!(hits(&line, 102.5, 0.0));
+        assert!(!hits(&line, 103.5, 0.0));
+    }
+
+    #[test]
+    fn rectangle_is_hit_on_its_outline() {
+        let rect = shape(Tool::Rectangle, &[(100.0, 50.0), (0.0, 0.0)]);
+        assert!(hits(&rect, 50.0, 52.5));
+        assert!(hits(&rect, -2.5, 25.0));
+        assert!(hits(&rect, 100.0, 0.0));
+        assert!(!hits(&rect, 50.0, 53.5));
+        assert!(!hits(&rect, 50.0, 25.0));
+    }
+
+    #[test]
+    fn circle_is_hit_on_its_rim() {
+        // Centre, then a point on the rim 50 away
+        let circle = shape(Tool::Circle, &[(0.0, 0.0), (30.0, 40.0)]);
+        assert!(hits(&circle, 0.0, 52.5));
+        assert!(hits(&circle, -47.5, 0.0));
+        assert!(!hits(&circle, 0.0, 53.5));
+        assert!(!hits(&circle, 0.0, 46.5));
+        assert!(!hits(&circle, 0.0, 0.0));
+    }
+
+    #[test]
+    fn topmost_hit_prefers_later_strokes_and_skips_erasers() {
+        let strokes = vec![
+            shape(Tool::Line, &[(0.0, 0.0), (100.0, 0.0)]),
+            shape(Tool::Rectangle, &[(40.0, 0.0), (60.0, 20.0)]),
+            shape(Tool::Eraser, &[(0.0, 0.0), (100.0, 0.0)]),
+        ];
+        assert_eq!(topmost_hit(&strokes, StrokePoint::new(50.0, 0.0), 1.0), Some(1));
+        assert_eq!(topmost_hit(&strokes, StrokePoint::new(10.0, 0.0), 1.0), Some(0));
+        assert_eq!(topmost_hit(&strokes, StrokePoint::new(50.0, 10.0), 1.0), None);
+        assert!(!hits(&strokes[2], 50.0, 0.0));
+    }
+
+    #[test]
+    fn rubber_band_takes_only_strokes_fully_inside() {
+        let strokes = vec![
+            shape(Tool::Pen, &[(10.0, 10.0), (20.0, 20.0)]),
+            // Crosses the band's right edge
+            shape(Tool::Line, &[(50.0, 50.0), (150.0, 50.0)]),
+            // Both points inside, but the rim reaches x = 102
+            shape(Tool::Circle, &[(90.0, 50.0), (90.0, 60.0)]),
+            // Only the line width pokes out
+            shape(Tool::Pen, &[(1.0, 80.0), (10.0, 80.0)]),
+            shape(Tool::Eraser, &[(30.0, 30.0), (40.0, 40.0)]),
+            text(40.0, 60.0, "TP"),
+        ];
+        let band = Rect::from_corners(StrokePoint::new(100.0, 100.0), StrokePoint::new(0.0, 0.0));
+        assert_eq!(strokes_in_rect(&strokes, &band), vec![0, 5]);
+
+        let wider = Rect { x0: -5.0, y0: 0.0, x1: 160.0, y1: 100.0 };
+        assert_eq!(strokes_in_rect(&strokes, &wider), vec![0, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn selection_bounds_cover_the_selected_strokes() {
+        let strokes = vec![
+            shape(Tool::Pen, &[(10.0, 10.0), (20.0, 20.0)]),
+            shape(Tool::Line, &[(500.0, 500.0), (600.0, 500.0)]),
+            shape(Tool::Circle, &[(90.0, 50.0), (90.0, 60.0)]),
+        ];
+        assert_eq!(
+            selection_bounds(&strokes, &[0, 2]),
+            Some(Rect { x0: 8.0, y0: 8.0, x1: 102.0, y1: 62.0 })
+        );
+        // Stale indices are ignored
+        assert_eq!(selection_bounds(&strokes, &[2, 7]), stroke_bounds(&strokes[2]));
+        assert_eq!(selection_bounds(&strokes, &[]), None);
+    }
+
+    #[test]
+    fn translate_moves_every_point() {
+        let mut pen = shape(Tool::Pen, &[(0.0, 0.0), (10.0, 5.0)]);
+        pen.points[1].pressure = 0.9;
+        let before = stroke_bounds(&pen).unwrap();
+        translate(&mut pen, 15.0, -5.0);
+
+        assert_eq!(
+            pen.points,
+            vec![StrokePoint::new(15.0, -5.0), StrokePoint::with_pressure(25.0, 0.0, 0.9)]
+        );
+        let after = stroke_bounds(&pen).unwrap();
+        assert_eq!(
+            (after.x0, after.y0, after.x1, after.y1),
+            (before.x0 + 15.0, before.y0 - 5.0, before.x1 + 15.0, before.y1 - 5.0)
+        );
+    }
+
+    #[test]
+    fn scale_keeps_the_anchor_fixed() {
+        let anchor = StrokePoint::new(10.0, 10.0);
+        let mut rect = shape(Tool::Rectangle, &[(10.0, 10.0), (30.0, 20.0)]);
+        scale(&mut rect, anchor, 2.0, 3.0);
+        assert_eq!(rect.points, vec![StrokePoint::new(10.0, 10.0), StrokePoint::new(50.0, 40.0)]);
+        assert_eq!(rect.thickness, 4.0);
+
+        // Dragging a handle past the anchor mirrors the stroke
+        let mut line = shape(Tool::Line, &[(20.0, 0.0), (30.0, 10.0)]);
+        scale(&mut line, anchor, -1.0, 1.0);
+        assert_eq!(line.points, vec![StrokePoint::new(0.0, 0.0), StrokePoint::new(-10.0, 10.0)]);
+
+        let mut label = text(10.0, 10.0, "TP");
+        scale(&mut label, StrokePoint::new(0.0, 0.0), 1.0, -1.5);
+        assert_eq!(label.points, vec![StrokePoint::new(10.0, -15.0)]);
+        assert_eq!(label.thickness, 3.0);
+        scale(&mut label, StrokePoint::new(0.0, 0.0), 1.0, 0.01);
+        assert_eq!(label.thickness, 0.5);
+    }
+
+    fn zigzag(n: usize) -> Vec<StrokePoint> {
+        (0..n)
+            .map(|i| StrokePoint::with_pressure(i as f64 * 10.0, (i % 2) as f64 * 10.0, 0.3 + (i % 3) as f32 * 0.2))
+            .collect()
+    }
+
+    #[test]
+    fn chaikin_keeps_the_endpoints() {
+        let points = zigzag(6);
+        let smooth = chaikin(&points, 2);
+        // Each pass turns n points into 2n: both ends plus two per segment
+        assert_eq!(smooth.len(), 24);
+        assert_eq!(smooth.first(), points.first());
+        assert_eq!(smooth.last(), points.last());
+        // Corners are cut, so no point reaches the zigzag's peaks any more
+        assert!(smooth[1..smooth.len() - 1].iter().all(|p| p.y > 0.0 && p.y < 10.0));
+    }
+
+    #[test]
+    fn decimate_drops_collinear_points() {
+        let line: Vec<StrokePoint> = (0..1000).map(|i| StrokePoint::new(i as f64 * 0.5, i as f64 * 0.25)).collect();
+        assert_eq!(decimate(&line, 0.5), vec![line[0], line[999]]);
+
+        // Jitter below the tolerance goes too
+        let shaky: Vec<StrokePoint> = (0..1000).map(|i| StrokePoint::new(i as f64, (i % 2) as f64 * 0.3)).collect();
+        assert_eq!(decimate(&shaky, 0.5).len(), 2);
+
+        // A corner is kept, the points along its legs are not
+        let corner: Vec<StrokePoint> = (0..=100)
+            .map(|i| StrokePoint::new(i.min(50) as f64, (i.max(50) - 50) as f64))
+            .collect();
+        assert_eq!(decimate(&corner, 0.5), vec![corner[0], corner[50], corner[100]]);
+    }
+
+    #[test]
+    fn decimate_keeps_points_whose_pressure_changes() {
+        let pressed = |pressure| {
+            vec![
+                StrokePoint::with_pressure(0.0, 0.0, 0.5),
+                StrokePoint::with_pressure(10.0, 0.0, pressure),
+                StrokePoint::with_pressure(20.0, 0.0, 0.5),
+            ]
+        };
+        assert_eq!(decimate(&pressed(0.6), 0.5).len(), 3);
+        assert_eq!(decimate(&pressed(0.4), 0.5).len(), 3);
+        assert_eq!(decimate(&pressed(0.55), 0.5).len(), 2);
+
+        // Pressure that follows the line's own interpolation is redundant
+        let ramp: Vec<StrokePoint> = (0..=100)
+            .map(|i| StrokePoint::with_pressure(i as f64, 0.0, 0.2 + i as f32 * 0.006))
+            .collect();
+        assert_eq!(decimate(&ramp, 0.5).len(), 2);
+    }
+
+    #[test]
+    fn filters_pass_short_strokes_through() {
+        for n in 0..=2 {
+            let points = zigzag(n);
+            assert_eq!(chaikin(&points, 3), points);
+            assert_eq!(decimate(&points, 0.5), points);
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use crate::board_geometry::translate;
+use crate::types::{Color, Stroke};
+use std::collections::VecDeque;
+
+/// Undo steps kept per board
+pub const MAX_HISTORY_EDITS: usize = 200;
+/// Stroke points retained across all undo steps (see `BoardEdit::cost`)
+pub const MAX_HISTORY_POINTS: usize = 250_000;
+
+/// Color and width of a stroke, the part a style change touches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    pub color: Color,
+    pub thickness: f64,
+}
+
+impl StrokeStyle {
+    pub fn of(stroke: &Stroke) -> Self {
+        StrokeStyle {
+            color: stroke.color,
+            thickness: stroke.thickness,
+        }
+    }
+
+    fn set_on(&self, stroke: &mut Stroke) {
+        stroke.color = self.color;
+        stroke.thickness = self.thickness;
+    }
+}
+
+/// One undoable change to a board's stroke list. Each edit carries what it
+/// needs to be applied again and reverted, so undo and redo never have to
+/// guess from the current strokes.
+#[derive(Debug, Clone)]
+pub enum BoardEdit {
+    /// Stroke appended at the end
+    Add { stroke: Stroke },
+    /// Strokes replaced in place: `(index, before, after)`
+    Update { changes: Vec<(usize, Stroke, Stroke)> },
+    /// Strokes removed from these indices (ascending)
+    Remove { removed: Vec<(usize, Stroke)> },
+    /// Every stroke removed
+    Clear { strokes: Vec<Stroke> },
+    /// Strokes shifted by an offset
+    Move { indices: Vec<usize>, dx: f64, dy: f64 },
+    /// Color/width changed: `(index, before, after)`
+    Restyle { changes: Vec<(usize, StrokeStyle, StrokeStyle)> },
+    /// Several edits undone and redone as one step
+    Group { edits: Vec<BoardEdit> },
+}
+
+impl BoardEdit {
+    /// Remove the strokes at `indices`, remembering them for undo.
+    pub fn remove(strokes: &[Stroke], indices: &[usize]) -> Option<Self> {
+        let mut indices: Vec<usize> = indices.iter().copied().filter(|&i| i < strokes.len()).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.is_empty() {
+            return None;
+        }
+        Some(BoardEdit::Remove {
+            removed: indices.into_iter().map(|i| (i, strokes[i].clone())).collect(),
+        })
+    }
+
+    /// Remove everything, or `None` for an empty board.
+    pub fn clear(strokes: &[Stroke]) -> Option<Self> {
+        (!strokes.is_empty()).then(|| BoardEdit::Clear {
+            strokes: strokes.to_vec(),
+        })
+    }
+
+    /// Restyle the strokes at `indices` with `change`; `None` if nothing changes.
+    pub fn restyle(strokes: &[Stroke], indices: &[usize], change: impl Fn(&mut StrokeStyle)) -> Option<Self> {
+        let changes: Vec<(usize, StrokeStyle, StrokeStyle)> = indices
+            .iter()
+            .filter_map(|&i| {
+                let before = StrokeStyle::of(strokes.get(i)?);
+                let mut after = before;
+                change(&mut after);
+                (after != before).then_some((i, before, after))
+            })
+            .collect();
+        (!changes.is_empty()).then_some(BoardEdit::Restyle { changes })
+    }
+
+    pub fn apply(&self, strokes: &mut Vec<Stroke>) {
+        match self {
+            BoardEdit::Add { stroke } => strokes.push(stroke.clone()),
+            BoardEdit::Update { changes } => {
+                for (i, _, after) in changes {
+                    if let Some(slot) = strokes.get_mut(*i) {
+                        *slot = after.clone();
+                    }
+                }
+            }
+            BoardEdit::Remove { removed } => {
+                // Back to front so earlier indices stay valid
+                for (i, _) in removed.iter().rev() {
+                    if *i < strokes.len() {
+                        strokes.remove(*i);
+                    }
+                }
+            }
+            BoardEdit::Clear { .. } => strokes.clear(),
+            BoardEdit::Move { indices, dx, dy } => {
+                for i in indices {
+                    if let Some(stroke) = strokes.get_mut(*i) {
+                        translate(stroke, *dx, *dy);
+                    }
+                }
+            }
+            BoardEdit::Restyle { changes } => {
+                for (i, _, after) in changes {
+                    if let Some(stroke) = strokes.get_mut(*i) {
+                        after.set_on(stroke);
+                    }
+                }
+            }
+            BoardEdit::Group { edits } => {
+                for edit in edits {
+                    edit.apply(strokes);
+                }
+            }
+        }
+    }
+
+    pub fn revert(&self, strokes: &mut Vec<Stroke>) {
+        match self {
+            BoardEdit::Add { .. } => {
+                strokes.pop();
+            }
+            BoardEdit::Update { changes } => {
+                for (i, before, _) in changes {
+                    if let Some(slot) = strokes.get_mut(*i) {
+                        *slot = before.clone();
+                    }
+                }
+            }
+            BoardEdit::Remove { removed } => {
+                for (i, stroke) in removed {
+                    let at = (*i).min(strokes.len());
+                    strokes.insert(at, stroke.clone());
+                }
+            }
+            BoardEdit::Clear { strokes: cleared } => *strokes = cleared.clone(),
+            BoardEdit::Move { indices, dx, dy } => {
+                for i in indices {
+                    if let Some(stroke) = strokes.get_mut(*i) {
+                        translate(stroke, -dx, -dy);
+                    }
+                }
+            }
+            BoardEdit::Restyle { changes } => {
+                for (i, before, _) in changes {
+                    if let Some(stroke) = strokes.get_mut(*i) {
+                        before.set_on(stroke);
+                    }
+                }
+            }
+            BoardEdit::Group { edits } => {
+                for edit in edits.iter().rev() {
+                    edit.revert(strokes);
+                }
+            }
+        }
+    }
+
+    /// Rough memory weight: stroke points held (at least 1 per edit).
+    pub fn cost(&self) -> usize {
+        let points = |s: &Stroke| s.points.len() + s.text.as_ref().map_or(0, |t| t.len() / 16);
+        let cost = match self {
+            BoardEdit::Add { stroke } => points(stroke),
+            BoardEdit::Update { changes } => changes.iter().map(|(_, a, b)| points(a) + points(b)).sum(),
+            BoardEdit::Remove { removed } => removed.iter().map(|(_, s)| points(s)).sum(),
+            BoardEdit::Clear { strokes } => strokes.iter().map(points).sum(),
+            BoardEdit::Move { indices, .. } => indices.len(),
+            BoardEdit::Restyle { changes } => changes.len(),
+            BoardEdit::Group { edits } => edits.iter().map(BoardEdit::cost).sum(),
+        };
+        cost.max(1)
+    }
+
+    /// Fold `next` into `self` when both are moves of the same strokes, so a
+    /// drag becomes one step no matter how many pointer events it took.
+    fn merge(&mut self, next: &BoardEdit) -> bool {
+        match (self, next) {
+            (
+                BoardEdit::Move { indices, dx, dy },
+                BoardEdit::Move { indices: next_indices, dx: ndx, dy: ndy },
+            ) if indices == next_indices => {
+                *dx += ndx;
+                *dy += ndy;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Undo/redo stacks of `BoardEdit`s with a bounded footprint. Oldest undo
+/// steps are dropped once there are more than `max_edits` of them or they
+/// hold more than `max_cost` points. Edits recorded between `begin` and
+/// `commit` become a single step.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<BoardEdit>,
+    redo: Vec<BoardEdit>,
+    /// Edits of the open transaction, if any
+    pending: Option<Vec<BoardEdit>>,
+    cost: usize,
+    max_edits: usize,
+    max_cost: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(MAX_HISTORY_EDITS, MAX_HISTORY_POINTS)
+    }
+}
+
+impl History {
+    pub fn new(max_edits: usize, max_cost: usize) -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            pending: None,
+            cost: 0,
+            max_edits: max_edits.max(1),
+            max_cost,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.pending.as_ref().is_some_and(|p| !p.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Apply `edit` to `strokes` and record it.
+    pub fn apply(&mut self, edit: BoardEdit, strokes: &mut Vec<Stroke>) {
+        edit.apply(strokes);
+        self.record(edit);
+    }
+
+    /// Record an edit whose effect is already on the board.
+    pub fn record(&mut self, edit: BoardEdit) {
+        self.redo.clear();
+        if let Some(pending) = self.pending.as_mut() {
+            let merged = pending.last_mut().is_some_and(|last| last.merge(&edit));
+            if !merged {
+                pending.push(edit);
+            }
+            return;
+        }
+        self.push_undo(edit);
+    }
+
+    fn push_undo(&mut self, edit: BoardEdit) {
+        self.cost += edit.cost();
+        self.undo.push_back(edit);
+        while self.undo.len() > self.max_edits || (self.cost > self.max_cost && self.undo.len() > 1) {
+            if let Some(dropped) = self.undo.pop_front() {
+                self.cost -= dropped.cost();
+            }
+        }
+    }
+
+    /// Start a transaction. Nested calls join the open one.
+    pub fn begin(&mut self) {
+        if self.pending.is_none() {
+            self.pending = Some(Vec::new());
+        }
+    }
+
+    /// Close the transaction, recording its edits as one step.
+    pub fn commit(&mut self) {
+        let Some(mut edits) = self.pending.take() else {
+            return;
+        };
+        match edits.len() {
+            0 => {}
+            1 => self.push_undo(edits.remove(0)),
+            _ => self.push_undo(BoardEdit::Group { edits }),
+        }
+    }
+
+    /// Step back; returns whether anything changed. Closes an open transaction first.
+    pub fn undo(&mut self, strokes: &mut Vec<Stroke>) -> bool {
+        self.commit();
+        let Some(edit) = self.undo.pop_back() else {
+            return false;
+        };
+        self.cost -= edit.cost();
+        edit.revert(strokes);
+        self.redo.push(edit);
+        true
+    }
+
+    pub fn redo(&mut self, strokes: &mut Vec<Stroke>) -> bool {
+        self.commit();
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        edit.apply(strokes);
+        self.push_undo(edit);
+        true
+    }
+
+    /// Drop every step, e.g. when another board is opened.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending = None;
+        self.cost = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{StrokePoint, Tool, DEFAULT_PRESSURE};
+
+    fn stroke(x: f64, points: usize) -> Stroke {
+        Stroke {
+            points: (0..points)
+                .map(|i| StrokePoint { x: x + i as f64, y: 0.0, pressure: DEFAULT_PRESSURE })
+                .collect(),
+            color: Color::White,
+            thickness: 2.0,
+            tool: Tool::Pen,
+            text: None,
+        }
+    }
+
+    fn xs(strokes: &[Stroke]) -> Vec<f64> {
+        strokes.iter().map(|s| s.points[0].x).collect()
+    }
+
+    fn ys(strokes: &[Stroke]) -> Vec<f64> {
+        strokes.iter().map(|s| s.points[0].y).collect()
+    }
+
+    fn add(history: &mut History, strokes: &mut Vec<Stroke>, x: f64) {
+        history.apply(BoardEdit::Add { stroke: stroke(x, 1) }, strokes);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_stack() {
+        let mut history = History::default();
+        let mut strokes = Vec::new();
+        assert!(!history.can_undo() && !history.can_redo());
+
+        add(&mut history, &mut strokes, 1.0);
+        add(&mut history, &mut strokes, 2.0);
+        add(&mut history, &mut strokes, 3.0);
+
+        assert!(history.undo(&mut strokes));
+        assert!(history.undo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![1.0]);
+        assert!(history.can_redo());
+
+        assert!(history.redo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![1.0, 2.0]);
+
+        assert!(history.undo(&mut strokes));
+        assert!(history.undo(&mut strokes));
+        assert!(!history.undo(&mut strokes));
+        assert!(strokes.is_empty());
+
+        while history.redo(&mut strokes) {}
+        assert_eq!(xs(&strokes), vec![1.0, 2.0, 3.0]);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut history = History::default();
+        let mut strokes = Vec::new();
+        add(&mut history, &mut strokes, 1.0);
+        add(&mut history, &mut strokes, 2.0);
+        history.undo(&mut strokes);
+        assert!(history.can_redo());
+
+        add(&mut history, &mut strokes, 9.0);
+        assert!(!history.can_redo());
+        assert!(!history.redo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![1.0, 9.0]);
+    }
+
+    #[test]
+    fn every_edit_reverts_exactly() {
+        let mut strokes: Vec<Stroke> = (0..4).map(|i| stroke(i as f64 * 10.0, 2)).collect();
+        let original = strokes.clone();
+        let mut history = History::default();
+
+        history.apply(BoardEdit::remove(&strokes, &[2, 0, 2, 7]).unwrap(), &mut strokes);
+        assert_eq!(xs(&strokes), vec![10.0, 30.0]);
+
+        let edit = BoardEdit::restyle(&strokes, &[0], |s| s.color = Color::Red).unwrap();
+        history.apply(edit, &mut strokes);
+        assert_eq!(strokes[0].color, Color::Red);
+        assert!(BoardEdit::restyle(&strokes, &[0], |s| s.color = Color::Red).is_none());
+
+        let changes = vec![(1, strokes[1].clone(), stroke(99.0, 3))];
+        history.apply(BoardEdit::Update { changes }, &mut strokes);
+        history.apply(BoardEdit::Move { indices: vec![0, 1], dx: 5.0, dy: -2.0 }, &mut strokes);
+        assert_eq!(xs(&strokes), vec![15.0, 104.0]);
+        assert_eq!(ys(&strokes), vec![-2.0, -2.0]);
+
+        history.apply(BoardEdit::clear(&strokes).unwrap(), &mut strokes);
+        assert!(strokes.is_empty());
+        assert!(BoardEdit::clear(&strokes).is_none());
+
+        while history.undo(&mut strokes) {}
+        assert_eq!(strokes, original);
+
+        while history.redo(&mut strokes) {}
+        assert!(strokes.is_empty());
+    }
+
+    #[test]
+    fn oldest_steps_are_evicted_by_count() {
+        let mut history = History::new(3, usize::MAX);
+        let mut strokes = Vec::new();
+        for x in 1..=5 {
+            add(&mut history, &mut strokes, x as f64);
+        }
+
+        let mut undone = 0;
+        while history.undo(&mut strokes) {
+            undone += 1;
+        }
+        assert_eq!(undone, 3);
+        assert_eq!(xs(&strokes), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn oldest_steps_are_evicted_by_cost() {
+        let mut history = History::new(100, 10);
+        let mut strokes = Vec::new();
+        for x in 0..3 {
+            history.apply(BoardEdit::Add { stroke: stroke(x as f64, 4) }, &mut strokes);
+        }
+        // 12 points > 10: the first add is dropped
+        assert!(history.undo(&mut strokes));
+        assert!(history.undo(&mut strokes));
+        assert!(!history.undo(&mut strokes));
+        assert_eq!(strokes.len(), 1);
+
+        // A single step over budget is still kept
+        let mut history = History::new(100, 10);
+        history.apply(BoardEdit::Add { stroke: stroke(0.0, 50) }, &mut strokes);
+        assert!(history.undo(&mut strokes));
+    }
+
+    #[test]
+    fn drag_moves_merge_into_one_step() {
+        let mut strokes = vec![stroke(0.0, 1), stroke(10.0, 1)];
+        let mut history = History::default();
+
+        history.begin();
+        for _ in 0..10 {
+            history.apply(BoardEdit::Move { indices: vec![0, 1], dx: 1.0, dy: 2.0 }, &mut strokes);
+        }
+        // Nested begin joins the open transaction
+        history.begin();
+        history.apply(BoardEdit::Move { indices: vec![0, 1], dx: 1.0, dy: 2.0 }, &mut strokes);
+        history.commit();
+        assert_eq!(xs(&strokes), vec![11.0, 21.0]);
+        assert_eq!(ys(&strokes), vec![22.0, 22.0]);
+
+        assert!(history.undo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![0.0, 10.0]);
+        assert_eq!(ys(&strokes), vec![0.0, 0.0]);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn moves_of_different_strokes_do_not_merge() {
+        let mut strokes = vec![stroke(0.0, 1), stroke(10.0, 1)];
+        let mut history = History::default();
+
+        history.begin();
+        history.apply(BoardEdit::Move { indices: vec![0], dx: 1.0, dy: 0.0 }, &mut strokes);
+        history.apply(BoardEdit::Move { indices: vec![1], dx: 1.0, dy: 0.0 }, &mut strokes);
+        history.apply(BoardEdit::Add { stroke: stroke(50.0, 1) }, &mut strokes);
+        history.commit();
+        // Outside a transaction moves stay separate steps
+        history.apply(BoardEdit::Move { indices: vec![0], dx: 1.0, dy: 0.0 }, &mut strokes);
+        history.apply(BoardEdit::Move { indices: vec![0], dx: 1.0, dy: 0.0 }, &mut strokes);
+
+        assert!(history.undo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![2.0, 11.0, 50.0]);
+        assert!(history.undo(&mut strokes));
+        assert!(history.undo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![0.0, 10.0]);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn undo_closes_open_transaction() {
+        let mut strokes = Vec::new();
+        let mut history = History::default();
+        history.begin();
+        add(&mut history, &mut strokes, 1.0);
+        add(&mut history, &mut strokes, 2.0);
+        assert!(history.can_undo());
+
+        assert!(history.undo(&mut strokes));
+        assert!(strokes.is_empty());
+        assert!(history.redo(&mut strokes));
+        assert_eq!(xs(&strokes), vec![1.0, 2.0]);
+
+        history.clear();
+        assert!(!history.can_undo() && !history.can_redo());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::types::{ChartInvocation, Stroke};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Revisions kept per board; older ones are pruned on save.
+pub const MAX_REVISIONS: i32 = 50;
+
+/// Longest board name accepted (matches the column size).
+pub const MAX_BOARD_NAME: usize = 200;
+
+/// Chart widgets accepted per board.
+pub const MAX_BOARD_CHARTS: usize = 20;
+
+/// Board list entry, without the strokes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub revision: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Board {
+    pub id: Uuid,
+    pub name: String,
+    pub strokes: Vec<Stroke>,
+    /// Live chart widgets placed on the board
+    #[serde(default)]
+    pub charts: Vec<ChartInvocation>,
+    /// Bumped on every save; 0 for a board that was never saved
+    pub revision: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Board {
+    pub fn summary(&self) -> BoardSummary {
+        BoardSummary {
+            id: self.id,
+            name: self.name.clone(),
+            revision: self.revision,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// Entry in a board's history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardRevision {
+    pub revision: i32,
+    pub stroke_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Strokes and charts of one saved revision, for previewing it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardRevisionContent {
+    pub revision: i32,
+    pub strokes: Vec<Stroke>,
+    #[serde(default)]
+    pub charts: Vec<ChartInvocation>,
+}
+
+/// Trimmed board name, or an error message for the user.
+pub fn validate_board_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Board name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_BOARD_NAME {
+        return Err(format!("Board name must be at most {} characters", MAX_BOARD_NAME));
+    }
+    Ok(name.to_string())
+}
+
+/// Check chart widgets before they are stored: a bounded number, plain
+/// symbol/timeframe/indicator names and a position on the board.
+pub fn validate_board_charts(charts: &[ChartInvocation]) -> Result<(), String> {
+    if charts.len() > MAX_BOARD_CHARTS {
+        return Err(format!("A board can hold at most {} charts", MAX_BOARD_CHARTS));
+    }
+    let plain = |text: &str, max: usize| {
+        !text.is_empty() && text.len() <= max && text.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+    for chart in charts {
+        if !plain(&chart.symbol, 20) {
+            return Err(format!("Invalid chart symbol '{}'", chart.symbol));
+        }
+        if !plain(&chart.timeframe, 4) {
+            return Err(format!("Invalid chart timeframe '{}'", chart.timeframe));
+        }
+        if chart.indicators.len() > 8 || !chart.indicators.iter().all(|i| plain(i, 16)) {
+            return Err(format!("Invalid indicators on the {} chart", chart.symbol));
+        }
+        let p = &chart.position;
+        let in_range = |v: f64| v.is_finite() && (0.0..=100.0).contains(&v);
+        if !(in_range(p.x) && in_range(p.y) && in_range(p.width) && in_range(p.height)) {
+            return Err(format!("Chart position of {} is off the board", chart.symbol));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,601 @@
+use crate::session::CurrentUser;
+use crate::types::{Stroke, Tool};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Strokes kept per room before new ones are rejected
+pub const MAX_ROOM_STROKES: usize = 5_000;
+/// Points accepted in a single stroke
+pub const MAX_STROKE_POINTS: usize = 10_000;
+/// Characters accepted in a text stroke
+pub const MAX_TEXT_LEN: usize = 2_000;
+
+// =====================
+// Protocol
+// =====================
+
+/// Globally unique stroke id: the participant that drew it plus that
+/// participant's own counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StrokeId {
+    pub participant: u64,
+    pub n: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomStroke {
+    pub id: StrokeId,
+    /// User who drew it; `Undo` only removes the caller's own strokes
+    pub author: Uuid,
+    pub stroke: Stroke,
+}
+
+/// Client -> server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ClientOp {
+    AddStroke { id: StrokeId, stroke: Stroke },
+    /// Remove the caller's most recent stroke still on the board
+    Undo,
+    Clear,
+    /// Pointer position in board coordinates
+    Cursor { x: f64, y: f64 },
+}
+
+/// A change to the shared stroke list, as sequenced by the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RoomOp {
+    Add { stroke: RoomStroke },
+    Remove { id: StrokeId },
+    Clear,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Participant {
+    pub id: u64,
+    pub name: String,
+}
+
+/// Server -> client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMsg {
+    /// Sent on join (and after a resync): who you are and the full state
+    Welcome {
+        participant: u64,
+        state: RoomState,
+        participants: Vec<Participant>,
+    },
+    /// Every client applies these in `seq` order
+    Op { seq: u64, op: RoomOp },
+    Cursor { participant: u64, name: String, x: f64, y: f64 },
+    Joined { participant: Participant },
+    Left { participant: u64 },
+    /// A submitted op was refused; `id` names the rejected stroke
+    Rejected { id: Option<StrokeId>, reason: String },
+}
+
+// =====================
+// Room state (shared by hub and browser)
+// =====================
+
+/// The stroke list of a room after applying ops `1..=seq`. The server
+/// assigns one total order to all ops, so every client that applies the
+/// same sequence ends with identical strokes regardless of timing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoomState {
+    pub seq: u64,
+    pub strokes: Vec<RoomStroke>,
+}
+
+impl RoomState {
+    /// Apply op number `seq`. Already-applied ops are ignored, so
+    /// replaying after a resync is harmless. Returns whether it applied.
+    pub fn apply(&mut self, seq: u64, op: &RoomOp) -> bool {
+        if seq <= self.seq {
+            return false;
+        }
+        match op {
+            RoomOp::Add { stroke } => {
+                if !self.strokes.iter().any(|s| s.id == stroke.id) {
+                    self.strokes.push(stroke.clone());
+                }
+            }
+            RoomOp::Remove { id } => self.strokes.retain(|s| s.id != *id),
+            RoomOp::Clear => self.strokes.clear(),
+        }
+        self.seq = seq;
+        true
+    }
+
+    /// The op an `Undo` from `author` turns into, if they have a stroke left.
+    pub fn undo_op(&self, author: Uuid) -> Option<RoomOp> {
+        self.strokes
+            .iter()
+            .rev()
+            .find(|s| s.author == author)
+            .map(|s| RoomOp::Remove { id: s.id })
+    }
+
+    /// State of a newly opened room holding a board's saved strokes.
+    /// They are attributed to participant 0 and no author, so nobody's
+    /// `Undo` removes them.
+    pub fn seeded(strokes: Vec<Stroke>) -> Self {
+        RoomState {
+            seq: 0,
+            strokes: strokes
+                .into_iter()
+                .enumerate()
+                .map(|(i, stroke)| RoomStroke {
+                    id: StrokeId { participant: 0, n: i as u64 + 1 },
+                    author: Uuid::nil(),
+                    stroke,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn contains(&self, id: StrokeId) -> bool {
+        self.strokes.iter().any(|s| s.id == id)
+    }
+}
+
+// =====================
+// Hub (server)
+// =====================
+
+struct Room {
+    state: RoomState,
+    participants: HashMap<u64, Participant>,
+    tx: broadcast::Sender<ServerMsg>,
+}
+
+/// Process-wide set of whiteboard rooms, one per saved board. All mutations of a room happen
+/// under one lock and are broadcast before it is released, so subscribers
+/// see ops in exactly the order they were sequenced.
+pub struct WhiteboardHub {
+    rooms: Mutex<HashMap<Uuid, Room>>,
+    next_participant: AtomicU64,
+}
+
+impl WhiteboardHub {
+    pub fn new() -> Self {
+        WhiteboardHub {
+            rooms: Mutex::new(HashMap::new()),
+            next_participant: AtomicU64::new(1),
+        }
+    }
+
+    /// Add a participant and return their id, the welcome message carrying
+    /// the current state, and the room's broadcast stream. A room that is
+    /// not open yet starts from `saved`, the board's stored strokes;
+    /// otherwise `saved` is ignored.
+    pub fn join(
+        &self,
+        board: Uuid,
+        name: String,
+        saved: Vec<Stroke>,
+    ) -> (u64, ServerMsg, broadcast::Receiver<ServerMsg>) {
+        let id = self.next_participant.fetch_add(1, Ordering::Relaxed);
+        let mut rooms = self.rooms.lock().unwrap_or_else(|e| e.into_inner());
+        let room = rooms.entry(board).or_insert_with(|| Room {
+            state: RoomState::seeded(saved),
+            participants: HashMap::new(),
+            tx: broadcast::channel(512).0,
+        });
+
+        let participant = Participant { id, name };
+        let _ = room.tx.send(ServerMsg::Joined {
+            participant: participant.clone(),
+        });
+        room.participants.insert(id, participant);
+
+        let rx = room.tx.subscribe();
+        (id, Self::welcome(room, id), rx)
+    }
+
+    /// Fresh `Welcome` for a participant whose stream lagged.
+    pub fn resync(&self, board: Uuid, participant: u64) -> Option<ServerMsg> {
+        let rooms = self.rooms.lock().unwrap_or_else(|e| e.into_inner());
+        rooms.get(&board).map(|room| Self::welcome(room, participant))
+    }
+
+    fn welcome(room: &Room, participant: u64) -> ServerMsg {
+        let mut participants: Vec<Participant> = room.participants.values().cloned().collect();
+        participants.sort_by_key(|p| p.id);
+        ServerMsg::Welcome {
+            participant,
+            state: room.state.clone(),
+            participants,
+        }
+    }
+
+    /// Sequence and broadcast one op from `participant`. Returns a
+    /// rejection to send back to that participant only.
+    pub fn submit(&self, board: Uuid, participant: u64, author: Uuid, op: ClientOp) -> Option<ServerMsg> {
+        let mut rooms = self.rooms.lock().unwrap_or_else(|e| e.into_inner());
+        let room = rooms.get_mut(&board)?;
+
+        let room_op = match op {
+            ClientOp::Cursor { x, y } => {
+                let name = room.participants.get(&participant)?.name.clone();
+                let _ = room.tx.send(ServerMsg::Cursor { participant, name, x, y });
+                return None;
+            }
+            ClientOp::AddStroke { id, stroke } => {
+                let rejected = |reason: &str| {
+                    Some(ServerMsg::Rejected {
+                        id: Some(id),
+                        reason: reason.to_string(),
+                    })
+                };
+                if id.participant != participant {
+                    return rejected("stroke id belongs to another participant");
+                }
+                if stroke.tool == Tool::Select {
+                    return rejected("select is not a drawing tool");
+                }
+                if stroke.points.is_empty() || stroke.points.len() > MAX_STROKE_POINTS {
+                    return rejected(&format!("stroke must have 1-{} points", MAX_STROKE_POINTS));
+                }
+                if stroke.text.as_ref().is_some_and(|t| t.chars().count() > MAX_TEXT_LEN) {
+                    return rejected(&format!("text is longer than {} characters", MAX_TEXT_LEN));
+                }
+                if room.state.strokes.len() >= MAX_ROOM_STROKES {
+                    return rejected("room is full");
+                }
+                if room.state.contains(id) {
+                    return None;
+                }
+                RoomOp::Add {
+                    stroke: RoomStroke { id, author, stroke },
+                }
+            }
+            ClientOp::Undo => room.state.undo_op(author)?,
+            ClientOp::Clear => RoomOp::Clear,
+        };
+
+        let seq = room.state.seq + 1;
+        room.state.apply(seq, &room_op);
+        let _ = room.tx.send(ServerMsg::Op { seq, op: room_op });
+        None
+    }
+
+    /// Remove a participant; the room is dropped once empty.
+    pub fn leave(&self, board: Uuid, participant: u64) {
+        let mut rooms = self.rooms.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(room) = rooms.get_mut(&board) {
+            room.participants.remove(&participant);
+            if room.participants.is_empty() {
+                rooms.remove(&board);
+            } else {
+                let _ = room.tx.send(ServerMsg::Left { participant });
+            }
+        }
+    }
+}
+
+impl Default for WhiteboardHub {
+    fn default() -> Self {
+        WhiteboardHub::new()
+    }
+}
+
+static HUB: OnceLock<WhiteboardHub> = OnceLock::new();
+
+pub fn whiteboard_hub() -> &'static WhiteboardHub {
+    HUB.get_or_init(WhiteboardHub::new)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoomQuery {
+    pub board: Uuid,
+}
+
+/// `GET /ws/whiteboard?board=<id>` for the board's owner and the users it
+/// is shared with: a `Welcome` with the room's current state, then every
+/// sequenced op. A room opens with the board's saved strokes, never with
+/// content offered by a client.
+pub async fn whiteboard_ws_handler(
+    ws: axum::extract::ws::WebSocketUpgrade,
+    CurrentUser(user): CurrentUser,
+    axum::extract::Query(query): axum::extract::Query<RoomQuery>,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    let Some(db) = crate::server::database() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Database not available").into_response();
+    };
+    // Loaded on every join: the room may close before this socket joins it
+    let saved = match db.shared_board_strokes(&user.id, &query.board).await {
+        Ok(Some(strokes)) => strokes,
+        Ok(None) => return (StatusCode::FORBIDDEN, "Board not shared with you").into_response(),
+        Err(e) => {
+            tracing::error!("Board access check failed: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    };
+
+    let board = query.board;
+    ws.on_upgrade(move |socket| whiteboard_socket(socket, board, user.id, user.username, saved))
+}
+
+async fn whiteboard_socket(
+    mut socket: axum::extract::ws::WebSocket,
+    room: Uuid,
+    user_id: Uuid,
+    username: String,
+    saved: Vec<Stroke>,
+) {
+    use axum::extract::ws::Message as WsMessage;
+
+    async fn send(socket: &mut axum::extract::ws::WebSocket, message: &ServerMsg) -> bool {
+        match serde_json::to_string(message) {
+            Ok(text) => socket.send(WsMessage::Text(text)).await.is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    let hub = whiteboard_hub();
+    let (participant, welcome, mut updates) = hub.join(room, username, saved);
+
+    if send(&mut socket, &welcome).await {
+        loop {
+            tokio::select! {
+                update = updates.recv() => match update {
+                    // Own cursor echoes are noise
+                    Ok(ServerMsg::Cursor { participant: from, .. }) if from == participant => {}
+                    Ok(message) => {
+                        if !send(&mut socket, &message).await {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        // Missed ops: start over from the current state
+                        match hub.resync(room, participant) {
+                            Some(welcome) if send(&mut socket, &welcome).await => {}
+                            _ => break,
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                incoming = socket.recv() => match incoming {
+                    Some(Ok(WsMessage::Text(text))) => match serde_json::from_str::<ClientOp>(&text) {
+                        Ok(op) => {
+                            if let Some(rejection) = hub.submit(room, participant, user_id, op) {
+                                if !send(&mut socket, &rejection).await {
+                                    break;
+                                }
+                            }
+                        }
+                        Err(e) => tracing::debug!("Bad whiteboard op: {}", e),
+                    },
+                    Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    hub.leave(room, participant);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Color, StrokePoint};
+
+    fn stroke(x: f64) -> Stroke {
+        Stroke {
+            points: vec![StrokePoint { x, y: x, pressure: 0.5 }],
+            color: Color::White,
+            thickness: 2.0,
+            tool: Tool::Pen,
+            text: None,
+        }
+    }
+
+    fn add(participant: u64, n: u64) -> ClientOp {
+        ClientOp::AddStroke {
+            id: StrokeId { participant, n },
+            stroke: stroke(n as f64),
+        }
+    }
+
+    /// Apply every queued op to `state` the way a browser does.
+    fn drain(rx: &mut broadcast::Receiver<ServerMsg>, state: &mut RoomState) {
+        while let Ok(message) = rx.try_recv() {
+            if let ServerMsg::Op { seq, op } = message {
+                state.apply(seq, &op);
+            }
+        }
+    }
+
+    fn welcome_state(message: ServerMsg) -> RoomState {
+        match message {
+            ServerMsg::Welcome { state, .. } => state,
+            other => panic!("expected welcome, got {:?}", other),
+        }
+    }
+
+    fn ids(state: &RoomState) -> Vec<StrokeId> {
+        state.strokes.iter().map(|s| s.id).collect()
+    }
+
+    #[test]
+    fn interleaved_ops_converge() {
+        let hub = WhiteboardHub::new();
+        let board = Uuid::new_v4();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let (a, welcome_a, mut rx_a) = hub.join(board, "alice".into(), Vec::new());
+        let (b, welcome_b, mut rx_b) = hub.join(board, "bob".into(), Vec::new());
+        let mut state_a = welcome_state(welcome_a);
+        let mut state_b = welcome_state(welcome_b);
+
+        hub.submit(board, a, alice, add(a, 1));
+        hub.submit(board, b, bob, add(b, 1));
+        hub.submit(board, a, alice, add(a, 2));
+        // Duplicate submits are not sequenced twice
+        hub.submit(board, b, bob, add(b, 1));
+        hub.submit(board, b, bob, ClientOp::Undo);
+        hub.submit(board, b, bob, add(b, 2));
+        hub.submit(board, a, alice, ClientOp::Cursor { x: 1.0, y: 2.0 });
+
+        // A third participant joins mid-stream
+        let (c, welcome_c, mut rx_c) = hub.join(board, "carol".into(), Vec::new());
+        let mut state_c = welcome_state(welcome_c);
+        hub.submit(board, c, Uuid::new_v4(), add(c, 1));
+
+        drain(&mut rx_a, &mut state_a);
+        drain(&mut rx_b, &mut state_b);
+        drain(&mut rx_c, &mut state_c);
+
+        let hub_state = welcome_state(hub.resync(board, a).unwrap());
+        assert_eq!(hub_state.seq, 6);
+        assert_eq!(
+            ids(&hub_state),
+            vec![
+                StrokeId { participant: a, n: 1 },
+                StrokeId { participant: a, n: 2 },
+                StrokeId { participant: b, n: 2 },
+                StrokeId { participant: c, n: 1 },
+            ]
+        );
+        for state in [&state_a, &state_b, &state_c] {
+            assert_eq!(state.seq, hub_state.seq);
+            assert_eq!(ids(state), ids(&hub_state));
+        }
+    }
+
+    #[test]
+    fn replayed_ops_are_ignored() {
+        let mut state = RoomState::default();
+        let op = RoomOp::Add {
+            stroke: RoomStroke {
+                id: StrokeId { participant: 1, n: 1 },
+                author: Uuid::nil(),
+                stroke: stroke(1.0),
+            },
+        };
+        assert!(state.apply(1, &op));
+        assert!(state.apply(2, &RoomOp::Clear));
+        assert!(!state.apply(1, &op));
+        assert!(state.strokes.is_empty());
+        assert_eq!(state.seq, 2);
+    }
+
+    #[test]
+    fn lagged_receiver_resyncs_and_continues() {
+        let hub = WhiteboardHub::new();
+        let board = Uuid::new_v4();
+        let author = Uuid::new_v4();
+        let (slow, _, mut rx) = hub.join(board, "slow".into(), Vec::new());
+        let (fast, _, _fast_rx) = hub.join(board, "fast".into(), Vec::new());
+
+        for n in 1..=600 {
+            hub.submit(board, fast, author, add(fast, n));
+        }
+        assert!(matches!(rx.try_recv(), Err(broadcast::error::TryRecvError::Lagged(_))));
+
+        // What the socket task does on `Lagged`: start over from a fresh welcome
+        let mut state = welcome_state(hub.resync(board, slow).unwrap());
+        assert_eq!(state.seq, 600);
+        assert_eq!(state.strokes.len(), 600);
+
+        // Ops still buffered from before the resync are skipped...
+        drain(&mut rx, &mut state);
+        assert_eq!(state.seq, 600);
+        assert_eq!(state.strokes.len(), 600);
+
+        // ...and later ones apply on top of the fresh state
+        hub.submit(board, fast, author, ClientOp::Clear);
+        drain(&mut rx, &mut state);
+        assert_eq!(state.seq, 601);
+        assert!(state.strokes.is_empty());
+    }
+
+    #[test]
+    fn strokes_are_owned_by_their_participant() {
+        let hub = WhiteboardHub::new();
+        let board = Uuid::new_v4();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let (a, _, _rx_a) = hub.join(board, "alice".into(), Vec::new());
+        let (b, _, _rx_b) = hub.join(board, "bob".into(), Vec::new());
+
+        let rejection = hub.submit(board, b, bob, add(a, 1));
+        assert!(matches!(rejection, Some(ServerMsg::Rejected { id: Some(_), .. })));
+
+        hub.submit(board, a, alice, add(a, 1));
+        // Bob has nothing to undo, so Alice's stroke stays
+        hub.submit(board, b, bob, ClientOp::Undo);
+        let state = welcome_state(hub.resync(board, a).unwrap());
+        assert_eq!(ids(&state), vec![StrokeId { participant: a, n: 1 }]);
+
+        let empty = ClientOp::AddStroke {
+            id: StrokeId { participant: a, n: 2 },
+            stroke: Stroke { points: Vec::new(), ..stroke(0.0) },
+        };
+        assert!(hub.submit(board, a, alice, empty).is_some());
+
+        let select = ClientOp::AddStroke {
+            id: StrokeId { participant: a, n: 3 },
+            stroke: Stroke { tool: Tool::Select, ..stroke(0.0) },
+        };
+        assert!(hub.submit(board, a, alice, select).is_some());
+        assert_eq!(welcome_state(hub.resync(board, a).unwrap()).strokes.len(), 1);
+    }
+
+    #[test]
+    fn rooms_are_separate_and_dropped_when_empty() {
+        let hub = WhiteboardHub::new();
+        let (one, two) = (Uuid::new_v4(), Uuid::new_v4());
+        let author = Uuid::new_v4();
+        let (a, _, _rx_a) = hub.join(one, "alice".into(), Vec::new());
+        let (b, _, _rx_b) = hub.join(two, "bob".into(), Vec::new());
+
+        hub.submit(one, a, author, add(a, 1));
+        assert_eq!(welcome_state(hub.resync(two, b).unwrap()).seq, 0);
+        // Ops for a room the participant is not in go nowhere
+        assert!(hub.submit(Uuid::new_v4(), a, author, add(a, 2)).is_none());
+
+        hub.leave(one, a);
+        assert!(hub.resync(one, a).is_none());
+        assert!(hub.resync(two, b).is_some());
+    }
+
+    #[test]
+    fn rooms_open_with_the_saved_strokes() {
+        let hub = WhiteboardHub::new();
+        let board = Uuid::new_v4();
+        let (owner, guest) = (Uuid::new_v4(), Uuid::new_v4());
+        let (g, welcome, _rx_g) = hub.join(board, "guest".into(), vec![stroke(1.0), stroke(2.0)]);
+        let state = welcome_state(welcome);
+        assert_eq!(state.seq, 0);
+        assert_eq!(
+            state.strokes.iter().map(|s| s.stroke.clone()).collect::<Vec<_>>(),
+            vec![stroke(1.0), stroke(2.0)]
+        );
+
+        // Once open, later joiners cannot replace the content
+        let (o, welcome, _rx_o) = hub.join(board, "owner".into(), vec![stroke(9.0)]);
+        assert_eq!(ids(&welcome_state(welcome)), ids(&state));
+
+        // Saved strokes are nobody's to undo, and new ids do not collide
+        hub.submit(board, g, guest, ClientOp::Undo);
+        hub.submit(board, o, owner, add(o, 1));
+        hub.submit(board, o, owner, ClientOp::Undo);
+        assert_eq!(ids(&welcome_state(hub.resync(board, g).unwrap())), ids(&state));
+
+        // A room that emptied reopens from what is saved then
+        hub.leave(board, g);
+        hub.leave(board, o);
+        let (_, welcome, _rx) = hub.join(board, "owner".into(), vec![stroke(3.0)]);
+        assert_eq!(welcome_state(welcome).strokes.len(), 1);
+    }
+}
@@ -167,7 +167,7 @@ pub fn LoginPage() -> impl IntoView {
                                     match login(username_val, password_val).await {
                                         Ok(response) => {
                                             if response.success {
-                                                navigate(&"/admin/dashboard".to_string(), Default::default());
+                                                navigate("/admin/dashboard", Default::default());
                                             } else {
                                                 set_error.set(response.message);
                                                 set_is_loading.set(false);
@@ -207,7 +207,7 @@ pub fn LoginPage() -> impl IntoView {
                             match login(username_val, password_val).await {
                                 Ok(response) => {
                                     if response.success {
-                                        navigate_clone(&"/admin/dashboard".to_string(), Default::default());
+                                        navigate_clone("/admin/dashboard", Default::default());
                                     } else {
                                         set_error.set(response.message);
                                         set_is_loading.set(false);
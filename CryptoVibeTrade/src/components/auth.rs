@@ -72,7 +72,7 @@ pub fn RegisterPage() -> impl IntoView {
                         set_success.set(response.message);
                         set_is_loading.set(false);
                         // Redirect to login immediately
-                        navigate(&"/auth/login".to_string(), Default::default());
+                        navigate("/auth/login", Default::default());
                     } else {
                         set_error.set(response.message);
                         set_is_loading.set(false);
@@ -460,9 +460,9 @@ pub fn LoginPage() -> impl IntoView {
                         if response.success {
                             set_is_loading.set(false);
                             if response.is_admin.unwrap_or(false) {
-                                navigate(&"/admin/dashboard".to_string(), Default::default());
+                                navigate("/admin/dashboard", Default::default());
                             } else {
-                                navigate(&"/".to_string(), Default::default());
+                                navigate("/", Default::default());
                             }
                         } else {
                             set_error.set(response.message);
@@ -1,8 +1,528 @@
 use leptos::*;
 use leptos_meta::Style;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
-use wasm_bindgen::JsValue;
-use crate::types::{Candle};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, MouseEvent, WheelEvent, WebSocket};
+use wasm_bindgen::JsCast;
+use crate::indicators::{
+    compute, Bollinger, BollingerValue, Ema, Ichimoku, Indicator, Macd, Rsi, Sma,
+};
+use crate::types::{upsert_candle, Candle, MarketMessage};
+
+/// Candles kept client-side per chart (the server snapshot is the source
+/// of history).
+const MAX_CANDLES: usize = 500;
+
+const BULL_COLOR: &str = "#00ff88";
+const BEAR_COLOR: &str = "#ff3333";
+const GRID_COLOR: &str = "#1a1a1a";
+const TEXT_COLOR: &str = "#888888";
+const ACCENT_COLOR: &str = "#ff6b35";
+const BACKGROUND: &str = "#0a0a0a";
+
+const PRICE_AXIS_WIDTH: f64 = 72.0;
+const TIME_AXIS_HEIGHT: f64 = 22.0;
+/// Share of the plot height used by the volume sub-pane
+const VOLUME_PANE_RATIO: f64 = 0.22;
+const MIN_VISIBLE: f64 = 10.0;
+
+// =====================
+// VIEWPORT + LAYOUT
+// =====================
+
+/// Which candles are on screen: `visible` bars ending `offset` bars before
+/// the newest one. Both are fractional so zoom and pan stay smooth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartView {
+    pub visible: f64,
+    pub offset: f64,
+}
+
+impl Default for ChartView {
+    fn default() -> Self {
+        ChartView {
+            visible: 60.0,
+            offset: 0.0,
+        }
+    }
+}
+
+impl ChartView {
+    /// Index range `[start, end)` of the visible candles.
+    pub fn range(&self, len: usize) -> (usize, usize) {
+        let end = (len as f64 - self.offset.max(0.0)).round().clamp(0.0, len as f64) as usize;
+        let start = (end as f64 - self.visible.round()).max(0.0) as usize;
+        (start, end)
+    }
+
+    /// Zoom by `factor` (< 1 zooms in) keeping the bar under `anchor`
+    /// (0 = left edge, 1 = right edge of the plot) in place.
+    pub fn zoom(&mut self, factor: f64, anchor: f64, len: usize) {
+        let anchor = anchor.clamp(0.0, 1.0);
+        let max_visible = (len as f64).max(MIN_VISIBLE);
+        let new_visible = (self.visible * factor).clamp(MIN_VISIBLE, max_visible);
+
+        // Bars between the anchor and the right edge scale with the zoom
+        let right_of_anchor = (1.0 - anchor) * self.visible;
+        let new_right_of_anchor = (1.0 - anchor) * new_visible;
+        self.offset += right_of_anchor - new_right_of_anchor;
+        self.visible = new_visible;
+        self.clamp(len);
+    }
+
+    /// Shift by `bars` (positive = towards older candles).
+    pub fn pan(&mut self, bars: f64, len: usize) {
+        self.offset += bars;
+        self.clamp(len);
+    }
+
+    fn clamp(&mut self, len: usize) {
+        let max_offset = (len as f64 - MIN_VISIBLE).max(0.0);
+        self.offset = self.offset.clamp(0.0, max_offset);
+    }
+}
+
+/// Pixel geometry of the chart panes for a canvas size.
+#[derive(Debug, Clone, Copy)]
+pub struct ChartLayout {
+    pub width: f64,
+    pub height: f64,
+    pub plot_right: f64,
+    pub price_top: f64,
+    pub price_bottom: f64,
+    pub volume_top: f64,
+    pub volume_bottom: f64,
+}
+
+impl ChartLayout {
+    pub fn new(width: f64, height: f64) -> Self {
+        let plot_bottom = (height - TIME_AXIS_HEIGHT).max(0.0);
+        let volume_height = plot_bottom * VOLUME_PANE_RATIO;
+        ChartLayout {
+            width,
+            height,
+            plot_right: (width - PRICE_AXIS_WIDTH).max(0.0),
+            price_top: 8.0,
+            price_bottom: plot_bottom - volume_height - 6.0,
+            volume_top: plot_bottom - volume_height,
+            volume_bottom: plot_bottom,
+        }
+    }
+
+    pub fn bar_width(&self, view: &ChartView) -> f64 {
+        self.plot_right / view.visible.max(1.0)
+    }
+}
+
+/// Round a raw tick step to 1, 2 or 5 times a power of ten.
+fn nice_step(raw: f64) -> f64 {
+    if raw <= 0.0 || !raw.is_finite() {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+    let nice = if normalized <= 1.0 {
+        1.0
+    } else if normalized <= 2.0 {
+        2.0
+    } else if normalized <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+fn format_price(price: f64) -> String {
+    if price >= 1000.0 {
+        format!("{:.1}", price)
+    } else if price >= 1.0 {
+        format!("{:.2}", price)
+    } else {
+        format!("{:.6}", price)
+    }
+}
+
+/// UTC "HH:MM" for a candle open time. Accepts seconds or milliseconds.
+fn format_time(time: i64) -> String {
+    let secs = if time > 10_000_000_000 { time / 1000 } else { time };
+    let day_secs = secs.rem_euclid(86_400);
+    format!("{:02}:{:02}", day_secs / 3600, (day_secs % 3600) / 60)
+}
+
+// =====================
+// INDICATOR OVERLAYS
+// =====================
+
+/// Indicator names `indicator_lines` understands, for pickers.
+pub const OVERLAY_INDICATORS: &[&str] = &["ema", "sma", "bollinger", "ichimoku", "rsi", "macd"];
+
+/// Where an overlay line is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlayPane {
+    /// On the price scale, over the candles
+    Price,
+    /// Over the volume pane on its own scale; `None` fits the visible values
+    Lower(Option<(f64, f64)>),
+}
+
+/// One indicator line, with a value (or gap) per candle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayLine {
+    pub label: String,
+    pub color: &'static str,
+    pub pane: OverlayPane,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Picks one value out of an indicator's output
+type Pick<T, R> = fn(&T) -> R;
+
+fn series<T>(values: &[Option<T>], pick: impl Fn(&T) -> f64) -> Vec<Option<f64>> {
+    values.iter().map(|v| v.as_ref().map(&pick)).collect()
+}
+
+/// Lines for the named indicators ("ema", "sma", "bollinger", "ichimoku",
+/// "rsi", "macd") over `candles`, with default periods. Volume is always
+/// shown, and unknown names are skipped.
+pub fn indicator_lines(candles: &[Candle], names: &[String]) -> Vec<OverlayLine> {
+    let mut lines = Vec::new();
+    for name in names {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ema" => {
+                let mut ema = Ema::default();
+                lines.push(OverlayLine {
+                    label: ema.name(),
+                    color: "#ffd166",
+                    pane: OverlayPane::Price,
+                    values: compute(&mut ema, candles),
+                });
+            }
+            "sma" => {
+                let mut sma = Sma::new(50);
+                lines.push(OverlayLine {
+                    label: sma.name(),
+                    color: "#4cc9f0",
+                    pane: OverlayPane::Price,
+                    values: compute(&mut sma, candles),
+                });
+            }
+            "bollinger" | "bb" => {
+                let mut bollinger = Bollinger::default();
+                let values = compute(&mut bollinger, candles);
+                let bands: [(&str, Pick<BollingerValue, f64>); 3] =
+                    [("upper", |v| v.upper), ("middle", |v| v.middle), ("lower", |v| v.lower)];
+                for (band, pick) in bands {
+                    lines.push(OverlayLine {
+                        label: format!("{} {}", bollinger.name(), band),
+                        color: "#b388ff",
+                        pane: OverlayPane::Price,
+                        values: series(&values, pick),
+                    });
+                }
+            }
+            "ichimoku" => {
+                let mut ichimoku = Ichimoku::default();
+                let values = compute(&mut ichimoku, candles);
+                lines.push(OverlayLine {
+                    label: "Tenkan".to_string(),
+                    color: "#4cc9f0",
+                    pane: OverlayPane::Price,
+                    values: series(&values, |v| v.tenkan),
+                });
+                lines.push(OverlayLine {
+                    label: "Kijun".to_string(),
+                    color: "#ff6b9d",
+                    pane: OverlayPane::Price,
+                    values: series(&values, |v| v.kijun),
+                });
+                // The cloud edges that project onto each candle
+                lines.push(OverlayLine {
+                    label: "Span A".to_string(),
+                    color: "#00ff88",
+                    pane: OverlayPane::Price,
+                    values: values.iter().map(|v| v.as_ref().and_then(|v| v.cloud_a)).collect(),
+                });
+                lines.push(OverlayLine {
+                    label: "Span B".to_string(),
+                    color: "#ff3333",
+                    pane: OverlayPane::Price,
+                    values: values.iter().map(|v| v.as_ref().and_then(|v| v.cloud_b)).collect(),
+                });
+            }
+            "rsi" => {
+                let mut rsi = Rsi::default();
+                lines.push(OverlayLine {
+                    label: rsi.name(),
+                    color: "#ffd166",
+                    pane: OverlayPane::Lower(Some((0.0, 100.0))),
+                    values: compute(&mut rsi, candles),
+                });
+            }
+            "macd" => {
+                let mut macd = Macd::default();
+                let values = compute(&mut macd, candles);
+                lines.push(OverlayLine {
+                    label: macd.name(),
+                    color: "#4cc9f0",
+                    pane: OverlayPane::Lower(None),
+                    values: series(&values, |v| v.macd),
+                });
+                lines.push(OverlayLine {
+                    label: "Signal".to_string(),
+                    color: "#ff6b9d",
+                    pane: OverlayPane::Lower(None),
+                    values: series(&values, |v| v.signal),
+                });
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+// =====================
+// DRAWING
+// =====================
+
+fn draw_chart(
+    ctx: &CanvasRenderingContext2d,
+    candles: &[Candle],
+    view: &ChartView,
+    layout: &ChartLayout,
+    crosshair: Option<(f64, f64)>,
+    overlays: &[OverlayLine],
+) {
+    ctx.set_fill_style_str(BACKGROUND);
+    ctx.fill_rect(0.0, 0.0, layout.width, layout.height);
+
+    let (start, end) = view.range(candles.len());
+    let visible = &candles[start..end];
+    if visible.is_empty() {
+        ctx.set_fill_style_str(TEXT_COLOR);
+        ctx.set_font("12px 'SF Mono', monospace");
+        ctx.set_text_align("center");
+        let _ = ctx.fill_text(
+            "No candle data available. Connect to data source.",
+            layout.width / 2.0,
+            layout.height / 2.0,
+        );
+        return;
+    }
+
+    // Autoscale to the visible candles and price overlays, with 5% headroom
+    let (mut low, mut high) = visible
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(l, h), c| (l.min(c.low), h.max(c.high)));
+    for line in overlays.iter().filter(|l| l.pane == OverlayPane::Price) {
+        for value in line.values.get(start..end).unwrap_or(&[]).iter().flatten() {
+            low = low.min(*value);
+            high = high.max(*value);
+        }
+    }
+    let pad = ((high - low) * 0.05).max(high.abs() * 1e-4);
+    low -= pad;
+    high += pad;
+    let max_volume = visible.iter().map(|c| c.volume).fold(0.0, f64::max);
+
+    let price_y = |price: f64| {
+        layout.price_top
+            + (high - price) / (high - low) * (layout.price_bottom - layout.price_top)
+    };
+    let volume_y = |volume: f64| {
+        if max_volume > 0.0 {
+            layout.volume_bottom - volume / max_volume * (layout.volume_bottom - layout.volume_top)
+        } else {
+            layout.volume_bottom
+        }
+    };
+    let bar_w = layout.bar_width(view);
+    // Right-align the newest visible bar when fewer bars than slots
+    let first_slot = view.visible - visible.len() as f64;
+    let bar_x = |i: usize| (first_slot + i as f64 + 0.5) * bar_w;
+
+    // Price grid + axis labels
+    ctx.set_font("10px 'SF Mono', monospace");
+    ctx.set_text_baseline("middle");
+    ctx.set_text_align("left");
+    ctx.set_line_width(1.0);
+    let step = nice_step((high - low) / 6.0);
+    let mut level = (low / step).ceil() * step;
+    while level <= high {
+        let y = price_y(level).round() + 0.5;
+        ctx.set_stroke_style_str(GRID_COLOR);
+        ctx.begin_path();
+        ctx.move_to(0.0, y);
+        ctx.line_to(layout.plot_right, y);
+        ctx.stroke();
+        ctx.set_fill_style_str(TEXT_COLOR);
+        let _ = ctx.fill_text(&format_price(level), layout.plot_right + 6.0, y);
+        level += step;
+    }
+
+    // Time axis labels, roughly every 90px
+    ctx.set_text_align("center");
+    ctx.set_text_baseline("top");
+    let label_every = ((90.0 / bar_w).ceil() as usize).max(1);
+    for (i, candle) in visible.iter().enumerate() {
+        if (start + i) % label_every == 0 {
+            let x = bar_x(i);
+            ctx.set_stroke_style_str(GRID_COLOR);
+            ctx.begin_path();
+            ctx.move_to(x.round() + 0.5, layout.price_top);
+            ctx.line_to(x.round() + 0.5, layout.volume_bottom);
+            ctx.stroke();
+            ctx.set_fill_style_str(TEXT_COLOR);
+            let _ = ctx.fill_text(&format_time(candle.time), x, layout.volume_bottom + 5.0);
+        }
+    }
+
+    // Candles: wick, body, volume bar
+    let body_w = (bar_w * 0.7).max(1.0);
+    for (i, candle) in visible.iter().enumerate() {
+        let x = bar_x(i);
+        let color = if candle.close >= candle.open { BULL_COLOR } else { BEAR_COLOR };
+        ctx.set_stroke_style_str(color);
+        ctx.set_fill_style_str(color);
+
+        ctx.begin_path();
+        ctx.move_to(x.round() + 0.5, price_y(candle.high));
+        ctx.line_to(x.round() + 0.5, price_y(candle.low));
+        ctx.stroke();
+
+        let top = price_y(candle.open.max(candle.close));
+        let bottom = price_y(candle.open.min(candle.close));
+        ctx.fill_rect(x - body_w / 2.0, top, body_w, (bottom - top).max(1.0));
+
+        ctx.set_global_alpha(0.35);
+        let vy = volume_y(candle.volume);
+        ctx.fill_rect(x - body_w / 2.0, vy, body_w, layout.volume_bottom - vy);
+        ctx.set_global_alpha(1.0);
+    }
+
+    // Indicator lines, broken where the indicator has no value
+    ctx.set_line_width(1.5);
+    for line in overlays {
+        let values = line.values.get(start..end).unwrap_or(&[]);
+        let (min, max, top, bottom) = match line.pane {
+            OverlayPane::Price => (low, high, layout.price_top, layout.price_bottom),
+            OverlayPane::Lower(range) => {
+                let (min, max) = range.unwrap_or_else(|| {
+                    values
+                        .iter()
+                        .flatten()
+                        .fold((f64::MAX, f64::MIN), |(l, h), v| (l.min(*v), h.max(*v)))
+                });
+                (min, max, layout.volume_top, layout.volume_bottom)
+            }
+        };
+        let span = if max > min { max - min } else { 1.0 };
+        let to_y = |v: f64| bottom - (v - min) / span * (bottom - top);
+        ctx.set_stroke_style_str(line.color);
+        ctx.begin_path();
+        let mut drawing = false;
+        for (i, value) in values.iter().enumerate() {
+            match value {
+                Some(v) if drawing => ctx.line_to(bar_x(i), to_y(*v)),
+                Some(v) => {
+                    ctx.move_to(bar_x(i), to_y(*v));
+                    drawing = true;
+                }
+                None => drawing = false,
+            }
+        }
+        ctx.stroke();
+    }
+    ctx.set_line_width(1.0);
+
+    // Legend under the tooltip row
+    ctx.set_font("10px 'SF Mono', monospace");
+    ctx.set_text_align("left");
+    ctx.set_text_baseline("middle");
+    let mut legend_x = 8.0;
+    for line in overlays {
+        ctx.set_fill_style_str(line.color);
+        let _ = ctx.fill_text(&line.label, legend_x, 32.0);
+        legend_x += line.label.len() as f64 * 6.2 + 12.0;
+    }
+
+    // Last price marker
+    if let Some(last) = visible.last() {
+        let y = price_y(last.close);
+        ctx.set_fill_style_str(ACCENT_COLOR);
+        ctx.fill_rect(layout.plot_right, y - 8.0, PRICE_AXIS_WIDTH, 16.0);
+        ctx.set_fill_style_str("#000000");
+        ctx.set_text_align("left");
+        ctx.set_text_baseline("middle");
+        let _ = ctx.fill_text(&format_price(last.close), layout.plot_right + 6.0, y);
+    }
+
+    // Pane separator
+    ctx.set_stroke_style_str("#333333");
+    ctx.begin_path();
+    ctx.move_to(0.0, layout.volume_top - 3.0);
+    ctx.line_to(layout.plot_right, layout.volume_top - 3.0);
+    ctx.move_to(layout.plot_right + 0.5, 0.0);
+    ctx.line_to(layout.plot_right + 0.5, layout.volume_bottom);
+    ctx.stroke();
+
+    // Crosshair + OHLCV tooltip
+    if let Some((mx, my)) = crosshair {
+        if mx < 0.0 || mx > layout.plot_right || my < layout.price_top || my > layout.volume_bottom {
+            return;
+        }
+
+        let slot = (mx / bar_w - first_slot).floor();
+        if slot < 0.0 || slot as usize >= visible.len() {
+            return;
+        }
+        let index = slot as usize;
+        let candle = &visible[index];
+        let x = bar_x(index).round() + 0.5;
+
+        ctx.set_stroke_style_str("rgba(255, 255, 255, 0.35)");
+        ctx.begin_path();
+        ctx.move_to(x, layout.price_top);
+        ctx.line_to(x, layout.volume_bottom);
+        ctx.move_to(0.0, my.round() + 0.5);
+        ctx.line_to(layout.plot_right, my.round() + 0.5);
+        ctx.stroke();
+
+        if my <= layout.price_bottom {
+            let price = high - (my - layout.price_top) / (layout.price_bottom - layout.price_top) * (high - low);
+            ctx.set_fill_style_str("#333333");
+            ctx.fill_rect(layout.plot_right, my - 8.0, PRICE_AXIS_WIDTH, 16.0);
+            ctx.set_fill_style_str("#ffffff");
+            ctx.set_text_align("left");
+            ctx.set_text_baseline("middle");
+            let _ = ctx.fill_text(&format_price(price), layout.plot_right + 6.0, my);
+        }
+
+        let change_pct = if candle.open > 0.0 {
+            (candle.close - candle.open) / candle.open * 100.0
+        } else {
+            0.0
+        };
+        let tooltip = format!(
+            "{}  O {}  H {}  L {}  C {}  V {:.2}  {:+.2}%",
+            format_time(candle.time),
+            format_price(candle.open),
+            format_price(candle.high),
+            format_price(candle.low),
+            format_price(candle.close),
+            candle.volume,
+            change_pct
+        );
+        ctx.set_fill_style_str("rgba(10, 10, 10, 0.85)");
+        ctx.fill_rect(4.0, 4.0, (tooltip.len() as f64 * 6.2).min(layout.plot_right - 8.0), 18.0);
+        ctx.set_fill_style_str(if candle.close >= candle.open { BULL_COLOR } else { BEAR_COLOR });
+        ctx.set_text_align("left");
+        ctx.set_text_baseline("middle");
+        let _ = ctx.fill_text(&tooltip, 8.0, 13.0);
+    }
+}
+
+// =====================
+// COMPONENT
+// =====================
 
 #[component]
 pub fn TradingChart(
@@ -11,7 +531,107 @@ pub fn TradingChart(
     /// Chart symbol
     #[prop(default = "BTCUSDT".to_string())]
     symbol: String,
+    /// Canvas height in pixels
+    #[prop(default = 360)]
+    height: u32,
+    /// Indicators drawn over the chart, e.g. `["ema", "rsi"]`
+    #[prop(default = Vec::new())]
+    indicators: Vec<String>,
 ) -> impl IntoView {
+    let canvas_ref: NodeRef<leptos::html::Canvas> = create_node_ref();
+    let overlays = create_memo(move |_| candles.with(|c| indicator_lines(c, &indicators)));
+    let (view, set_view) = create_signal(ChartView::default());
+    let (crosshair, set_crosshair) = create_signal(None::<(f64, f64)>);
+    // (start mouse x, view offset at drag start)
+    let (drag, set_drag) = create_signal(None::<(f64, f64)>);
+
+    let canvas_element = move || -> Option<HtmlCanvasElement> {
+        canvas_ref.get().map(|canvas| {
+            let element: &HtmlCanvasElement = &canvas;
+            element.clone()
+        })
+    };
+
+    let layout = move || -> Option<ChartLayout> {
+        let canvas = canvas_element()?;
+        Some(ChartLayout::new(canvas.width() as f64, canvas.height() as f64))
+    };
+
+    // Redraw whenever data, viewport or crosshair change
+    create_effect(move |_| {
+        let candles = candles.get();
+        let view = view.get();
+        let crosshair = crosshair.get();
+
+        let Some(canvas) = canvas_element() else {
+            return;
+        };
+        let width = canvas.client_width().max(200) as u32;
+        if canvas.width() != width {
+            canvas.set_width(width);
+        }
+        if canvas.height() != height {
+            canvas.set_height(height);
+        }
+
+        let ctx = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|ctx| ctx.dyn_into::<CanvasRenderingContext2d>().ok());
+        if let Some(ctx) = ctx {
+            let layout = ChartLayout::new(width as f64, height as f64);
+            overlays.with(|overlays| draw_chart(&ctx, &candles, &view, &layout, crosshair, overlays));
+        }
+    });
+
+    let local_point = move |ev: &MouseEvent| -> Option<(f64, f64)> {
+        let canvas = canvas_element()?;
+        let rect = canvas.get_bounding_client_rect();
+        Some((ev.client_x() as f64 - rect.left(), ev.client_y() as f64 - rect.top()))
+    };
+
+    let on_wheel = move |ev: WheelEvent| {
+        ev.prevent_default();
+        let (Some((x, _)), Some(layout)) = (local_point(&ev), layout()) else {
+            return;
+        };
+        let len = candles.with_untracked(|c| c.len());
+        let factor = if ev.delta_y() > 0.0 { 1.1 } else { 1.0 / 1.1 };
+        set_view.update(|v| v.zoom(factor, x / layout.plot_right.max(1.0), len));
+    };
+
+    let on_mouse_down = move |ev: MouseEvent| {
+        if let Some((x, _)) = local_point(&ev) {
+            set_drag.set(Some((x, view.get_untracked().offset)));
+        }
+    };
+
+    let on_mouse_move = move |ev: MouseEvent| {
+        let Some((x, y)) = local_point(&ev) else {
+            return;
+        };
+        set_crosshair.set(Some((x, y)));
+
+        if let (Some((start_x, start_offset)), Some(layout)) = (drag.get_untracked(), layout()) {
+            let len = candles.with_untracked(|c| c.len());
+            let bar_w = layout.bar_width(&view.get_untracked());
+            // Dragging right reveals older candles
+            let bars = (x - start_x) / bar_w.max(1e-6);
+            set_view.update(|v| {
+                v.offset = start_offset;
+                v.pan(bars, len);
+            });
+        }
+    };
+
+    let on_mouse_up = move |_: MouseEvent| set_drag.set(None);
+
+    let on_mouse_leave = move |_: MouseEvent| {
+        set_drag.set(None);
+        set_crosshair.set(None);
+    };
+
     view! {
         <Style>{r#"
             .simple-chart {
@@ -19,41 +639,21 @@ pub fn TradingChart(
                 background: #0a0a0a;
                 border: 2px solid #1a1a1a;
                 border-radius: 12px;
-                padding: 20px;
-                text-align: center;
+                padding: 12px;
             }
             .chart-title {
-                font-size: 18px;
+                font-size: 14px;
                 font-weight: 700;
                 color: #ff6b35;
-                margin-bottom: 20px;
-            }
-            .candle-grid {
-                display: flex;
-                flex-wrap: wrap;
-                justify-content: center;
-                gap: 8px;
+                margin-bottom: 8px;
             }
-            .candle {
-                padding: 8px 12px;
-                border-radius: 6px;
-                font-size: 11px;
-                display: flex;
-                flex-direction: column;
-                gap: 4px;
+            .chart-canvas {
+                display: block;
+                width: 100%;
+                cursor: crosshair;
             }
-            .candle.bullish {
-                background: rgba(0, 255, 136, 0.1);
-                border: 1px solid #00ff88;
-                color: #00ff88;
-            }
-            .candle.bearish {
-                background: rgba(255, 51, 51, 0.1);
-                border: 1px solid #ff3333;
-                color: #ff3333;
-            }
-            .candle-price {
-                font-weight: 700;
+            .chart-canvas.dragging {
+                cursor: grabbing;
             }
         "#}</Style>
 
@@ -62,44 +662,82 @@ pub fn TradingChart(
                 {move || format!("📊 {} Chart ({})", symbol, candles.get().len())}
             </div>
 
-            <div class="candle-grid">
-                {move || {
-                    // Show last 20 candles or empty message
-                    if candles.get().is_empty() {
-                        view! {
-                            <div style="color: #888; padding: 40px; grid-column: 1 / -1;">
-                                "No candle data available. Connect to data source."
-                            </div>
-                        }.into_view()
-                    } else {
-                        let candles_vec = candles.get();
-                        let display_candles: Vec<_> = candles_vec.iter().rev().take(20).collect();
-
-                        display_candles.into_iter().map(|candle| {
-                            let is_bullish = candle.close >= candle.open;
-                            let change_pct = if candle.open > 0.0 {
-                                ((candle.close - candle.open) / candle.open * 100.0)
-                            } else {
-                                0.0
-                            };
-
-                            view! {
-                                <div class=format!("candle {}", if is_bullish { "bullish" } else { "bearish" })>
-                                    <div class="candle-price">
-                                        {format!("{:.2}", candle.close)}
-                                    </div>
-                                    <div style="font-size: 9px; opacity: 0.7;">
-                                        {format!("{:+.2}%", change_pct)}
-                                    </div>
-                                    <div style="font-size: 9px; opacity: 0.5;">
-                                        {format!("O:{:.2} H:{:.2} L:{:.2}", candle.open, candle.high, candle.low)}
-                                    </div>
-                                </div>
-                            }
-                        }).collect_view().into_view()
-                    }
-                }}
-            </div>
+            <canvas
+                ref=canvas_ref
+                class="chart-canvas"
+                class:dragging=move || drag.get().is_some()
+                style=format!("height: {}px;", height)
+                on:wheel=on_wheel
+                on:mousedown=on_mouse_down
+                on:mousemove=on_mouse_move
+                on:mouseup=on_mouse_up
+                on:mouseleave=on_mouse_leave
+                on:dblclick=move |_| set_view.set(ChartView::default())
+            ></canvas>
         </div>
     }
 }
+
+/// `TradingChart` fed by its own `/ws/market` subscription. Re-create the
+/// component to switch symbol or interval.
+#[component]
+pub fn LiveChart(
+    /// Binance symbol, e.g. "BTCUSDT"
+    symbol: String,
+    /// Kline interval
+    #[prop(default = "1m".to_string())]
+    interval: String,
+    /// Canvas height in pixels
+    #[prop(default = 360)]
+    height: u32,
+    /// Indicators drawn over the chart, e.g. `["ema", "rsi"]`
+    #[prop(default = Vec::new())]
+    indicators: Vec<String>,
+) -> impl IntoView {
+    let (candles, set_candles) = create_signal(Vec::<Candle>::new());
+    let market_socket = store_value(None::<WebSocket>);
+
+    // Effects only run in the browser, so the socket is never opened during SSR
+    let ws_symbol = symbol.clone();
+    create_effect(move |_| {
+        let location = window().location();
+        let scheme = if location.protocol().unwrap_or_default() == "https:" { "wss" } else { "ws" };
+        let host = location.host().unwrap_or_default();
+        let ws_url = format!("{}://{}/ws/market?symbol={}&interval={}", scheme, host, ws_symbol, interval);
+
+        let ws = match WebSocket::new(&ws_url) {
+            Ok(ws) => ws,
+            Err(e) => {
+                leptos::logging::log!("Failed to open market socket: {:?}", e);
+                return;
+            }
+        };
+
+        let onmessage_callback = wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+
+            match serde_json::from_str::<MarketMessage>(&text) {
+                Ok(MarketMessage::Snapshot { candles, .. }) => set_candles.set(candles),
+                Ok(MarketMessage::Candle { candle, .. }) => {
+                    set_candles.update(|candles| upsert_candle(candles, candle, MAX_CANDLES));
+                }
+                Err(e) => leptos::logging::log!("Bad market message: {:?}", e),
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+        onmessage_callback.forget(); // Prevent callback from being dropped
+
+        market_socket.set_value(Some(ws));
+    });
+
+    on_cleanup(move || {
+        if let Some(ws) = market_socket.get_value() {
+            let _ = ws.close();
+        }
+    });
+
+    view! { <TradingChart candles=candles.into() symbol=symbol height=height indicators=indicators /> }
+}
@@ -1,12 +1,17 @@
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
-use web_sys::{MouseEvent, WebSocket as WebWebSocket};
+use web_sys::MouseEvent;
 use wasm_bindgen::JsCast;
-use crate::components::dragon::Dragon;
-use crate::components::chart::TradingChart;
-use crate::server::{grok_analyze, verify_nft};
-use crate::types::Candle;
+use crate::components::dragon::{use_dragrok_events, Dragon};
+use crate::components::chart::LiveChart;
+use crate::components::portfolio::PortfolioPanel;
+use crate::nft_gating::{GatedFeature, NewNftCollection};
+use crate::server::{
+    delete_nft_collection, linked_wallets, list_nft_collections, logout, nft_entitlements,
+    save_nft_collection, verify_nft,
+};
+use crate::wallet_auth::short_address;
 
 #[component]
 pub fn DashboardPage() -> impl IntoView {
@@ -14,12 +19,11 @@ pub fn DashboardPage() -> impl IntoView {
 
     // Dragon state signals
     let (is_speaking, set_is_speaking) = create_signal(false);
-    let (is_firing, set_is_firing) = create_signal(false);
     let (dragon_x, set_dragon_x) = create_signal(50.0); // % position
     let (dragon_y, set_dragon_y) = create_signal(20.0); // % position
     let (rotation, set_rotation) = create_signal(0.0); // degrees
-    let (mouse_x, set_mouse_x) = create_signal(0.0);
-    let (mouse_y, set_mouse_y) = create_signal(0.0);
+    let (_mouse_x, set_mouse_x) = create_signal(0.0);
+    let (_mouse_y, set_mouse_y) = create_signal(0.0);
 
     // Grok analysis state
     let (analysis_text, set_analysis_text) = create_signal(String::new());
@@ -28,77 +32,49 @@ pub fn DashboardPage() -> impl IntoView {
 
     // NFT verification state
     let (wallet_address, set_wallet_address) = create_signal(String::new());
-    let (mint_address, set_mint_address) = create_signal(String::new());
     let (is_nft_holder, set_is_nft_holder) = create_signal(false);
+    let (nft_features, set_nft_features) = create_signal(Vec::<GatedFeature>::new());
     let (is_verifying, set_is_verifying) = create_signal(false);
     let (nft_message, set_nft_message) = create_signal(String::new());
 
-    // Price monitoring state
-    let (current_price, set_current_price) = create_signal(0.0f64);
-    let (previous_price, set_previous_price) = create_signal(0.0f64);
-    let (price_change_percent, set_price_change_percent) = create_signal(0.0f64);
-
-    // Real-time candle data signal
-    let (candles, set_candles) = create_signal(Vec::<Candle>::new());
-
-    // Initialize with some historical data (placeholder)
-    let initial_candles = vec![
-        Candle { time: 1704067200, open: 42000.0, high: 42500.0, low: 41800.0, close: 42300.0, volume: 1234.5 },
-        Candle { time: 1704070800, open: 42300.0, high: 42800.0, low: 42100.0, close: 42600.0, volume: 1456.7 },
-        Candle { time: 1704074400, open: 42600.0, high: 42900.0, low: 42400.0, close: 42450.0, volume: 1678.9 },
-        Candle { time: 1704078000, open: 42450.0, high: 42700.0, low: 42200.0, close: 42500.0, volume: 1890.2 },
-        Candle { time: 1704081600, open: 42500.0, high: 43100.0, low: 42400.0, close: 42900.0, volume: 2101.4 },
-    ];
-    set_candles.set(initial_candles);
-
-    // WebSocket connection for real-time data (Binance public stream)
-    let ws_url = "wss://stream.binance.com:9443/ws/btcusdt@kline_1m";
-    let set_candles_ws = set_candles.clone();
-
-    // Create WebSocket connection using web_sys
-    let ws = WebWebSocket::new(ws_url).expect("Failed to create WebSocket");
-    leptos::logging::log!("✅ WebSocket created, connecting to Binance...");
-
-    // Set up onmessage handler
-    let onmessage_callback = wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
-        let data = event.data();
-        let text = data.as_string().expect("Failed to convert data to string");
-
-        if let Ok(json_data) = serde_json::from_str::<serde_json::Value>(&text) {
-            if let Some(kline) = json_data.get("k") {
-                let time = kline.get("t").and_then(|v| v.as_i64()).unwrap_or(0);
-                let open = kline.get("o").and_then(|v| v.as_str()).and_then(|s| s.parse().ok()).unwrap_or(0.0);
-                let high = kline.get("h").and_then(|v| v.as_str()).and_then(|s| s.parse().ok()).unwrap_or(0.0);
-                let low = kline.get("l").and_then(|v| v.as_str()).and_then(|s| s.parse().ok()).unwrap_or(0.0);
-                let close = kline.get("c").and_then(|v| v.as_str()).and_then(|s| s.parse().ok()).unwrap_or(0.0);
-                let volume = kline.get("v").and_then(|v| v.as_str()).and_then(|s| s.parse().ok()).unwrap_or(0.0);
-                let is_closed = kline.get("x").and_then(|v| v.as_bool()).unwrap_or(false);
-
-                let new_candle = Candle { time, open, high, low, close, volume };
-
-                // Update candles: if candle is closed, add new one; otherwise update last
-                set_candles_ws.update(|candles| {
-                    if is_closed {
-                        candles.push(new_candle);
-                        // Keep only last 50 candles
-                        if candles.len() > 50 {
-                            candles.remove(0);
-                        }
-                    } else if let Some(last) = candles.last_mut() {
-                        // Update the last candle (it's still forming)
-                        *last = new_candle;
-                    } else {
-                        candles.push(new_candle);
+    // Entitlements verified earlier stay valid until they expire
+    create_effect(move |_| {
+        spawn_local(async move {
+            if let Ok(entitlements) = nft_entitlements().await {
+                let mut features: Vec<GatedFeature> = Vec::new();
+                for feature in entitlements.iter().flat_map(|e| e.features.iter()) {
+                    if !features.contains(feature) {
+                        features.push(*feature);
                     }
-                });
+                }
+                if let Some(latest) = entitlements.first() {
+                    set_is_nft_holder.set(true);
+                    set_nft_message.set(format!(
+                        "NFT verified ({}) until {}",
+                        latest.collections.join(", "),
+                        latest.expires_at.format("%Y-%m-%d %H:%M UTC")
+                    ));
+                }
+                set_nft_features.set(features);
+            }
+        });
+    });
+
+    // Only wallets proven by signing in with them can be verified
+    let wallets = create_resource(|| (), |_| async move { linked_wallets().await.unwrap_or_default() });
+    create_effect(move |_| {
+        if let Some(first) = wallets.get().and_then(|list| list.into_iter().next()) {
+            if wallet_address.get_untracked().is_empty() {
+                set_wallet_address.set(first);
             }
         }
-    }) as Box<dyn FnMut(_)>);
+    });
 
-    ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
-    onmessage_callback.forget(); // Prevent callback from being dropped
+    // Symbol shown in the live chart
+    let (chart_symbol, set_chart_symbol) = create_signal("BTCUSDT".to_string());
 
-    leptos::logging::log!("✅ WebSocket connected to Binance");
+    // Dragon events pushed by the server (price alerts, broadcasts)
+    let dragon_event = use_dragrok_events();
 
     // Mouse tracking for dragon cursor following
     let handle_mouse_move = move |ev: MouseEvent| {
@@ -134,66 +110,142 @@ pub fn DashboardPage() -> impl IntoView {
         set_dragon_y.set(new_y.clamp(10.0, 90.0));
     };
 
-    // Grok analysis + voice synthesis handler
+    // Grok analysis + voice synthesis handler (streamed over SSE)
     let handle_analyze = move |_| {
         // Check NFT verification first
-        if !is_nft_holder.get() {
+        if !nft_features.get().contains(&GatedFeature::GrokAnalysis) {
             set_nft_message.set("⚠️ NFT verification required for Grok analysis".to_string());
             return;
         }
-        let set_is_speaking = set_is_speaking.clone();
-        let set_analysis_text = set_analysis_text.clone();
-        let set_is_analyzing = set_is_analyzing.clone();
-        let set_show_speech = set_show_speech.clone();
 
+        let prompt = "Analyze current market conditions and provide trading insights for Solana DeFi pools";
+        let selected_text = "Orca, Raydium, Meteora liquidity pools";
+
+        let params = match web_sys::UrlSearchParams::new() {
+            Ok(params) => params,
+            Err(e) => {
+                leptos::logging::log!("Failed to build query: {:?}", e);
+                return;
+            }
+        };
+        params.append("mode", "risk");
+        params.append("prompt", prompt);
+        params.append("selected_text", selected_text);
+        params.append("include_screenshot", "false");
+        let url = format!("/api/analyze/stream?{}", String::from(params.to_string()));
+
+        let source = match web_sys::EventSource::new(&url) {
+            Ok(source) => source,
+            Err(e) => {
+                leptos::logging::log!("Failed to open analysis stream: {:?}", e);
+                set_analysis_text.set("Analysis failed: could not open stream".to_string());
+                return;
+            }
+        };
+
+        let _ = crate::components::dragon::cancel_speech();
+        set_analysis_text.set(String::new());
         set_is_analyzing.set(true);
-        set_show_speech.set(false);
-
-        spawn_local(async move {
-            let prompt = "Analyze current market conditions and provide trading insights for Solana DeFi pools".to_string();
-            let selected_text = "Orca, Raydium, Meteora liquidity pools".to_string();
-            let include_screenshot = false;
+        set_show_speech.set(true);
+        set_is_speaking.set(true);
 
-            match grok_analyze(prompt, selected_text, include_screenshot).await {
-                Ok(analysis) => {
-                    set_analysis_text.set(analysis.clone());
+        // Text received but not yet handed to speech synthesis
+        let pending = std::rc::Rc::new(std::cell::RefCell::new(String::new()));
 
-                    // Trigger dragon speech
-                    set_show_speech.set(true);
-                    set_is_speaking.set(true);
+        let speak_all = |sentences: Vec<String>| {
+            for sentence in sentences {
+                if let Err(e) = crate::components::dragon::speak(&sentence) {
+                    leptos::logging::log!("Speech error: {:?}", e);
+                }
+            }
+        };
 
-                    // Use the speak function from dragon module
-                    if let Err(e) = crate::components::dragon::speak(&analysis) {
-                        leptos::logging::log!("Speech error: {:?}", e);
-                    }
+        let finish = move |source: &web_sys::EventSource| {
+            source.close();
+            set_is_analyzing.set(false);
 
-                    // Reset speaking state after estimated duration
-                    use std::time::Duration;
-                    let duration = Duration::from_millis(analysis.len() as u64 * 80); // ~80ms per character
-                    set_timeout(
-                        move || {
-                            set_is_speaking.set(false);
-                            set_show_speech.set(false);
-                        },
-                        duration,
-                    );
+            // Reset speaking state after estimated duration
+            use std::time::Duration;
+            let duration = Duration::from_millis(analysis_text.get_untracked().len() as u64 * 80); // ~80ms per character
+            set_timeout(
+                move || {
+                    set_is_speaking.set(false);
+                    set_show_speech.set(false);
+                },
+                duration,
+            );
+        };
+
+        let on_delta = {
+            let pending = pending.clone();
+            wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                let delta = event.data().as_string().unwrap_or_default();
+                set_analysis_text.update(|text| text.push_str(&delta));
+
+                let sentences = {
+                    let mut pending = pending.borrow_mut();
+                    pending.push_str(&delta);
+                    crate::components::dragon::take_sentences(&mut pending)
+                };
+                speak_all(sentences);
+            }) as Box<dyn FnMut(_)>)
+        };
+
+        let on_done = {
+            let pending = pending.clone();
+            let source = source.clone();
+            wasm_bindgen::closure::Closure::wrap(Box::new(move |_: web_sys::MessageEvent| {
+                let rest = std::mem::take(&mut *pending.borrow_mut());
+                if !rest.trim().is_empty() {
+                    speak_all(vec![rest.trim().to_string()]);
                 }
-                Err(e) => {
-                    leptos::logging::log!("Grok analysis error: {:?}", e);
-                    set_analysis_text.set(format!("Analysis failed: {}", e));
+                finish(&source);
+            }) as Box<dyn FnMut(_)>)
+        };
+
+        let on_failure = {
+            let source = source.clone();
+            wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                let message = event.data().as_string().unwrap_or_default();
+                leptos::logging::log!("Grok analysis error: {}", message);
+                set_analysis_text.set(format!("Analysis failed: {}", message));
+                finish(&source);
+            }) as Box<dyn FnMut(_)>)
+        };
+
+        // Connection-level errors (server down, stream dropped)
+        let on_error = {
+            let source = source.clone();
+            wasm_bindgen::closure::Closure::wrap(Box::new(move |_: web_sys::Event| {
+                if is_analyzing.get_untracked() {
+                    set_analysis_text.update(|text| {
+                        if text.is_empty() {
+                            *text = "Analysis failed: connection lost".to_string();
+                        }
+                    });
+                    finish(&source);
                 }
-            }
-            set_is_analyzing.set(false);
-        });
+            }) as Box<dyn FnMut(_)>)
+        };
+
+        let _ = source.add_event_listener_with_callback("delta", on_delta.as_ref().unchecked_ref());
+        let _ = source.add_event_listener_with_callback("done", on_done.as_ref().unchecked_ref());
+        let _ = source.add_event_listener_with_callback("failure", on_failure.as_ref().unchecked_ref());
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+        // Listeners live as long as the EventSource
+        on_delta.forget();
+        on_done.forget();
+        on_failure.forget();
+        on_error.forget();
     };
 
     // NFT verification handler
     let handle_verify_nft = move |_| {
         let wallet = wallet_address.get();
-        let mint = mint_address.get();
 
-        if wallet.is_empty() || mint.is_empty() {
-            set_nft_message.set("Please enter both wallet and mint addresses".to_string());
+        if wallet.is_empty() {
+            set_nft_message.set("Please choose a signed-in wallet".to_string());
             return;
         }
 
@@ -201,85 +253,61 @@ pub fn DashboardPage() -> impl IntoView {
         set_nft_message.set("Verifying NFT ownership...".to_string());
 
         spawn_local(async move {
-            match verify_nft(wallet, mint).await {
+            match verify_nft(wallet).await {
                 Ok(response) => {
                     set_is_nft_holder.set(response.is_holder);
+                    set_nft_features.set(response.features);
                     set_nft_message.set(response.message);
                 }
                 Err(e) => {
                     set_nft_message.set(format!("Verification failed: {}", e));
                     set_is_nft_holder.set(false);
+                    set_nft_features.set(Vec::new());
                 }
             }
             set_is_verifying.set(false);
         });
     };
 
-    // Price monitoring effect - runs every 5 seconds
-    let check_prices = move || {
-        let set_is_firing = set_is_firing.clone();
-        let set_current_price = set_current_price.clone();
-        let set_previous_price = set_previous_price.clone();
-        let set_price_change_percent = set_price_change_percent.clone();
-        let current_price = current_price.clone();
-        let previous_price = previous_price.clone();
-
+    // Allow-listed NFT collections (admin)
+    let (collections_version, set_collections_version) = create_signal(0u32);
+    let collections = create_resource(
+        move || collections_version.get(),
+        |_| async move { list_nft_collections().await.unwrap_or_default() },
+    );
+    let (new_collection_address, set_new_collection_address) = create_signal(String::new());
+    let (new_collection_label, set_new_collection_label) = create_signal(String::new());
+    let (new_collection_features, set_new_collection_features) =
+        create_signal(GatedFeature::ALL.to_vec());
+    let (collection_message, set_collection_message) = create_signal(String::new());
+
+    let handle_add_collection = move |_| {
+        let collection = NewNftCollection {
+            address: new_collection_address.get(),
+            label: new_collection_label.get(),
+            features: new_collection_features.get(),
+        };
         spawn_local(async move {
-            // Fetch SOL price from Binance API
-            match reqwest::get("https://api.binance.com/api/v3/ticker/price?symbol=SOLUSDT").await {
-                Ok(resp) => {
-                    if let Ok(data) = resp.json::<serde_json::Value>().await {
-                        if let Some(price_str) = data["price"].as_str() {
-                            if let Ok(price) = price_str.parse::<f64>() {
-                                set_previous_price.set(current_price.get());
-                                set_current_price.set(price);
-
-                                let prev = previous_price.get();
-                                let change_pct = if prev > 0.0 {
-                                    ((price - prev) / prev) * 100.0
-                                } else {
-                                    0.0
-                                };
-                                set_price_change_percent.set(change_pct);
-
-                                // Trigger fire breath on significant pump (>5%)
-                                if change_pct.abs() > 5.0 {
-                                    leptos::logging::log!("🔥 Price pump detected: {:.2}%!", change_pct);
-                                    set_is_firing.set(true);
-
-                                    // Fire breath for 2 seconds
-                                    let set_is_firing = set_is_firing.clone();
-                                    set_timeout(
-                                        move || {
-                                            set_is_firing.set(false);
-                                        },
-                                        std::time::Duration::from_secs(2),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    leptos::logging::log!("Price fetch error: {:?}", e);
+            match save_nft_collection(collection).await {
+                Ok(saved) => {
+                    set_collection_message.set(format!("{} approved", saved.label));
+                    set_new_collection_address.set(String::new());
+                    set_new_collection_label.set(String::new());
+                    set_collections_version.update(|v| *v += 1);
                 }
+                Err(e) => set_collection_message.set(e.to_string()),
             }
         });
     };
 
-    // Start price monitoring loop
-    let check_prices_clone = check_prices.clone();
-    set_timeout(
-        move || {
-            check_prices_clone();
-            // Schedule next check using gloo timers
-            let check = check_prices.clone();
-            let _interval = gloo_timers::callback::Interval::new(5_000, move || {
-                check();
-            });
-        },
-        std::time::Duration::from_secs(1),
-    );
+    let remove_collection = move |address: String| {
+        spawn_local(async move {
+            match delete_nft_collection(address).await {
+                Ok(()) => set_collections_version.update(|v| *v += 1),
+                Err(e) => set_collection_message.set(e.to_string()),
+            }
+        });
+    };
 
     view! {
         <Style>{r#"
@@ -584,6 +612,27 @@ pub fn DashboardPage() -> impl IntoView {
                 color: #888;
             }
 
+            .nft-collection {
+                display: flex;
+                gap: 12px;
+                align-items: center;
+                font-size: 11px;
+                color: #ccc;
+            }
+
+            .nft-collection-address {
+                color: #666;
+            }
+
+            .nft-feature-toggle {
+                display: flex;
+                gap: 4px;
+                align-items: center;
+                font-size: 10px;
+                color: #888;
+                white-space: nowrap;
+            }
+
             .dragon-speech-bubble {
                 position: absolute;
                 bottom: 80px;
@@ -640,7 +689,7 @@ pub fn DashboardPage() -> impl IntoView {
                         on:click={
                             let navigate = navigate.clone();
                             move |_| {
-                                navigate(&"/whiteboard".to_string(), Default::default());
+                                navigate("/whiteboard", Default::default());
                             }
                         }
                     >
@@ -651,7 +700,13 @@ pub fn DashboardPage() -> impl IntoView {
                         on:click={
                             let navigate = navigate.clone();
                             move |_| {
-                                navigate(&"/admin".to_string(), Default::default());
+                                let navigate = navigate.clone();
+                                spawn_local(async move {
+                                    if let Err(e) = logout().await {
+                                        leptos::logging::log!("Logout error: {:?}", e);
+                                    }
+                                    navigate("/admin", Default::default());
+                                });
                             }
                         }
                     >
@@ -664,20 +719,25 @@ pub fn DashboardPage() -> impl IntoView {
             <div class="nft-verify-section">
                 <div class="nft-verify-title">"🔮 NFT Verification Required"</div>
                 <div class="nft-inputs">
-                    <input
-                        type="text"
+                    <select
                         class="nft-input"
-                        placeholder="Wallet Address"
                         prop:value=wallet_address
-                        on:input=move |ev| set_wallet_address.set(event_target_value(&ev))
-                    />
-                    <input
-                        type="text"
-                        class="nft-input"
-                        placeholder="NFT Mint Address"
-                        prop:value=mint_address
-                        on:input=move |ev| set_mint_address.set(event_target_value(&ev))
-                    />
+                        on:change=move |ev| set_wallet_address.set(event_target_value(&ev))
+                    >
+                        {move || {
+                            let list = wallets.get().unwrap_or_default();
+                            if list.is_empty() {
+                                view! { <option value="">"Sign in with a wallet first"</option> }.into_view()
+                            } else {
+                                list.into_iter()
+                                    .map(|address| {
+                                        let label = short_address(&address);
+                                        view! { <option value=address>{label}</option> }
+                                    })
+                                    .collect_view()
+                            }
+                        }}
+                    </select>
                     <button
                         class="verify-btn"
                         on:click=handle_verify_nft
@@ -694,13 +754,94 @@ pub fn DashboardPage() -> impl IntoView {
                 >
                     {move || nft_message.get()}
                 </div>
+
+                <div class="nft-verify-title">"Approved collections"</div>
+                {move || {
+                    collections
+                        .get()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|collection| {
+                            let address = collection.address.clone();
+                            let features: Vec<&str> = collection.features.iter().map(|f| f.label()).collect();
+                            view! {
+                                <div class="nft-collection">
+                                    <span>{collection.label}</span>
+                                    <span class="nft-collection-address">{short_address(&collection.address)}</span>
+                                    <span>{features.join(", ")}</span>
+                                    <button class="verify-btn" on:click=move |_| remove_collection(address.clone())>
+                                        "Remove"
+                                    </button>
+                                </div>
+                            }
+                        })
+                        .collect_view()
+                }}
+                <div class="nft-inputs">
+                    <input
+                        type="text"
+                        class="nft-input"
+                        placeholder="Collection Mint Address"
+                        prop:value=new_collection_address
+                        on:input=move |ev| set_new_collection_address.set(event_target_value(&ev))
+                    />
+                    <input
+                        type="text"
+                        class="nft-input"
+                        placeholder="Label"
+                        prop:value=new_collection_label
+                        on:input=move |ev| set_new_collection_label.set(event_target_value(&ev))
+                    />
+                    {GatedFeature::ALL
+                        .into_iter()
+                        .map(|feature| {
+                            view! {
+                                <label class="nft-feature-toggle">
+                                    <input
+                                        type="checkbox"
+                                        prop:checked=move || new_collection_features.get().contains(&feature)
+                                        on:change=move |ev| {
+                                            let checked = event_target_checked(&ev);
+                                            set_new_collection_features.update(|features| {
+                                                features.retain(|f| *f != feature);
+                                                if checked {
+                                                    features.push(feature);
+                                                }
+                                            });
+                                        }
+                                    />
+                                    {feature.label()}
+                                </label>
+                            }
+                        })
+                        .collect_view()}
+                    <button class="verify-btn" on:click=handle_add_collection>
+                        "Approve"
+                    </button>
+                </div>
+                <Show when=move || !collection_message.get().is_empty()>
+                    <div class="nft-status neutral">{move || collection_message.get()}</div>
+                </Show>
             </div>
 
+            <PortfolioPanel wallet=wallet_address />
+
             // Chart Section
             <div class="iframe-wrapper" style="flex: 0 0 auto; height: 400px; margin-bottom: 16px;">
-                <div class="iframe-header">"📊 BTC/USDT Live Chart"</div>
+                <div class="iframe-header">
+                    "📊 Live Chart"
+                    <select
+                        class="nft-input"
+                        style="flex: 0 0 auto; padding: 4px 8px;"
+                        on:change=move |ev| set_chart_symbol.set(event_target_value(&ev))
+                    >
+                        <option value="BTCUSDT" selected=true>"BTC/USDT"</option>
+                        <option value="SOLUSDT">"SOL/USDT"</option>
+                        <option value="ZECUSDT">"ZEC/USDT"</option>
+                    </select>
+                </div>
                 <div style="padding: 16px; overflow-y: auto; flex: 1;">
-                    <TradingChart candles=candles.into() symbol="BTCUSDT".to_string() />
+                    {move || view! { <LiveChart symbol=chart_symbol.get() /> }}
                 </div>
             </div>
 
@@ -744,11 +885,11 @@ pub fn DashboardPage() -> impl IntoView {
 
                 // Grokinho the Dragon (always visible for now, will be NFT-gated later)
                 <Dragon
-                    is_speaking=is_speaking.into()
-                    is_firing=is_firing.into()
-                    pos_x=dragon_x.into()
-                    pos_y=dragon_y.into()
-                    rotation=rotation.into()
+                    is_speaking=is_speaking
+                    event=dragon_event
+                    pos_x=dragon_x
+                    pos_y=dragon_y
+                    rotation=rotation
                 />
 
                 // Speech bubble for dragon's analysis
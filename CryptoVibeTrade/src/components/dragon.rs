@@ -1,14 +1,35 @@
 use leptos::*;
 use leptos_meta::*;
+use std::time::Duration;
 use wasm_bindgen::prelude::*;
-use web_sys::{SpeechSynthesis, SpeechSynthesisUtterance, Window};
+use wasm_bindgen::JsCast;
+use web_sys::{MessageEvent, SpeechSynthesisUtterance, WebSocket};
+use crate::components::chart::LiveChart;
+use crate::types::{ChartInvocation, DragrokEvent, DragrokEventType};
+
+/// CSS class for each event variant
+fn event_class(event_type: DragrokEventType) -> &'static str {
+    match event_type {
+        DragrokEventType::Fire => "fire",
+        DragrokEventType::Alert => "alert",
+        DragrokEventType::Glow => "glow",
+        DragrokEventType::Roar => "roar",
+        DragrokEventType::Speak => "speak",
+    }
+}
+
+/// How long an event's animation plays: 1.5s, up to 4.5s at intensity 100.
+fn event_duration(event: &DragrokEvent) -> Duration {
+    Duration::from_millis(1_500 + event.intensity.min(100) as u64 * 30)
+}
 
 #[component]
 pub fn Dragon(
     /// Whether the dragon is currently speaking
     is_speaking: ReadSignal<bool>,
-    /// Whether dragon should show fire breath effect
-    is_firing: ReadSignal<bool>,
+    /// Latest event pushed by the server; each new value replays the
+    /// matching animation
+    event: ReadSignal<Option<DragrokEvent>>,
     /// Dragon's horizontal position (0-100%)
     pos_x: ReadSignal<f64>,
     /// Dragon's vertical position (0-100%)
@@ -16,6 +37,52 @@ pub fn Dragon(
     /// Rotation angle in degrees
     rotation: ReadSignal<f64>,
 ) -> impl IntoView {
+    // Event currently animating, cleared when its duration is over
+    let (active, set_active) = create_signal(None::<DragrokEvent>);
+    // Chart opened by the last event carrying `chart_data`; stays until closed
+    let (chart, set_chart) = create_signal(None::<ChartInvocation>);
+    // Bumped per event so an older timeout never clears a newer animation
+    let generation = store_value(0u64);
+
+    create_effect(move |_| {
+        let Some(event) = event.get() else {
+            return;
+        };
+
+        generation.update_value(|g| *g += 1);
+        let this_generation = generation.get_value();
+
+        if event.event_type == DragrokEventType::Speak {
+            if let Err(e) = speak(&event.message) {
+                leptos::logging::log!("Speech error: {:?}", e);
+            }
+        }
+        if let Some(invocation) = event.chart_data.clone() {
+            set_chart.set(Some(invocation));
+        }
+
+        let duration = event_duration(&event);
+        set_active.set(Some(event));
+        set_timeout(
+            move || {
+                if generation.get_value() == this_generation {
+                    set_active.set(None);
+                }
+            },
+            duration,
+        );
+    });
+
+    let intensity = move || active.with(|e| e.as_ref().map(|e| e.intensity.min(100) as f64 / 100.0).unwrap_or(0.0));
+    let is_firing = move || active.with(|e| e.as_ref().is_some_and(|e| e.event_type == DragrokEventType::Fire));
+    let variant_class = move || {
+        active.with(|e| {
+            e.as_ref()
+                .map(|e| format!("grokinho-dragon event-{}", event_class(e.event_type)))
+                .unwrap_or_else(|| "grokinho-dragon".to_string())
+        })
+    };
+
     view! {
         <Style>{r#"
             .grokinho-container {
@@ -25,6 +92,7 @@ pub fn Dragon(
                 z-index: 1000;
                 pointer-events: none;
                 transition: transform 0.1s ease-out;
+                --intensity: 0;
             }
 
             .grokinho-dragon {
@@ -40,8 +108,27 @@ pub fn Dragon(
             }
 
             .grokinho-dragon.firing {
-                filter: drop-shadow(0 0 20px rgba(255, 69, 0, 0.8))
-                        drop-shadow(0 0 40px rgba(255, 140, 0, 0.6));
+                filter: drop-shadow(0 0 calc(10px + var(--intensity) * 20px) rgba(255, 69, 0, 0.8))
+                        drop-shadow(0 0 calc(20px + var(--intensity) * 40px) rgba(255, 140, 0, 0.6));
+            }
+
+            /* Event variants; strength scales with --intensity (0-1) */
+            .grokinho-dragon.event-alert {
+                filter: drop-shadow(0 0 calc(8px + var(--intensity) * 24px) rgba(255, 51, 51, 0.9));
+                animation: dragon-shake calc(0.5s - var(--intensity) * 0.35s) ease-in-out infinite;
+            }
+
+            .grokinho-dragon.event-glow {
+                animation: dragon-glow calc(2s - var(--intensity) * 1.2s) ease-in-out infinite;
+            }
+
+            .grokinho-dragon.event-roar {
+                filter: drop-shadow(0 0 calc(10px + var(--intensity) * 30px) rgba(255, 107, 53, 0.8));
+                animation: dragon-roar calc(0.8s - var(--intensity) * 0.4s) ease-out infinite;
+            }
+
+            .grokinho-dragon.event-speak {
+                animation: speaking 0.2s ease-in-out infinite;
             }
 
             /* Fire breath particles */
@@ -56,7 +143,7 @@ pub fn Dragon(
             }
 
             .grokinho-dragon.firing ~ .fire-particles .fire-particle {
-                animation: fire-breath 0.6s ease-out infinite;
+                animation: fire-breath calc(0.8s - var(--intensity) * 0.4s) ease-out infinite;
             }
 
             .fire-particle:nth-child(1) { top: 30px; left: 64px; animation-delay: 0s; }
@@ -75,6 +162,23 @@ pub fn Dragon(
                 50% { transform: scaleY(0.85); }
             }
 
+            @keyframes dragon-shake {
+                0%, 100% { transform: translateX(0) rotate(0deg); }
+                25% { transform: translateX(-4px) rotate(-6deg); }
+                75% { transform: translateX(4px) rotate(6deg); }
+            }
+
+            @keyframes dragon-glow {
+                0%, 100% { filter: drop-shadow(0 0 8px rgba(0, 255, 136, 0.4)); }
+                50% { filter: drop-shadow(0 0 calc(12px + var(--intensity) * 36px) rgba(0, 255, 136, 0.9)); }
+            }
+
+            @keyframes dragon-roar {
+                0% { transform: scale(1); }
+                30% { transform: scale(calc(1.1 + var(--intensity) * 0.4)); }
+                100% { transform: scale(1); }
+            }
+
             @keyframes fire-breath {
                 0% {
                     opacity: 0;
@@ -125,16 +229,57 @@ pub fn Dragon(
                 border-style: solid;
                 border-color: #ff6b35 transparent transparent transparent;
             }
+
+            /* Chart opened by an event's chart_data */
+            .dragon-chart {
+                position: absolute;
+                z-index: 1001;
+                background: #0a0a0a;
+                border: 2px solid #ff6b35;
+                border-radius: 12px;
+                box-shadow: 0 0 30px rgba(255, 107, 53, 0.3);
+                overflow: hidden;
+                pointer-events: auto;
+                display: flex;
+                flex-direction: column;
+            }
+
+            .dragon-chart-header {
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                padding: 6px 12px;
+                font-size: 11px;
+                color: #888;
+                letter-spacing: 0.1em;
+                text-transform: uppercase;
+            }
+
+            .dragon-chart-close {
+                background: transparent;
+                border: none;
+                color: #888;
+                cursor: pointer;
+                font-size: 14px;
+            }
+
+            .dragon-chart-close:hover {
+                color: #ff6b35;
+            }
         "#}</Style>
 
         <div
             class="grokinho-container"
-            style:left=move || format!("{}%", pos_x.get())
-            style:top=move || format!("{}%", pos_y.get())
-            style:transform=move || format!("rotate({}deg)", rotation.get())
+            style=move || format!(
+                "left: {}%; top: {}%; transform: rotate({}deg); --intensity: {:.2};",
+                pos_x.get(),
+                pos_y.get(),
+                rotation.get(),
+                intensity()
+            )
         >
             <div
-                class="grokinho-dragon"
+                class=variant_class
                 class:speaking=is_speaking
                 class:firing=is_firing
             >
@@ -148,8 +293,100 @@ pub fn Dragon(
                 <div class="fire-particle"></div>
                 <div class="fire-particle"></div>
             </div>
+
+            <div class="dragon-speech" class:visible=move || active.with(|e| e.is_some())>
+                {move || active.with(|e| e.as_ref().map(|e| e.message.clone()).unwrap_or_default())}
+            </div>
         </div>
+
+        {move || chart.get().map(|invocation| {
+            let position = invocation.position.clone();
+            let title = invocation.title();
+            view! {
+                <div
+                    class="dragon-chart"
+                    style:left=format!("{}%", position.x.clamp(0.0, 100.0))
+                    style:top=format!("{}%", position.y.clamp(0.0, 100.0))
+                    style:width=format!("{}%", position.width.clamp(10.0, 100.0))
+                    style:height=format!("{}%", position.height.clamp(10.0, 100.0))
+                >
+                    <div class="dragon-chart-header">
+                        <span>{title}</span>
+                        <button class="dragon-chart-close" on:click=move |_| set_chart.set(None)>"✕"</button>
+                    </div>
+                    <LiveChart
+                        symbol=invocation.symbol.clone()
+                        interval=invocation.timeframe.clone()
+                        height=240
+                        indicators=invocation.indicators.clone()
+                    />
+                </div>
+            }
+        })}
+    }
+}
+
+/// Subscribe to `/ws/events` and expose the latest pushed `DragrokEvent`.
+/// Reconnects a few seconds after the socket closes, until the calling
+/// component is cleaned up.
+pub fn use_dragrok_events() -> ReadSignal<Option<DragrokEvent>> {
+    let (event, set_event) = create_signal(None::<DragrokEvent>);
+    let socket = store_value(None::<WebSocket>);
+    let stopped = store_value(false);
+
+    fn connect(
+        set_event: WriteSignal<Option<DragrokEvent>>,
+        socket: StoredValue<Option<WebSocket>>,
+        stopped: StoredValue<bool>,
+    ) {
+        if stopped.get_value() {
+            return;
+        }
+
+        let location = window().location();
+        let scheme = if location.protocol().unwrap_or_default() == "https:" { "wss" } else { "ws" };
+        let host = location.host().unwrap_or_default();
+        let ws = match WebSocket::new(&format!("{}://{}/ws/events", scheme, host)) {
+            Ok(ws) => ws,
+            Err(e) => {
+                leptos::logging::log!("Failed to open events socket: {:?}", e);
+                return;
+            }
+        };
+
+        let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<DragrokEvent>(&text) {
+                Ok(event) => set_event.set(Some(event)),
+                Err(e) => leptos::logging::log!("Bad dragon event: {:?}", e),
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        let onclose = Closure::wrap(Box::new(move |_: web_sys::CloseEvent| {
+            set_timeout(move || connect(set_event, socket, stopped), Duration::from_secs(3));
+        }) as Box<dyn FnMut(_)>);
+
+        ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+        onmessage.forget();
+        onclose.forget();
+
+        socket.set_value(Some(ws));
     }
+
+    // Effects only run in the browser
+    create_effect(move |_| connect(set_event, socket, stopped));
+
+    on_cleanup(move || {
+        stopped.set_value(true);
+        if let Some(ws) = socket.get_value() {
+            let _ = ws.close();
+        }
+    });
+
+    event
 }
 
 /// Speak text using Web Speech API
@@ -180,3 +417,32 @@ pub fn cancel_speech() -> Result<(), JsValue> {
     speech.cancel();
     Ok(())
 }
+
+/// Remove every complete sentence from the front of `buffer` and return
+/// them, leaving any unfinished tail in place. Used to start speaking a
+/// streamed analysis before the full text has arrived.
+pub fn take_sentences(buffer: &mut String) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = buffer.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let ends_sentence = match c {
+            '\n' => true,
+            '.' | '!' | '?' => matches!(chars.peek(), Some((_, next)) if next.is_whitespace()),
+            _ => false,
+        };
+
+        if ends_sentence {
+            let end = i + c.len_utf8();
+            let sentence = buffer[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+            start = end;
+        }
+    }
+
+    buffer.drain(..start);
+    sentences
+}
@@ -1,6 +1,8 @@
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
+use crate::components::wallet::{self, sign_in_with_wallet, SolanaWallet};
+use crate::wallet_auth::short_address;
 
 #[derive(Clone, Copy)]
 struct TradingPair {
@@ -52,39 +54,37 @@ pub fn LandingPage() -> impl IntoView {
         set_wallet_dropdown_open.set(!current);
     };
 
-    // Connect wallet handlers
-    let connect_metamask = move |_| {
-        set_wallet_type.set("MetaMask".to_string());
-        set_wallet_address.set("0x1234...5678".to_string());
-        set_wallet_connected.set(true);
-        set_wallet_dropdown_open.set(false);
-    };
-
-    let connect_phantom = move |_| {
-        set_wallet_type.set("Phantom".to_string());
-        set_wallet_address.set("Solana...Demo".to_string());
-        set_wallet_connected.set(true);
-        set_wallet_dropdown_open.set(false);
-    };
-
-    let connect_jupiter = move |_| {
-        set_wallet_type.set("Jupiter".to_string());
-        set_wallet_address.set("Solana...Jupiter".to_string());
-        set_wallet_connected.set(true);
-        set_wallet_dropdown_open.set(false);
-    };
+    // Sign in with a Solana wallet: the wallet signs a server challenge
+    let (wallet_status, set_wallet_status) = create_signal(String::new());
+    let (connected_wallet, set_connected_wallet) = create_signal(None::<SolanaWallet>);
 
-    let connect_walletconnect = move |_| {
-        set_wallet_type.set("WalletConnect".to_string());
-        set_wallet_address.set("wc...1234".to_string());
-        set_wallet_connected.set(true);
+    let connect_wallet = move |wallet: SolanaWallet| {
         set_wallet_dropdown_open.set(false);
+        set_wallet_status.set(format!("Approve the sign-in request in {}...", wallet.label()));
+        spawn_local(async move {
+            match sign_in_with_wallet(wallet).await {
+                Ok(session) if session.response.success => {
+                    set_wallet_type.set(wallet.label().to_string());
+                    set_wallet_address.set(short_address(&session.address));
+                    set_connected_wallet.set(Some(wallet));
+                    set_wallet_connected.set(true);
+                    set_wallet_status.set(session.response.message);
+                }
+                Ok(session) => set_wallet_status.set(session.response.message),
+                Err(e) => set_wallet_status.set(e),
+            }
+        });
     };
 
     let disconnect_wallet = move |_| {
+        if let Some(connected) = connected_wallet.get() {
+            spawn_local(wallet::disconnect_wallet(connected));
+        }
+        set_connected_wallet.set(None);
         set_wallet_connected.set(false);
         set_wallet_address.set(String::new());
         set_wallet_type.set(String::new());
+        set_wallet_status.set(String::new());
     };
 
     // TradingView widget - using reactive key to force iframe reload
@@ -98,7 +98,7 @@ pub fn LandingPage() -> impl IntoView {
     let current_pair_label = move || find_pair(&current_pair.get()).label;
 
     let summon_dragrok = move |_| {
-        navigate(&"/whiteboard".to_string(), Default::default());
+        navigate("/whiteboard", Default::default());
     };
 
     view! {
@@ -235,6 +235,14 @@ pub fn LandingPage() -> impl IntoView {
                 border-left: 1px solid var(--neon-gold);
             }
 
+            .wallet-status {
+                margin-left: 12px;
+                max-width: 260px;
+                font-size: 11px;
+                color: #999900;
+                letter-spacing: 0.05em;
+            }
+
             .chart-container {
                 max-width: 1400px;
                 margin: 40px auto;
@@ -448,15 +456,16 @@ pub fn LandingPage() -> impl IntoView {
                                         <span class="dropdown-arrow">"▼"</span>
                                     </button>
                                     <div class="wallet-dropdown-menu">
-                                        <div class="dropdown-item" on:click=connect_metamask>"🦊 MetaMask"</div>
-                                        <div class="dropdown-item" on:click=connect_phantom>"👻 Phantom"</div>
-                                        <div class="dropdown-item" on:click=connect_jupiter>"🪐 Jupiter"</div>
-                                        <div class="dropdown-item" on:click=connect_walletconnect>"🔗 WalletConnect"</div>
+                                        <div class="dropdown-item" on:click=move |_| connect_wallet(SolanaWallet::Phantom)>"👻 Phantom"</div>
+                                        <div class="dropdown-item" on:click=move |_| connect_wallet(SolanaWallet::Solflare)>"🔆 Solflare"</div>
                                     </div>
                                 </div>
                             }.into_view()
                         }
                     }}
+                    <Show when=move || !wallet_status.get().is_empty()>
+                        <div class="wallet-status">{move || wallet_status.get()}</div>
+                    </Show>
                 </div>
             </div>
         </div>
@@ -4,5 +4,8 @@ pub mod dashboard;
 pub mod auth;
 pub mod dragon;
 pub mod whiteboard;
+pub mod whiteboard_room;
 pub mod chart;
+pub mod wallet;
+pub mod portfolio;
 
@@ -0,0 +1,213 @@
+use leptos::*;
+use leptos_meta::Style;
+use crate::portfolio::{Portfolio, PortfolioHolding};
+use crate::server::wallet_portfolio;
+
+/// "$1,234.56"; small prices keep more precision.
+fn format_usd(value: f64) -> String {
+    let sign = if value < 0.0 { "-" } else { "" };
+    let value = value.abs();
+    if value > 0.0 && value < 0.01 {
+        return format!("{}${:.6}", sign, value);
+    }
+    let cents = format!("{:.2}", value);
+    let (whole, fraction) = cents.split_once('.').unwrap_or((&cents, "00"));
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}${}.{}", sign, grouped, fraction)
+}
+
+fn format_amount(amount: f64) -> String {
+    if amount >= 1000.0 {
+        format!("{:.2}", amount)
+    } else {
+        format!("{:.4}", amount)
+    }
+}
+
+fn signed(value: Option<f64>, format: impl Fn(f64) -> String) -> String {
+    match value {
+        Some(v) if v >= 0.0 => format!("+{}", format(v)),
+        Some(v) => format(v),
+        None => "—".to_string(),
+    }
+}
+
+fn pnl_class(value: Option<f64>) -> &'static str {
+    match value {
+        Some(v) if v > 0.0 => "portfolio-up",
+        Some(v) if v < 0.0 => "portfolio-down",
+        _ => "",
+    }
+}
+
+/// SOL and SPL balances of `wallet` with USD value, allocation and 24h PnL.
+#[component]
+pub fn PortfolioPanel(
+    /// Linked wallet address; empty shows a hint instead
+    #[prop(into)]
+    wallet: Signal<String>,
+) -> impl IntoView {
+    let (refresh, set_refresh) = create_signal(0u32);
+    let portfolio = create_resource(
+        move || (wallet.get(), refresh.get()),
+        |(wallet, _)| async move {
+            if wallet.is_empty() {
+                return None;
+            }
+            Some(wallet_portfolio(wallet).await.map_err(|e| e.to_string()))
+        },
+    );
+
+    let holding_row = |holding: PortfolioHolding| {
+        let allocation = holding.allocation_pct.unwrap_or(0.0);
+        view! {
+            <div class="portfolio-row">
+                <span class="portfolio-symbol">{holding.symbol}</span>
+                <span>{format_amount(holding.amount)}</span>
+                <span>{holding.price_usd.map(format_usd).unwrap_or_else(|| "—".to_string())}</span>
+                <span>{holding.value_usd.map(format_usd).unwrap_or_else(|| "—".to_string())}</span>
+                <span class="portfolio-allocation">
+                    <span class="portfolio-bar" style=format!("width: {:.1}%;", allocation)></span>
+                    <span>{holding.allocation_pct.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "—".to_string())}</span>
+                </span>
+                <span class=pnl_class(holding.change_24h_pct)>
+                    {signed(holding.change_24h_pct, |p| format!("{:.2}%", p))}
+                </span>
+            </div>
+        }
+    };
+
+    let summary = move |portfolio: Portfolio| {
+        view! {
+            <div class="portfolio-summary">
+                <div>
+                    <div class="portfolio-label">"Total value"</div>
+                    <div class="portfolio-total">{format_usd(portfolio.total_usd)}</div>
+                </div>
+                <div>
+                    <div class="portfolio-label">"24h PnL"</div>
+                    <div class=format!("portfolio-total {}", pnl_class(portfolio.pnl_24h_usd))>
+                        {signed(portfolio.pnl_24h_usd, format_usd)}
+                        " "
+                        {portfolio.pnl_24h_pct.map(|p| format!("({:+.2}%)", p)).unwrap_or_default()}
+                    </div>
+                </div>
+            </div>
+            <div class="portfolio-row portfolio-head">
+                <span>"Token"</span>
+                <span>"Amount"</span>
+                <span>"Price"</span>
+                <span>"Value"</span>
+                <span>"Allocation"</span>
+                <span>"24h"</span>
+            </div>
+            {portfolio.holdings.into_iter().map(holding_row).collect_view()}
+        }
+    };
+
+    view! {
+        <Style>{r#"
+            .portfolio-panel {
+                padding: 16px 20px;
+                background: rgba(26, 26, 26, 0.8);
+                border-bottom: 1px solid var(--border-dim);
+                display: flex;
+                flex-direction: column;
+                gap: 10px;
+                flex-shrink: 0;
+                font-size: 11px;
+                color: #ccc;
+            }
+            .portfolio-header {
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                font-size: 11px;
+                font-weight: 600;
+                letter-spacing: 0.15em;
+                text-transform: uppercase;
+                color: #888;
+            }
+            .portfolio-summary {
+                display: flex;
+                gap: 32px;
+            }
+            .portfolio-label {
+                font-size: 10px;
+                color: #666;
+                text-transform: uppercase;
+                letter-spacing: 0.1em;
+            }
+            .portfolio-total {
+                font-size: 16px;
+                font-weight: 700;
+                color: #fff;
+            }
+            .portfolio-row {
+                display: grid;
+                grid-template-columns: 1fr 1fr 1fr 1fr 2fr 0.8fr;
+                gap: 12px;
+                align-items: center;
+            }
+            .portfolio-head {
+                color: #666;
+                font-size: 10px;
+                text-transform: uppercase;
+            }
+            .portfolio-symbol {
+                color: #fff;
+                font-weight: 600;
+            }
+            .portfolio-allocation {
+                display: flex;
+                gap: 8px;
+                align-items: center;
+            }
+            .portfolio-bar {
+                height: 4px;
+                max-width: 60%;
+                border-radius: 2px;
+                background: #ff6b35;
+            }
+            .portfolio-up {
+                color: #00ff88;
+            }
+            .portfolio-down {
+                color: #ff3333;
+            }
+        "#}</Style>
+
+        <div class="portfolio-panel">
+            <div class="portfolio-header">
+                "💼 Wallet Portfolio"
+                <button
+                    class="verify-btn"
+                    disabled=move || wallet.get().is_empty()
+                    on:click=move |_| set_refresh.update(|n| *n += 1)
+                >
+                    "Refresh"
+                </button>
+            </div>
+            <Suspense fallback=|| view! { <div class="portfolio-label">"Loading balances..."</div> }>
+                {move || match portfolio.get() {
+                    None => ().into_view(),
+                    Some(None) => view! {
+                        <div class="portfolio-label">"Sign in with a wallet to see its balances"</div>
+                    }
+                    .into_view(),
+                    Some(Some(Ok(portfolio))) => summary(portfolio).into_view(),
+                    Some(Some(Err(e))) => view! {
+                        <div class="nft-status error">{format!("Could not load portfolio: {}", e)}</div>
+                    }
+                    .into_view(),
+                }}
+            </Suspense>
+        </div>
+    }
+}
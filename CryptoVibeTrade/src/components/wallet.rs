@@ -0,0 +1,117 @@
+use crate::server::{request_wallet_challenge, wallet_sign_in, LoginResponse};
+use js_sys::{Array, Function, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// Browser wallets that inject a Solana provider with `connect` and
+/// `signMessage`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolanaWallet {
+    Phantom,
+    Solflare,
+}
+
+impl SolanaWallet {
+    pub fn label(self) -> &'static str {
+        match self {
+            SolanaWallet::Phantom => "Phantom",
+            SolanaWallet::Solflare => "Solflare",
+        }
+    }
+
+    fn install_url(self) -> &'static str {
+        match self {
+            SolanaWallet::Phantom => "https://phantom.app",
+            SolanaWallet::Solflare => "https://solflare.com",
+        }
+    }
+
+    /// The injected provider, if the extension is installed.
+    fn provider(self) -> Option<JsValue> {
+        let window: JsValue = web_sys::window()?.into();
+        let provider = match self {
+            SolanaWallet::Phantom => Reflect::get(&window, &"phantom".into())
+                .ok()
+                .filter(|phantom| phantom.is_object())
+                .and_then(|phantom| Reflect::get(&phantom, &"solana".into()).ok())
+                .filter(|solana| solana.is_object())
+                .or_else(|| Reflect::get(&window, &"solana".into()).ok())?,
+            SolanaWallet::Solflare => Reflect::get(&window, &"solflare".into()).ok()?,
+        };
+        provider.is_object().then_some(provider)
+    }
+}
+
+/// Connected wallet address and the server's answer to its signed challenge.
+pub struct WalletSession {
+    pub address: String,
+    pub response: LoginResponse,
+}
+
+/// Connect `wallet`, sign the server's challenge with it and redeem the
+/// signature for a session (or a wallet link when already logged in).
+pub async fn sign_in_with_wallet(wallet: SolanaWallet) -> Result<WalletSession, String> {
+    let provider = wallet
+        .provider()
+        .ok_or_else(|| format!("{} not found. Install it from {}", wallet.label(), wallet.install_url()))?;
+
+    let connected = call_async(&provider, "connect", &Array::new()).await?;
+    // Solflare resolves `connect` with nothing and exposes the key on the provider
+    let key_holder = if connected.is_object() { connected } else { provider.clone() };
+    let public_key = Reflect::get(&key_holder, &"publicKey".into()).map_err(js_error)?;
+    let address = call(&public_key, "toString", &Array::new())?
+        .as_string()
+        .ok_or_else(|| "Wallet did not return a public key".to_string())?;
+
+    let challenge = request_wallet_challenge(address.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let message = Uint8Array::from(challenge.message.as_bytes());
+    let signed = call_async(&provider, "signMessage", &Array::of2(&message, &"utf8".into())).await?;
+    // Phantom resolves `{ signature }`, Solflare the bytes themselves
+    let signature = if signed.is_instance_of::<Uint8Array>() {
+        signed
+    } else {
+        Reflect::get(&signed, &"signature".into()).map_err(js_error)?
+    };
+    let signature = bs58::encode(Uint8Array::new(&signature).to_vec()).into_string();
+
+    let response = wallet_sign_in(address.clone(), challenge.nonce, signature)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(WalletSession { address, response })
+}
+
+/// Ask the wallet to forget this site. Failures are ignored.
+pub async fn disconnect_wallet(wallet: SolanaWallet) {
+    if let Some(provider) = wallet.provider() {
+        let _ = call_async(&provider, "disconnect", &Array::new()).await;
+    }
+}
+
+fn call(target: &JsValue, method: &str, args: &Array) -> Result<JsValue, String> {
+    let function: Function = Reflect::get(target, &method.into())
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| format!("Wallet does not support {}", method))?;
+    function.apply(target, args).map_err(js_error)
+}
+
+async fn call_async(target: &JsValue, method: &str, args: &Array) -> Result<JsValue, String> {
+    let result = call(target, method, args)?;
+    if result.is_instance_of::<Promise>() {
+        JsFuture::from(Promise::from(result)).await.map_err(js_error)
+    } else {
+        Ok(result)
+    }
+}
+
+/// Readable text for a rejected wallet call (e.g. the user declined).
+fn js_error(error: JsValue) -> String {
+    Reflect::get(&error, &"message".into())
+        .ok()
+        .and_then(|message| message.as_string())
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| "Wallet request failed".to_string())
+}
@@ -1,8 +1,48 @@
 use leptos::*;
 use leptos_meta::Style;
-use web_sys::{CanvasRenderingContext2d, MouseEvent, WheelEvent, KeyboardEvent, HtmlCanvasElement, Element, DomRect};
-use wasm_bindgen::{JsCast, JsValue};
-use crate::types::{Tool, Color, Stroke, StrokePoint};
+use web_sys::{
+    CanvasRenderingContext2d, MouseEvent, PointerEvent, WheelEvent, KeyboardEvent, HtmlAnchorElement,
+    HtmlCanvasElement, HtmlInputElement, Element, DomRect,
+};
+use wasm_bindgen::JsCast;
+use crate::types::{
+    pressure_width, text_font_size, ChartInvocation, ChartPosition, Tool, Color, Stroke, StrokePoint,
+    TEXT_LINE_HEIGHT,
+};
+use crate::board_export::{strokes_to_svg, BoardDocument};
+use crate::board_geometry::{
+    chaikin, decimate, scale, selection_bounds, strokes_in_rect, topmost_hit, Rect, Viewport, ERASER_SCALE,
+};
+use crate::board_history::{BoardEdit, History, StrokeStyle};
+use crate::boards::{validate_board_charts, BoardRevision, BoardRevisionContent, BoardSummary, MAX_BOARD_CHARTS};
+use crate::components::chart::{LiveChart, OVERLAY_INDICATORS};
+use crate::components::whiteboard_room::use_whiteboard_room;
+use crate::server::{
+    create_board, list_board_revisions, list_boards, load_board, load_board_revision,
+    rename_board, restore_board_revision, save_board, share_board,
+};
+use uuid::Uuid;
+
+/// Quiet period after the last edit before the board is autosaved
+const AUTOSAVE_DELAY_MS: u64 = 1500;
+/// Side of the selection resize handles, in screen pixels
+const HANDLE_SIZE: f64 = 8.0;
+const SELECTION_COLOR: &str = "#ff6b35";
+/// Freehand samples closer than this to the previous one are dropped, in screen pixels
+const MIN_POINT_SPACING: f64 = 1.5;
+/// Chaikin passes applied to finished freehand strokes when smoothing is on
+const SMOOTHING_PASSES: usize = 2;
+/// Largest deviation decimation may introduce, in screen pixels
+const DECIMATE_TOLERANCE: f64 = 0.75;
+/// Zoom step of the keyboard shortcuts
+const KEY_ZOOM_STEP: f64 = 1.1;
+/// Ctrl-wheel zoom per pixel of scroll; exponential, so in and out cancel out
+const WHEEL_ZOOM_RATE: f64 = 0.0015;
+/// Size of a summoned chart widget, in percent of the board
+const CHART_WIDTH_PCT: f64 = 30.0;
+const CHART_HEIGHT_PCT: f64 = 35.0;
+/// Kline intervals offered when summoning a chart
+const CHART_TIMEFRAMES: &[&str] = &["1m", "5m", "15m", "1h", "4h", "1d"];
 
 #[component]
 pub fn Whiteboard(
@@ -19,27 +59,104 @@ pub fn Whiteboard(
     let (thickness, set_thickness) = create_signal(2.0);
     let (is_drawing, set_is_drawing) = create_signal(false);
 
-    // Undo/Redo stacks
+    // Strokes and their undo/redo history
     let (strokes, set_strokes) = create_signal(Vec::<Stroke>::new());
-    let (undo_stack, set_undo_stack) = create_signal(Vec::<Stroke>::new());
+    let (history, set_history) = create_signal(History::default());
+
+    // Select tool: chosen stroke indices and the gesture in progress
+    let (selection, set_selection) = create_signal(Vec::<usize>::new());
+    let (select_drag, set_select_drag) = create_signal(None::<SelectDrag>);
 
     // Pan/Zoom state
-    let (offset_x, set_offset_x) = create_signal(0.0);
-    let (offset_y, set_offset_y) = create_signal(0.0);
-    let (zoom, set_zoom) = create_signal(1.0);
+    let (viewport, set_viewport) = create_signal(Viewport::default());
 
     // Current stroke being drawn
     let (current_stroke, set_current_stroke) = create_signal(Vec::<StrokePoint>::new());
+    let (smoothing, set_smoothing) = create_signal(true);
+
+    // Touch pointers currently down (id, screen x, screen y) and the pinch they drive
+    let touches = store_value(Vec::<(i32, f64, f64)>::new());
+    let pinch = store_value(None::<Pinch>);
+
+    // Inline text editor, and a text stroke being dragged by the Text tool
+    let (text_editor, set_text_editor) = create_signal(None::<TextEditor>);
+    let text_drag = store_value(None::<TextDrag>);
+    let text_input_ref: NodeRef<leptos::html::Textarea> = create_node_ref();
 
     // Context menu state for "Summon Dragrok"
     let (show_context_menu, set_show_context_menu) = create_signal(false);
     let (context_menu_x, set_context_menu_x) = create_signal(0.0);
     let (context_menu_y, set_context_menu_y) = create_signal(0.0);
     let (dragrok_summoned, set_dragrok_summoned) = create_signal(false);
+    // Board point that was right-clicked, where a summoned chart goes
+    let summon_at = store_value(StrokePoint::new(0.0, 0.0));
+    let (chart_symbol, set_chart_symbol) = create_signal("BTCUSDT".to_string());
+    let (chart_timeframe, set_chart_timeframe) = create_signal("1m".to_string());
+    let (chart_indicators, set_chart_indicators) = create_signal(vec!["ema".to_string()]);
+
+    // Live chart widgets on the board, saved with it and drawn under the strokes
+    let (charts, set_charts) = create_signal(Vec::<ChartInvocation>::new());
 
     // Fire particle system
     let (particles, set_particles) = create_signal(Vec::<FireParticle>::new());
 
+    // Saved boards
+    let (boards, set_boards) = create_signal(Vec::<BoardSummary>::new());
+    let (board_id, set_board_id) = create_signal(None::<Uuid>);
+    let (board_name, set_board_name) = create_signal(String::new());
+    let (save_status, set_save_status) = create_signal(String::new());
+    let (show_history, set_show_history) = create_signal(false);
+    let (revisions, set_revisions) = create_signal(Vec::<BoardRevision>::new());
+    // Read-only view of an older revision, strokes and charts
+    let (preview, set_preview) = create_signal(None::<BoardRevisionContent>);
+    // JSON of the strokes and charts last loaded or saved, to tell real edits apart
+    let saved_json = store_value(String::new());
+    // Bumped on every edit so only the last pending autosave runs
+    let save_generation = store_value(0u64);
+
+    // Shared room; while connected, edits go through the server
+    let room = use_whiteboard_room(set_strokes);
+    // Board id of a room shared with us; blank joins the open board's room
+    let (room_input, set_room_input) = create_signal(String::new());
+    let (share_input, set_share_input) = create_signal(String::new());
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TextEditor {
+        /// Index of the text stroke being re-edited, `None` for new text
+        index: Option<usize>,
+        at: StrokePoint,
+        value: String,
+        color: Color,
+        thickness: f64,
+    }
+
+    #[derive(Clone, Debug)]
+    enum SelectDrag {
+        /// Dragging the selection, recorded as one history transaction
+        Move { indices: Vec<usize>, last: StrokePoint },
+        /// Dragging a corner handle; `anchor` is the opposite corner
+        Resize { anchor: StrokePoint, corner: StrokePoint, before: Vec<(usize, Stroke)> },
+        /// Rubber band from `start` to `current`
+        Band { start: StrokePoint, current: StrokePoint, additive: bool },
+    }
+
+    /// Two-finger gesture: zoom follows the finger spread and `anchor`
+    /// (the board point first under the fingers' midpoint) stays under it.
+    #[derive(Clone, Copy, Debug)]
+    struct Pinch {
+        distance: f64,
+        zoom: f64,
+        anchor: StrokePoint,
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    struct TextDrag {
+        index: usize,
+        grab: StrokePoint,
+        origin: StrokePoint,
+        moved: bool,
+    }
+
     #[derive(Clone, Debug)]
     struct FireParticle {
         x: f64,
@@ -58,8 +175,6 @@ pub fn Whiteboard(
 
     let get_context = move || -> Option<CanvasRenderingContext2d> {
         canvas_ref.get().and_then(|canvas| {
-            // Use leptos_html_element_to_web_sys pattern
-            use leptos::leptos_dom::HtmlElement;
             let web_canvas = canvas.into_any();
             web_canvas.dyn_ref::<HtmlCanvasElement>().and_then(|canvas| {
                 canvas.get_context("2d").ok().flatten()
@@ -79,26 +194,37 @@ pub fn Whiteboard(
         ctx.set_line_join("round");
 
         let color_str = format!("{}", stroke.color);
-        ctx.set_stroke_style(&JsValue::from_str(&color_str));
+        ctx.set_stroke_style_str(&color_str);
 
         match stroke.tool {
             Tool::Pen | Tool::Eraser => {
                 if stroke.tool == Tool::Eraser {
-                    ctx.set_global_composite_operation("destination-out");
-                    ctx.set_line_width(stroke.thickness * 5.0);
+                    let _ = ctx.set_global_composite_operation("destination-out");
+                    ctx.set_line_width(stroke.thickness * ERASER_SCALE);
                 } else {
-                    ctx.set_global_composite_operation("source-over");
+                    let _ = ctx.set_global_composite_operation("source-over");
                 }
 
-                let mut points = stroke.points.iter().peekable();
-                if let Some(first) = points.next() {
-                    ctx.move_to(first.x, first.y);
-                    while let Some(point) = points.next() {
-                        ctx.line_to(point.x, point.y);
+                if stroke.tool == Tool::Pen && stroke.has_pressure() && stroke.points.len() >= 2 {
+                    // Each segment takes the average pressure of its ends
+                    for w in stroke.points.windows(2) {
+                        ctx.begin_path();
+                        ctx.set_line_width(pressure_width(stroke.thickness, (w[0].pressure + w[1].pressure) / 2.0));
+                        ctx.move_to(w[0].x, w[0].y);
+                        ctx.line_to(w[1].x, w[1].y);
+                        ctx.stroke();
+                    }
+                } else {
+                    let mut points = stroke.points.iter();
+                    if let Some(first) = points.next() {
+                        ctx.move_to(first.x, first.y);
+                        for point in points {
+                            ctx.line_to(point.x, point.y);
+                        }
                     }
+                    ctx.stroke();
                 }
-                ctx.stroke();
-                ctx.set_global_composite_operation("source-over");
+                let _ = ctx.set_global_composite_operation("source-over");
             }
             Tool::Line => {
                 if stroke.points.len() >= 2 {
@@ -126,28 +252,52 @@ pub fn Whiteboard(
                     let edge = &stroke.points[stroke.points.len() - 1];
                     let radius = ((edge.x - center.x).powi(2) + (edge.y - center.y).powi(2)).sqrt();
                     ctx.begin_path();
-                    ctx.arc(center.x, center.y, radius, 0.0, std::f64::consts::PI * 2.0);
+                    let _ = ctx.arc(center.x, center.y, radius, 0.0, std::f64::consts::PI * 2.0);
                     ctx.stroke();
                 }
             }
             Tool::Text => {
-                // TODO: Implement text input
+                let (Some(text), Some(origin)) = (stroke.text.as_ref(), stroke.points.first()) else {
+                    return;
+                };
+                let size = stroke.font_size();
+                ctx.set_font(&format!("{}px 'SF Mono', 'Fira Code', monospace", size));
+                ctx.set_text_baseline("top");
+                ctx.set_fill_style_str(&color_str);
+                for (i, line) in text.lines().enumerate() {
+                    let _ = ctx.fill_text(line, origin.x, origin.y + i as f64 * size * TEXT_LINE_HEIGHT);
+                }
             }
+            Tool::Select => {}
         }
     };
 
     let redraw_all = move || {
         if let Some(ctx) = get_context() {
-            let _ = ctx.clear_rect(0.0, 0.0, width as f64, height as f64);
+            ctx.clear_rect(0.0, 0.0, width as f64, height as f64);
 
             // Apply pan/zoom transformation
             ctx.save();
-            ctx.translate(offset_x.get(), offset_y.get());
-            ctx.scale(zoom.get(), zoom.get());
-
-            // Draw all strokes
-            for stroke in strokes.get().iter() {
-                draw_stroke(&ctx, stroke);
+            let view = viewport.get();
+            let _ = ctx.translate(view.offset_x, view.offset_y);
+            let _ = ctx.scale(view.zoom, view.zoom);
+
+            // Draw all strokes (or the revision being previewed)
+            match preview.get() {
+                Some(revision) => {
+                    for stroke in revision.strokes.iter() {
+                        draw_stroke(&ctx, stroke);
+                    }
+                }
+                None => {
+                    // Text open in the editor is shown by the editor instead
+                    let editing = text_editor.with(|e| e.as_ref().and_then(|e| e.index));
+                    for (i, stroke) in strokes.get().iter().enumerate() {
+                        if Some(i) != editing {
+                            draw_stroke(&ctx, stroke);
+                        }
+                    }
+                }
             }
 
             // Draw current stroke
@@ -157,67 +307,551 @@ pub fn Whiteboard(
                     color: current_color.get(),
                     thickness: thickness.get(),
                     tool: current_tool.get(),
+                    text: None,
                 };
                 draw_stroke(&ctx, &temp_stroke);
             }
 
+            // Selection outline with corner handles, and the rubber band
+            if preview.with(|p| p.is_none()) {
+                let z = view.zoom;
+                let bounds = selection.with(|selected| strokes.with(|all| selection_bounds(all, selected)));
+                if let Some(bounds) = bounds {
+                    ctx.set_stroke_style_str(SELECTION_COLOR);
+                    ctx.set_fill_style_str(SELECTION_COLOR);
+                    ctx.set_line_width(1.0 / z);
+                    ctx.stroke_rect(bounds.x0, bounds.y0, bounds.width(), bounds.height());
+                    let size = HANDLE_SIZE / z;
+                    for corner in bounds.corners() {
+                        ctx.fill_rect(corner.x - size / 2.0, corner.y - size / 2.0, size, size);
+                    }
+                }
+                if let Some(SelectDrag::Band { start, current, .. }) = select_drag.get() {
+                    let band = Rect::from_corners(start, current);
+                    ctx.set_fill_style_str("rgba(255, 107, 53, 0.12)");
+                    ctx.fill_rect(band.x0, band.y0, band.width(), band.height());
+                    ctx.set_stroke_style_str(SELECTION_COLOR);
+                    ctx.set_line_width(1.0 / z);
+                    ctx.stroke_rect(band.x0, band.y0, band.width(), band.height());
+                }
+            }
+
             ctx.restore();
         }
     };
 
     // =====================
-    // MOUSE EVENT HANDLERS
+    // EDIT HISTORY
     // =====================
 
-    let on_mouse_down = move |ev: MouseEvent| {
-        let rect = match canvas_ref.get() {
-            Some(canvas) => {
-                let web_elem = canvas.into_any();
-                let element: Element = match web_elem.dyn_ref::<Element>() {
-                    Some(e) => e.clone(),
-                    None => return,
-                };
-                element.get_bounding_client_rect()
-            },
-            None => return,
+    // Apply a new edit and make it undoable
+    let commit_edit = move |edit: BoardEdit| {
+        set_strokes.update(|all| set_history.update(|h| h.apply(edit, all)));
+    };
+
+    // Make an edit undoable whose effect is already on the board
+    let record_edit = move |edit: BoardEdit| {
+        set_history.update(|h| h.record(edit));
+    };
+
+    // Toolbar and keyboard both step through here
+    let step_history = move |forward: bool| {
+        let mut all = strokes.get_untracked();
+        let changed = set_history
+            .try_update(|h| if forward { h.redo(&mut all) } else { h.undo(&mut all) })
+            .unwrap_or(false);
+        if changed {
+            set_strokes.set(all);
+            set_selection.set(Vec::new());
+        }
+    };
+
+    let undo = move || {
+        if room.is_connected() {
+            room.undo();
+        } else {
+            step_history(false);
+        }
+    };
+
+    // Redo is local only; undone room strokes are gone for everyone
+    let redo = move || {
+        if !room.is_connected() {
+            step_history(true);
+        }
+    };
+
+    let forget_history = move || {
+        set_history.update(|h| h.clear());
+        set_selection.set(Vec::new());
+    };
+
+    // Apply a style change to the selected strokes
+    let restyle_selection = move |change: &dyn Fn(&mut StrokeStyle)| {
+        let selected = selection.get_untracked();
+        if let Some(edit) = strokes.with_untracked(|all| BoardEdit::restyle(all, &selected, change)) {
+            commit_edit(edit);
+        }
+    };
+
+    // =====================
+    // SELECT TOOL
+    // =====================
+
+    let snapshot = move |indices: &[usize]| -> Vec<(usize, Stroke)> {
+        strokes.with_untracked(|all| {
+            indices
+                .iter()
+                .filter_map(|&i| all.get(i).map(|stroke| (i, stroke.clone())))
+                .collect()
+        })
+    };
+
+    let delete_selection = move || {
+        let selected = selection.get_untracked();
+        if let Some(edit) = strokes.with_untracked(|all| BoardEdit::remove(all, &selected)) {
+            commit_edit(edit);
+        }
+        set_selection.set(Vec::new());
+    };
+
+    // Selections only make sense while the Select tool is active
+    create_effect(move |_| {
+        if current_tool.get() != Tool::Select {
+            set_selection.set(Vec::new());
+        }
+    });
+
+    // =====================
+    // TEXT TOOL
+    // =====================
+
+    // Write the open editor back to the board; emptying existing text deletes it
+    let commit_text = move || {
+        let Some(editor) = text_editor.get_untracked() else {
+            return;
         };
+        set_text_editor.set(None);
+        let value = editor.value.trim_end().to_string();
+
+        match editor.index {
+            Some(index) => {
+                let edit = strokes.with_untracked(|all| {
+                    let before = all.get(index)?.clone();
+                    if value.trim().is_empty() {
+                        return BoardEdit::remove(all, &[index]);
+                    }
+                    let mut after = before.clone();
+                    after.text = Some(value);
+                    (after != before).then(|| BoardEdit::Update {
+                        changes: vec![(index, before, after)],
+                    })
+                });
+                if let Some(edit) = edit {
+                    commit_edit(edit);
+                }
+            }
+            None if value.trim().is_empty() => {}
+            None => {
+                let stroke = Stroke::new_text(editor.at, value, editor.color, editor.thickness);
+                if room.is_connected() {
+                    room.add_stroke(stroke);
+                } else {
+                    commit_edit(BoardEdit::Add { stroke });
+                }
+            }
+        }
+        redraw_all();
+    };
+
+    let on_text_key = move |ev: KeyboardEvent| match ev.key().as_str() {
+        // Shift+Enter inserts a newline
+        "Enter" if !ev.shift_key() => {
+            ev.prevent_default();
+            commit_text();
+        }
+        "Escape" => {
+            set_text_editor.set(None);
+            redraw_all();
+        }
+        _ => {}
+    };
 
-        let x = (ev.client_x() as f64 - rect.left()) / zoom.get() - offset_x.get();
-        let y = (ev.client_y() as f64 - rect.top()) / zoom.get() - offset_y.get();
+    // Focus the editor whenever it is (re)mounted
+    create_effect(move |_| {
+        if let Some(input) = text_input_ref.get() {
+            let _ = input.focus();
+        }
+    });
 
-        set_is_drawing.set(true);
-        set_current_stroke.set(vec![StrokePoint { x, y }]);
+    // =====================
+    // POINTER EVENT HANDLERS
+    // =====================
+
+    // Pointer position relative to the canvas, in screen pixels
+    let canvas_point = move |ev: &PointerEvent| -> Option<(f64, f64)> {
+        let canvas = canvas_ref.get()?;
+        let web_elem = canvas.into_any();
+        let rect: DomRect = web_elem.dyn_ref::<Element>()?.get_bounding_client_rect();
+        Some((ev.client_x() as f64 - rect.left(), ev.client_y() as f64 - rect.top()))
     };
 
-    let on_mouse_move = move |ev: MouseEvent| {
-        if !is_drawing.get() {
+    // Drop whatever one pointer was doing, e.g. when a second finger turns
+    // the gesture into a pinch. Moves made so far stay as one undo step.
+    let cancel_gesture = move || {
+        if is_drawing.get_untracked() {
+            set_is_drawing.set(false);
+            set_current_stroke.set(Vec::new());
+        }
+        if let Some(drag) = text_drag.get_value() {
+            text_drag.set_value(None);
+            if drag.moved {
+                set_strokes.update(|all| {
+                    if let Some(stroke) = all.get_mut(drag.index) {
+                        stroke.points[0] = drag.origin;
+                    }
+                });
+            }
+        }
+        match select_drag.get_untracked() {
+            Some(SelectDrag::Move { .. }) => set_history.update(|h| h.commit()),
+            Some(SelectDrag::Resize { before, .. }) => set_strokes.update(|all| {
+                for (i, original) in before {
+                    if let Some(slot) = all.get_mut(i) {
+                        *slot = original;
+                    }
+                }
+            }),
+            Some(SelectDrag::Band { .. }) | None => {}
+        }
+        set_select_drag.set(None);
+    };
+
+    // Pin the pinch anchor under the fingers' midpoint at the new spread
+    let update_pinch = move || {
+        let Some(start) = pinch.get_value() else {
+            return;
+        };
+        let Some(((ax, ay), (bx, by))) = touches.with_value(|t| match t.as_slice() {
+            [(_, ax, ay), (_, bx, by), ..] => Some(((*ax, *ay), (*bx, *by))),
+            _ => None,
+        }) else {
+            return;
+        };
+        let distance = (bx - ax).hypot(by - ay).max(1.0);
+        let (mid_x, mid_y) = ((ax + bx) / 2.0, (ay + by) / 2.0);
+        set_viewport.set(Viewport::anchored(
+            start.zoom * distance / start.distance,
+            start.anchor,
+            (mid_x, mid_y),
+        ));
+        redraw_all();
+    };
+
+    let on_pointer_down = move |ev: PointerEvent| {
+        set_show_context_menu.set(false);
+        if preview.get_untracked().is_some() {
             return;
         }
+        // Right and middle clicks are left to the context menu
+        if ev.pointer_type() == "mouse" && ev.button() != 0 {
+            return;
+        }
+        let Some((sx, sy)) = canvas_point(&ev) else {
+            return;
+        };
 
-        let rect = match canvas_ref.get() {
-            Some(canvas) => {
-                let web_elem = canvas.into_any();
-                let element: Element = match web_elem.dyn_ref::<Element>() {
-                    Some(e) => e.clone(),
-                    None => return,
-                };
-                element.get_bounding_client_rect()
-            },
-            None => return,
+        if ev.pointer_type() == "touch" {
+            touches.update_value(|t| {
+                t.retain(|(id, _, _)| *id != ev.pointer_id());
+                t.push((ev.pointer_id(), sx, sy));
+            });
+            if touches.with_value(|t| t.len()) >= 2 {
+                cancel_gesture();
+                let ((ax, ay), (bx, by)) = touches.with_value(|t| ((t[0].1, t[0].2), (t[1].1, t[1].2)));
+                let view = viewport.get_untracked();
+                pinch.set_value(Some(Pinch {
+                    distance: (bx - ax).hypot(by - ay).max(1.0),
+                    zoom: view.zoom,
+                    anchor: view.to_world((ax + bx) / 2.0, (ay + by) / 2.0),
+                }));
+                redraw_all();
+                return;
+            }
+        }
+
+        // Keep receiving moves while the pointer is outside the canvas
+        if let Some(target) = ev.target().and_then(|t| t.dyn_into::<Element>().ok()) {
+            let _ = target.set_pointer_capture(ev.pointer_id());
+        }
+
+        let view = viewport.get_untracked();
+        let StrokePoint { x, y, .. } = view.to_world(sx, sy);
+
+        if current_tool.get_untracked() == Tool::Text {
+            // Keep focus in the editor that is about to open
+            ev.prevent_default();
+            commit_text();
+            // Grab existing text to move it, or re-edit it on a plain click.
+            // Room strokes can only be added or removed, so not while shared.
+            let hit = if room.is_connected() {
+                None
+            } else {
+                strokes.with_untracked(|all| {
+                    all.iter().rposition(|s| s.tool == Tool::Text && s.text_contains(x, y, 4.0 / view.zoom))
+                })
+            };
+            match hit {
+                Some(index) => {
+                    let origin = strokes.with_untracked(|all| all[index].points[0]);
+                    text_drag.set_value(Some(TextDrag {
+                        index,
+                        grab: StrokePoint::new(x, y),
+                        origin,
+                        moved: false,
+                    }));
+                }
+                None => set_text_editor.set(Some(TextEditor {
+                    index: None,
+                    at: StrokePoint::new(x, y),
+                    value: String::new(),
+                    color: current_color.get_untracked(),
+                    thickness: thickness.get_untracked(),
+                })),
+            }
+            return;
+        }
+
+        if current_tool.get_untracked() == Tool::Select {
+            // Room strokes can only be added or removed
+            if room.is_connected() {
+                return;
+            }
+            let p = StrokePoint::new(x, y);
+            let z = view.zoom;
+            let selected = selection.get_untracked();
+
+            // Corner handles resize the whole selection
+            if let Some(bounds) = strokes.with_untracked(|all| selection_bounds(all, &selected)) {
+                let reach = HANDLE_SIZE / z;
+                let corners = bounds.corners();
+                if let Some(i) = corners.iter().position(|c| (c.x - x).abs() <= reach && (c.y - y).abs() <= reach) {
+                    set_select_drag.set(Some(SelectDrag::Resize {
+                        anchor: corners[(i + 2) % 4],
+                        corner: corners[i],
+                        before: snapshot(&selected),
+                    }));
+                    return;
+                }
+            }
+
+            match strokes.with_untracked(|all| topmost_hit(all, p, 4.0 / z)) {
+                // Shift-click toggles a stroke out of the selection
+                Some(hit) if ev.shift_key() && selected.contains(&hit) => {
+                    set_selection.update(|selected| selected.retain(|&i| i != hit));
+                }
+                Some(hit) => {
+                    let mut selected = selected;
+                    if ev.shift_key() {
+                        selected.push(hit);
+                    } else if !selected.contains(&hit) {
+                        selected = vec![hit];
+                    }
+                    set_history.update(|h| h.begin());
+                    set_select_drag.set(Some(SelectDrag::Move {
+                        indices: selected.clone(),
+                        last: p,
+                    }));
+                    set_selection.set(selected);
+                }
+                None => {
+                    if !ev.shift_key() {
+                        set_selection.set(Vec::new());
+                    }
+                    set_select_drag.set(Some(SelectDrag::Band {
+                        start: p,
+                        current: p,
+                        additive: ev.shift_key(),
+                    }));
+                }
+            }
+            return;
+        }
+
+        set_is_drawing.set(true);
+        set_current_stroke.set(vec![sample_point(&ev, x, y, current_tool.get_untracked())]);
+    };
+
+    let on_pointer_move = move |ev: PointerEvent| {
+        let Some((sx, sy)) = canvas_point(&ev) else {
+            return;
         };
 
-        let x = (ev.client_x() as f64 - rect.left()) / zoom.get() - offset_x.get();
-        let y = (ev.client_y() as f64 - rect.top()) / zoom.get() - offset_y.get();
+        if ev.pointer_type() == "touch" {
+            touches.update_value(|t| {
+                if let Some(touch) = t.iter_mut().find(|(id, _, _)| *id == ev.pointer_id()) {
+                    *touch = (ev.pointer_id(), sx, sy);
+                }
+            });
+            if pinch.with_value(|p| p.is_some()) {
+                update_pinch();
+                return;
+            }
+        }
+
+        let view = viewport.get_untracked();
+        let StrokePoint { x, y, .. } = view.to_world(sx, sy);
+
+        room.move_cursor(x, y);
+
+        if let Some(drag) = select_drag.get_untracked() {
+            match drag {
+                SelectDrag::Move { indices, last } => {
+                    // Each step joins the open transaction and merges into one move
+                    let (dx, dy) = (x - last.x, y - last.y);
+                    set_select_drag.set(Some(SelectDrag::Move {
+                        indices: indices.clone(),
+                        last: StrokePoint::new(x, y),
+                    }));
+                    commit_edit(BoardEdit::Move { indices, dx, dy });
+                }
+                SelectDrag::Resize { anchor, corner, before } => {
+                    // Scale so the dragged corner follows the pointer; never collapse to zero
+                    let factor = |to: f64, from: f64, at: f64| {
+                        let span = from - at;
+                        if span.abs() < 1e-6 {
+                            return 1.0;
+                        }
+                        let f = (to - at) / span;
+                        if f.abs() < 0.05 { 0.05f64.copysign(f) } else { f }
+                    };
+                    let (sx, sy) = (factor(x, corner.x, anchor.x), factor(y, corner.y, anchor.y));
+                    set_strokes.update(|all| {
+                        for (i, original) in before {
+                            if let Some(slot) = all.get_mut(i) {
+                                *slot = original;
+                                scale(slot, anchor, sx, sy);
+                            }
+                        }
+                    });
+                }
+                SelectDrag::Band { start, additive, .. } => {
+                    set_select_drag.set(Some(SelectDrag::Band {
+                        start,
+                        current: StrokePoint::new(x, y),
+                        additive,
+                    }));
+                }
+            }
+            return;
+        }
+
+        if let Some(mut drag) = text_drag.get_value() {
+            let (dx, dy) = (x - drag.grab.x, y - drag.grab.y);
+            if drag.moved || dx.hypot(dy) * view.zoom > 3.0 {
+                drag.moved = true;
+                text_drag.set_value(Some(drag));
+                set_strokes.update(|all| {
+                    if let Some(stroke) = all.get_mut(drag.index) {
+                        stroke.points[0] = StrokePoint::new(drag.origin.x + dx, drag.origin.y + dy);
+                    }
+                });
+            }
+            return;
+        }
+
+        if !is_drawing.get() {
+            return;
+        }
 
         let mut points = current_stroke.get();
-        points.push(StrokePoint { x, y });
+        // Shapes only need their latest corner; freehand skips samples too
+        // close together to change the line
+        let tool = current_tool.get_untracked();
+        if let Some(last) = points.last() {
+            if matches!(tool, Tool::Pen | Tool::Eraser)
+                && (x - last.x).hypot(y - last.y) * view.zoom < MIN_POINT_SPACING
+            {
+                return;
+            }
+        }
+        points.push(sample_point(&ev, x, y, tool));
         set_current_stroke.set(points);
 
         // Redraw for immediate feedback
         redraw_all();
     };
 
-    let on_mouse_up = move |_: MouseEvent| {
+    let on_pointer_up = move |ev: PointerEvent| {
+        if ev.pointer_type() == "touch" {
+            touches.update_value(|t| t.retain(|(id, _, _)| *id != ev.pointer_id()));
+            // Lifting either finger ends the pinch; the other one stays idle
+            if pinch.with_value(|p| p.is_some()) {
+                if touches.with_value(|t| t.len()) < 2 {
+                    pinch.set_value(None);
+                }
+                return;
+            }
+        }
+
+        if let Some(drag) = select_drag.get_untracked() {
+            set_select_drag.set(None);
+            match drag {
+                SelectDrag::Move { .. } => set_history.update(|h| h.commit()),
+                SelectDrag::Resize { before, .. } => {
+                    let changes: Vec<(usize, Stroke, Stroke)> = strokes.with_untracked(|all| {
+                        before
+                            .into_iter()
+                            .filter_map(|(i, before)| {
+                                let after = all.get(i)?;
+                                (*after != before).then(|| (i, before, after.clone()))
+                            })
+                            .collect()
+                    });
+                    if !changes.is_empty() {
+                        record_edit(BoardEdit::Update { changes });
+                    }
+                }
+                SelectDrag::Band { start, current, additive } => {
+                    let band = Rect::from_corners(start, current);
+                    let hits = strokes.with_untracked(|all| strokes_in_rect(all, &band));
+                    set_selection.update(|selected| {
+                        if !additive {
+                            selected.clear();
+                        }
+                        for i in hits {
+                            if !selected.contains(&i) {
+                                selected.push(i);
+                            }
+                        }
+                    });
+                }
+            }
+            return;
+        }
+
+        if let Some(drag) = text_drag.get_value() {
+            text_drag.set_value(None);
+            if drag.moved {
+                if let Some(at) = strokes.with_untracked(|all| all.get(drag.index).map(|s| s.points[0])) {
+                    record_edit(BoardEdit::Move {
+                        indices: vec![drag.index],
+                        dx: at.x - drag.origin.x,
+                        dy: at.y - drag.origin.y,
+                    });
+                }
+            } else if let Some(stroke) = strokes.with_untracked(|all| all.get(drag.index).cloned()) {
+                set_text_editor.set(Some(TextEditor {
+                    index: Some(drag.index),
+                    at: drag.origin,
+                    value: stroke.text.unwrap_or_default(),
+                    color: stroke.color,
+                    thickness: stroke.thickness,
+                }));
+            }
+            return;
+        }
+
         if !is_drawing.get() {
             return;
         }
@@ -225,20 +859,33 @@ pub fn Whiteboard(
         set_is_drawing.set(false);
 
         // Save the stroke
+        let tool = current_tool.get();
         let stroke = Stroke {
-            points: current_stroke.get(),
+            points: finish_points(current_stroke.get(), tool, smoothing.get_untracked(), viewport.get_untracked().zoom),
             color: current_color.get(),
             thickness: thickness.get(),
-            tool: current_tool.get(),
+            tool,
+            text: None,
         };
 
-        let mut all_strokes = strokes.get();
-        all_strokes.push(stroke);
-        set_strokes.set(all_strokes);
+        if room.is_connected() {
+            room.add_stroke(stroke);
+        } else {
+            commit_edit(BoardEdit::Add { stroke });
+        }
 
         // Clear current stroke
         set_current_stroke.set(Vec::new());
-        set_undo_stack.set(Vec::new()); // Clear redo stack on new action
+        redraw_all();
+    };
+
+    // The browser took the pointer away (palm rejection, scrolling, ...)
+    let on_pointer_cancel = move |ev: PointerEvent| {
+        touches.update_value(|t| t.retain(|(id, _, _)| *id != ev.pointer_id()));
+        if touches.with_value(|t| t.len()) < 2 {
+            pinch.set_value(None);
+        }
+        cancel_gesture();
         redraw_all();
     };
 
@@ -249,39 +896,78 @@ pub fn Whiteboard(
     let on_context_menu = move |ev: MouseEvent| {
         ev.prevent_default();
 
-        // Position context menu at mouse coordinates
-        let x = ev.client_x() as f64;
-        let y = ev.client_y() as f64;
+        let rect = canvas_ref
+            .get()
+            .and_then(|canvas| canvas.into_any().dyn_ref::<Element>().map(|e| e.get_bounding_client_rect()));
+        let Some(rect) = rect else {
+            return;
+        };
+
+        // Position context menu at mouse coordinates, and remember the board point
+        let x = ev.client_x() as f64 - rect.left();
+        let y = ev.client_y() as f64 - rect.top();
+        summon_at.set_value(viewport.get_untracked().to_world(x, y));
         set_context_menu_x.set(x);
         set_context_menu_y.set(y);
         set_show_context_menu.set(true);
     };
 
+    // Place a live chart at the right-clicked point, top-left corner first
     let summon_dragrok = move |_| {
         set_show_context_menu.set(false);
+
+        let at = summon_at.get_value();
+        let chart = ChartInvocation {
+            symbol: chart_symbol.get_untracked().trim().to_uppercase(),
+            timeframe: chart_timeframe.get_untracked(),
+            indicators: chart_indicators.get_untracked(),
+            position: ChartPosition {
+                x: (at.x / width as f64 * 100.0).clamp(0.0, 100.0 - CHART_WIDTH_PCT),
+                y: (at.y / height as f64 * 100.0).clamp(0.0, 100.0 - CHART_HEIGHT_PCT),
+                width: CHART_WIDTH_PCT,
+                height: CHART_HEIGHT_PCT,
+            },
+        };
+        if charts.with_untracked(|c| c.len()) >= MAX_BOARD_CHARTS {
+            set_save_status.set(format!("A board can hold at most {} charts", MAX_BOARD_CHARTS));
+            return;
+        }
+        if let Err(e) = validate_board_charts(std::slice::from_ref(&chart)) {
+            set_save_status.set(e);
+            return;
+        }
+        set_charts.update(|c| c.push(chart));
+
         set_dragrok_summoned.set(true);
+        // The overlay is a short flourish; it must not keep covering the board
+        set_timeout(
+            move || {
+                set_dragrok_summoned.set(false);
+                set_particles.set(Vec::new());
+            },
+            std::time::Duration::from_millis(1200),
+        );
 
         // Spawn initial burst of particles
+        let (burst_x, burst_y) = (context_menu_x.get_untracked(), context_menu_y.get_untracked());
         let mut initial_particles = particles.get();
         for _ in 0..50 {
-            initial_particles.push(create_fire_particle(width as f64 / 2.0, height as f64 / 2.0));
+            initial_particles.push(create_fire_particle(burst_x, burst_y));
         }
         set_particles.set(initial_particles);
 
         leptos::logging::log!("🐉 DRAGROK SUMMONED WITH FIRE BREATH!");
 
         // Start animation loop using gloo-timers
-        let set_particles_anim = set_particles.clone();
-        let particles_anim = particles.clone();
+        let set_particles_anim = set_particles;
+        let particles_anim = particles;
 
         let _interval = gloo_timers::callback::Interval::new(16, move || {
             let mut current_particles = particles_anim.get();
-            let center_x = width as f64 / 2.0;
-            let center_y = height as f64 / 2.0;
 
             // Add new particles (emitter)
             for _ in 0..5 {
-                current_particles.push(create_fire_particle(center_x, center_y));
+                current_particles.push(create_fire_particle(burst_x, burst_y));
             }
 
             // Update existing particles
@@ -334,49 +1020,50 @@ pub fn Whiteboard(
         set_show_context_menu.set(false);
     };
 
+    let toggle_chart_indicator = move |name: &'static str| {
+        set_chart_indicators.update(|list| {
+            if let Some(i) = list.iter().position(|n| n == name) {
+                list.remove(i);
+            } else {
+                list.push(name.to_string());
+            }
+        });
+    };
+
+    let remove_chart = move |index: usize| {
+        set_charts.update(|list| {
+            if index < list.len() {
+                list.remove(index);
+            }
+        });
+    };
+
     // =====================
     // KEYBOARD SHORTCUTS
     // =====================
 
     let on_key_down = move |ev: KeyboardEvent| {
         match ev.key().as_str() {
-            "z" if ev.ctrl_key() || ev.meta_key() => {
-                // Undo
-                let mut all_strokes = strokes.get();
-                if let Some(last) = all_strokes.pop() {
-                    let mut redo = undo_stack.get();
-                    redo.push(last);
-                    set_undo_stack.set(redo);
-                    set_strokes.set(all_strokes);
-                    redraw_all();
-                }
-            }
-            "y" if ev.ctrl_key() || ev.meta_key() => {
-                // Redo
-                let mut redo = undo_stack.get();
-                if let Some(stroke) = redo.pop() {
-                    let mut all_strokes = strokes.get();
-                    all_strokes.push(stroke);
-                    set_strokes.set(all_strokes);
-                    set_undo_stack.set(redo);
-                    redraw_all();
-                }
+            "z" if ev.ctrl_key() || ev.meta_key() => undo(),
+            "y" | "Z" if ev.ctrl_key() || ev.meta_key() => redo(),
+            "Delete" | "Backspace" if !selection.get_untracked().is_empty() => {
+                ev.prevent_default();
+                delete_selection();
             }
+            "Escape" => set_selection.set(Vec::new()),
             "+" | "=" => {
-                // Zoom in
-                set_zoom.update(|z| *z = (*z * 1.1).min(5.0));
+                // Zoom in around the middle of the board
+                set_viewport.update(|v| v.zoom_at(KEY_ZOOM_STEP, (width as f64 / 2.0, height as f64 / 2.0)));
                 redraw_all();
             }
             "-" | "_" => {
-                // Zoom out
-                set_zoom.update(|z| *z = (*z / 1.1).max(0.2));
+                // Zoom out around the middle of the board
+                set_viewport.update(|v| v.zoom_at(1.0 / KEY_ZOOM_STEP, (width as f64 / 2.0, height as f64 / 2.0)));
                 redraw_all();
             }
             "0" => {
                 // Reset zoom
-                set_zoom.set(1.0);
-                set_offset_x.set(0.0);
-                set_offset_y.set(0.0);
+                set_viewport.set(Viewport::default());
                 redraw_all();
             }
             _ => {}
@@ -384,23 +1071,316 @@ pub fn Whiteboard(
     };
 
     // =====================
-    // WHEEL HANDLER (PAN)
+    // WHEEL HANDLER (PAN / ZOOM)
     // =====================
 
     let on_wheel = move |ev: WheelEvent| {
-        if ev.shift_key() {
-            // Horizontal pan
-            let delta = ev.delta_x() as f64;
-            set_offset_x.update(|x| *x -= delta);
+        ev.prevent_default();
+
+        // Deltas may come in lines or pages rather than pixels
+        let unit = match ev.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => 16.0,
+            WheelEvent::DOM_DELTA_PAGE => height as f64,
+            _ => 1.0,
+        };
+        let (dx, dy) = (ev.delta_x() * unit, ev.delta_y() * unit);
+
+        if ev.ctrl_key() || ev.meta_key() {
+            // Zoom around the cursor; trackpad pinches arrive as ctrl-wheel too
+            let rect = canvas_ref
+                .get()
+                .and_then(|canvas| canvas.into_any().dyn_ref::<Element>().map(|e| e.get_bounding_client_rect()));
+            let Some(rect) = rect else {
+                return;
+            };
+            let cursor = (ev.client_x() as f64 - rect.left(), ev.client_y() as f64 - rect.top());
+            set_viewport.update(|v| v.zoom_at((-dy * WHEEL_ZOOM_RATE).exp(), cursor));
+        } else if ev.shift_key() {
+            // Horizontal pan; some browsers already move the delta to x
+            let delta = if dx != 0.0 { dx } else { dy };
+            set_viewport.update(|v| v.pan(-delta, 0.0));
         } else {
-            // Vertical pan
-            let delta = ev.delta_y() as f64;
-            set_offset_y.update(|y| *y -= delta);
+            set_viewport.update(|v| v.pan(-dx, -dy));
         }
-        ev.prevent_default();
         redraw_all();
     };
 
+    // =====================
+    // PERSISTENCE
+    // =====================
+
+    let refresh_revisions = move |id: Uuid| {
+        spawn_local(async move {
+            match list_board_revisions(id).await {
+                Ok(list) => set_revisions.set(list),
+                Err(e) => leptos::logging::log!("Failed to load revisions: {:?}", e),
+            }
+        });
+    };
+
+    let show_board = move |id: Uuid,
+                           name: String,
+                           loaded: Vec<Stroke>,
+                           loaded_charts: Vec<ChartInvocation>,
+                           revision: i32| {
+        // A room's strokes belong to the board it was joined from
+        if room.room.get_untracked().is_some() {
+            room.leave();
+        }
+        saved_json.set_value(serde_json::to_string(&(&loaded, &loaded_charts)).unwrap_or_default());
+        set_preview.set(None);
+        set_board_id.set(Some(id));
+        set_board_name.set(name);
+        forget_history();
+        set_strokes.set(loaded);
+        set_charts.set(loaded_charts);
+        set_save_status.set(format!("Saved · rev {}", revision));
+        if show_history.get_untracked() {
+            refresh_revisions(id);
+        }
+        redraw_all();
+    };
+
+    let open_board = move |id: Uuid| {
+        set_save_status.set("Loading…".to_string());
+        spawn_local(async move {
+            match load_board(id).await {
+                Ok(board) => show_board(board.id, board.name, board.strokes, board.charts, board.revision),
+                Err(e) => set_save_status.set(format!("Load failed: {}", e)),
+            }
+        });
+    };
+
+    let new_board = move |_| {
+        spawn_local(async move {
+            let name = format!("Board {}", boards.get_untracked().len() + 1);
+            match create_board(name).await {
+                Ok(summary) => {
+                    set_boards.update(|list| list.insert(0, summary.clone()));
+                    show_board(summary.id, summary.name, Vec::new(), Vec::new(), summary.revision);
+                }
+                Err(e) => set_save_status.set(format!("Create failed: {}", e)),
+            }
+        });
+    };
+
+    // =====================
+    // EXPORT / IMPORT
+    // =====================
+
+    let export_svg = move |_| {
+        let svg = strokes.with_untracked(|all| strokes_to_svg(all, width as f64, height as f64));
+        download(&text_data_url("image/svg+xml", &svg), &export_file_name(&board_name.get_untracked(), "svg"));
+    };
+
+    let export_png = move |_| {
+        let Some(canvas) = canvas_ref.get_untracked() else {
+            return;
+        };
+        let web_canvas = canvas.into_any();
+        let Some(source) = web_canvas.dyn_ref::<HtmlCanvasElement>() else {
+            return;
+        };
+        // The board background is CSS, so paint it under a copy of the canvas
+        let Some(copy) = document()
+            .create_element("canvas")
+            .ok()
+            .and_then(|e| e.dyn_into::<HtmlCanvasElement>().ok())
+        else {
+            return;
+        };
+        copy.set_width(source.width());
+        copy.set_height(source.height());
+        let Some(ctx) = copy
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|ctx| ctx.dyn_into::<CanvasRenderingContext2d>().ok())
+        else {
+            return;
+        };
+        ctx.set_fill_style_str("#0a0a0a");
+        ctx.fill_rect(0.0, 0.0, source.width() as f64, source.height() as f64);
+        let _ = ctx.draw_image_with_html_canvas_element(source, 0.0, 0.0);
+        if let Ok(url) = copy.to_data_url() {
+            download(&url, &export_file_name(&board_name.get_untracked(), "png"));
+        }
+    };
+
+    let export_json = move |_| {
+        let document =
+            BoardDocument::new(board_name.get_untracked(), strokes.get_untracked(), charts.get_untracked());
+        download(
+            &text_data_url("application/json", &document.to_json()),
+            &export_file_name(&board_name.get_untracked(), "json"),
+        );
+    };
+
+    // Imports become a new board, so nothing on the current one is lost
+    let on_import = move |ev: leptos::ev::Event| {
+        let Some(input) = ev.target().and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) else {
+            return;
+        };
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        // Allow importing the same file again
+        input.set_value("");
+
+        spawn_local(async move {
+            let text = match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                Ok(text) => text.as_string().unwrap_or_default(),
+                Err(e) => {
+                    set_save_status.set(format!("Import failed: {:?}", e));
+                    return;
+                }
+            };
+            let document = match BoardDocument::from_json(&text) {
+                Ok(document) => document,
+                Err(e) => {
+                    set_save_status.set(format!("Import failed: {}", e));
+                    return;
+                }
+            };
+            let name = if document.name.trim().is_empty() { file.name() } else { document.name.clone() };
+            match create_board(name).await {
+                Ok(summary) => {
+                    set_boards.update(|list| list.insert(0, summary.clone()));
+                    show_board(summary.id, summary.name, Vec::new(), Vec::new(), summary.revision);
+                    // Differs from the saved (empty) board, so autosave stores it
+                    set_strokes.set(document.strokes);
+                    set_charts.set(document.charts);
+                }
+                Err(e) => set_save_status.set(format!("Import failed: {}", e)),
+            }
+        });
+    };
+
+    let on_rename = move |ev: leptos::ev::Event| {
+        let Some(id) = board_id.get_untracked() else {
+            return;
+        };
+        let name = event_target_value(&ev);
+        spawn_local(async move {
+            match rename_board(id, name.clone()).await {
+                Ok(()) => set_boards.update(|list| {
+                    if let Some(entry) = list.iter_mut().find(|b| b.id == id) {
+                        entry.name = name.trim().to_string();
+                    }
+                }),
+                Err(e) => set_save_status.set(format!("Rename failed: {}", e)),
+            }
+        });
+    };
+
+    let preview_revision = move |revision: i32| {
+        let Some(id) = board_id.get_untracked() else {
+            return;
+        };
+        spawn_local(async move {
+            match load_board_revision(id, revision).await {
+                Ok(old) => {
+                    set_preview.set(Some(old));
+                    redraw_all();
+                }
+                Err(e) => set_save_status.set(format!("Load failed: {}", e)),
+            }
+        });
+    };
+
+    let restore_preview = move |_| {
+        let Some(id) = board_id.get_untracked() else {
+            return;
+        };
+        let Some(revision) = preview.with_untracked(|p| p.as_ref().map(|p| p.revision)) else {
+            return;
+        };
+        spawn_local(async move {
+            match restore_board_revision(id, revision).await {
+                Ok(board) => show_board(board.id, board.name, board.strokes, board.charts, board.revision),
+                Err(e) => set_save_status.set(format!("Restore failed: {}", e)),
+            }
+        });
+    };
+
+    // Load the board list once in the browser and open the most recent
+    // board, creating a first one for new users
+    create_effect(move |_| {
+        spawn_local(async move {
+            let list = match list_boards().await {
+                Ok(list) => list,
+                Err(e) => {
+                    set_save_status.set(format!("Boards unavailable: {}", e));
+                    return;
+                }
+            };
+
+            match list.first().cloned() {
+                Some(latest) => {
+                    set_boards.set(list);
+                    open_board(latest.id);
+                }
+                None => match create_board("Untitled board".to_string()).await {
+                    Ok(summary) => {
+                        set_boards.set(vec![summary.clone()]);
+                        show_board(summary.id, summary.name, Vec::new(), Vec::new(), summary.revision);
+                    }
+                    Err(e) => set_save_status.set(format!("Create failed: {}", e)),
+                },
+            }
+        });
+    });
+
+    // Debounced autosave: every change to the strokes or charts restarts the timer
+    create_effect(move |_| {
+        let json = strokes.with(|s| charts.with(|c| serde_json::to_string(&(s, c)).unwrap_or_default()));
+        let Some(id) = board_id.get_untracked() else {
+            return;
+        };
+        // Another board's room is not saved over the open board
+        if room.room.get_untracked().is_some_and(|joined| joined != id) {
+            return;
+        }
+        if json == saved_json.get_value() {
+            return;
+        }
+
+        save_generation.update_value(|g| *g += 1);
+        let generation = save_generation.get_value();
+        set_save_status.set("Unsaved changes".to_string());
+
+        set_timeout(
+            move || {
+                if save_generation.get_value() != generation || board_id.get_untracked() != Some(id) {
+                    return;
+                }
+                set_save_status.set("Saving…".to_string());
+                let to_save = strokes.get_untracked();
+                let charts_to_save = charts.get_untracked();
+                spawn_local(async move {
+                    match save_board(id, to_save, charts_to_save).await {
+                        Ok(revision) => {
+                            saved_json.set_value(json);
+                            if save_generation.get_value() == generation {
+                                set_save_status.set(format!("Saved · rev {}", revision));
+                            }
+                            set_boards.update(|list| {
+                                if let Some(entry) = list.iter_mut().find(|b| b.id == id) {
+                                    entry.revision = revision;
+                                }
+                            });
+                            if show_history.get_untracked() {
+                                refresh_revisions(id);
+                            }
+                        }
+                        Err(e) => set_save_status.set(format!("Save failed: {}", e)),
+                    }
+                });
+            },
+            std::time::Duration::from_millis(AUTOSAVE_DELAY_MS),
+        );
+    });
+
     // =====================
     // INITIALIZE CANVAS AFTER MOUNT
     // =====================
@@ -421,6 +1401,14 @@ pub fn Whiteboard(
         }
     };
 
+    // Size the canvas once it is mounted, then redraw whenever the board changes
+    create_effect(move |_| {
+        if canvas_ref.get().is_some() {
+            on_mount();
+        }
+    });
+    create_effect(move |_| redraw_all());
+
     view! {
         <Style>{r#"
             .whiteboard-container {
@@ -459,6 +1447,78 @@ pub fn Whiteboard(
                 background: rgba(255, 107, 53, 0.2);
             }
 
+            .chart-form {
+                display: flex;
+                flex-wrap: wrap;
+                gap: 6px;
+                padding: 4px 16px 8px;
+                max-width: 240px;
+            }
+
+            .chart-form input[type="text"],
+            .chart-form select {
+                background: #111;
+                color: #fff;
+                border: 1px solid #333;
+                border-radius: 4px;
+                padding: 4px 6px;
+                font-family: inherit;
+                font-size: 12px;
+            }
+
+            .chart-form-symbol {
+                width: 110px;
+            }
+
+            .chart-form-indicator {
+                display: flex;
+                align-items: center;
+                gap: 3px;
+                color: #ccc;
+                font-size: 11px;
+                cursor: pointer;
+            }
+
+            .board-chart {
+                position: absolute;
+                z-index: 0;
+                transform-origin: top left;
+                pointer-events: none;
+                opacity: 0.9;
+            }
+
+            .board-chart-bar {
+                position: absolute;
+                z-index: 2;
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                height: 22px;
+                padding: 0 8px;
+                box-sizing: border-box;
+                transform: translateY(-100%);
+                background: rgba(10, 10, 10, 0.9);
+                border: 1px solid #ff6b35;
+                border-radius: 6px 6px 0 0;
+                color: #888;
+                font-size: 11px;
+                letter-spacing: 0.05em;
+                overflow: hidden;
+                white-space: nowrap;
+            }
+
+            .board-chart-close {
+                background: transparent;
+                border: none;
+                color: #888;
+                cursor: pointer;
+                font-size: 12px;
+            }
+
+            .board-chart-close:hover {
+                color: #ff6b35;
+            }
+
             .dragrok-overlay {
                 position: absolute;
                 top: 0;
@@ -522,6 +1582,112 @@ pub fn Whiteboard(
                 z-index: 10001;
             }
 
+            .board-panel {
+                position: absolute;
+                top: 12px;
+                right: 12px;
+                width: 280px;
+                background: rgba(10, 10, 10, 0.92);
+                border: 1px solid #333;
+                border-radius: 8px;
+                padding: 10px;
+                display: flex;
+                flex-direction: column;
+                gap: 8px;
+                color: #ccc;
+                font-size: 12px;
+                z-index: 100;
+            }
+
+            .board-row {
+                display: flex;
+                gap: 6px;
+            }
+
+            .board-select, .board-name {
+                flex: 1;
+                min-width: 0;
+                background: #111;
+                color: #fff;
+                border: 1px solid #333;
+                border-radius: 6px;
+                padding: 4px 6px;
+                font-family: inherit;
+                font-size: 12px;
+            }
+
+            .board-status {
+                color: #888;
+                font-size: 11px;
+            }
+
+            .revision-list {
+                max-height: 240px;
+                overflow-y: auto;
+                border-top: 1px solid #222;
+            }
+
+            .revision-item {
+                padding: 6px 4px;
+                cursor: pointer;
+                border-bottom: 1px solid #1a1a1a;
+            }
+
+            .revision-item:hover, .revision-item.active {
+                background: rgba(255, 107, 53, 0.15);
+                color: #ff6b35;
+            }
+
+            .text-editor {
+                position: absolute;
+                z-index: 60;
+                min-width: 120px;
+                padding: 0;
+                margin: 0;
+                border: 1px dashed #ff6b35;
+                background: rgba(10, 10, 10, 0.6);
+                font-family: 'SF Mono', 'Fira Code', monospace;
+                line-height: 1.25;
+                resize: none;
+                overflow: hidden;
+                outline: none;
+                field-sizing: content;
+            }
+
+            .thickness-slider {
+                width: 90px;
+                accent-color: #ff6b35;
+            }
+
+            .room-peers {
+                color: #6bff9e;
+                font-size: 11px;
+            }
+
+            .remote-cursor {
+                position: absolute;
+                pointer-events: none;
+                z-index: 50;
+                transform: translate(-3px, -3px);
+            }
+
+            .remote-cursor-dot {
+                width: 6px;
+                height: 6px;
+                border-radius: 50%;
+                background: #6bff9e;
+            }
+
+            .remote-cursor-name {
+                margin: 2px 0 0 8px;
+                padding: 1px 5px;
+                border-radius: 4px;
+                background: rgba(107, 255, 158, 0.85);
+                color: #000;
+                font-size: 10px;
+                white-space: nowrap;
+            }
+
             @keyframes shake {
                 0%, 100% { transform: translateX(0); }
                 25% { transform: translateX(-10px) rotate(-5deg); }
@@ -537,14 +1703,64 @@ pub fn Whiteboard(
         <div class="whiteboard-container"
             style=format!("width: {}px; height: {}px; position: relative; overflow: hidden; background: #0a0a0a; border: 1px solid #333;", width, height)
         >
+            // Chart widgets sit under the canvas so strokes annotate them; they
+            // follow pan and zoom like the strokes. A previewed revision shows
+            // its own charts, read-only.
+            {move || {
+                let previewing = preview.with(|p| p.is_some());
+                let shown = preview.with(|p| p.as_ref().map(|p| p.charts.clone())).unwrap_or_else(|| charts.get());
+                shown.into_iter().enumerate().map(move |(index, chart)| {
+                    let (board_w, board_h) = (width as f64 / 100.0, height as f64 / 100.0);
+                    let (x, y) = (chart.position.x * board_w, chart.position.y * board_h);
+                    let (w, h) = (chart.position.width * board_w, chart.position.height * board_h);
+                    let screen = move || viewport.get().to_screen(x, y);
+                    // Leave room for the chart's own title row and padding
+                    let chart_height = (h - 60.0).max(120.0) as u32;
+                    view! {
+                        <div
+                            class="board-chart"
+                            style=move || {
+                                let (left, top) = screen();
+                                format!(
+                                    "left: {}px; top: {}px; width: {}px; height: {}px; transform: scale({});",
+                                    left, top, w, h, viewport.get().zoom
+                                )
+                            }
+                        >
+                            <LiveChart
+                                symbol=chart.symbol.clone()
+                                interval=chart.timeframe.clone()
+                                height=chart_height
+                                indicators=chart.indicators.clone()
+                            />
+                        </div>
+                        <div
+                            class="board-chart-bar"
+                            style=move || {
+                                let (left, top) = screen();
+                                format!("left: {}px; top: {}px; width: {}px;", left, top, w * viewport.get().zoom)
+                            }
+                        >
+                            <span>{chart.title()}</span>
+                            <Show when=move || !previewing>
+                                <button class="board-chart-close" title="Remove chart" on:click=move |_| remove_chart(index)>
+                                    "✕"
+                                </button>
+                            </Show>
+                        </div>
+                    }
+                }).collect_view()
+            }}
+
             <canvas
                 ref=canvas_ref
                 class="whiteboard-canvas"
-                style="cursor: crosshair; display: block;"
-                on:mousedown=on_mouse_down
-                on:mousemove=on_mouse_move
-                on:mouseup=on_mouse_up
-                on:mouseleave=on_mouse_up
+                style="cursor: crosshair; display: block; touch-action: none; position: relative; z-index: 1;"
+                on:pointerdown=on_pointer_down
+                on:pointermove=on_pointer_move
+                on:pointerup=on_pointer_up
+                on:pointercancel=on_pointer_cancel
+                on:pointerleave=on_pointer_up
                 on:wheel=on_wheel
                 on:contextmenu=on_context_menu
                 on:keydown=on_key_down
@@ -588,6 +1804,48 @@ pub fn Whiteboard(
                 >
                     "⭕ Circle"
                 </button>
+                <button
+                    class="tool-btn"
+                    class:active=move || current_tool.get() == Tool::Text
+                    on:click=move |_| set_current_tool.set(Tool::Text)
+                >
+                    "🔤 Text"
+                </button>
+                <button
+                    class="tool-btn"
+                    class:active=move || current_tool.get() == Tool::Select
+                    on:click=move |_| set_current_tool.set(Tool::Select)
+                >
+                    "⬚ Select"
+                </button>
+
+                <input
+                    class="thickness-slider"
+                    type="range"
+                    min="1"
+                    max="10"
+                    step="0.5"
+                    title="Line width / text size"
+                    prop:value=move || thickness.get().to_string()
+                    on:input=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse::<f64>() {
+                            set_thickness.set(value);
+                        }
+                    }
+                    // One restyle step per adjustment, not per input event
+                    on:change=move |_| {
+                        let width = thickness.get_untracked();
+                        restyle_selection(&|style| style.thickness = width);
+                    }
+                />
+                <button
+                    class="tool-btn"
+                    class:active=move || smoothing.get()
+                    title="Smooth freehand strokes when they are finished"
+                    on:click=move |_| set_smoothing.update(|on| *on = !*on)
+                >
+                    "〰️ Smooth"
+                </button>
 
                 <div class="toolbar-divider"></div>
 
@@ -606,6 +1864,8 @@ pub fn Whiteboard(
                             Color::White => set_current_color.set(Color::Black),
                             _ => {}
                         }
+                        let color = current_color.get_untracked();
+                        restyle_selection(&|style| style.color = color);
                     }
                 >
                     "🎨"
@@ -615,44 +1875,305 @@ pub fn Whiteboard(
 
                 <button
                     class="action-btn"
-                    on:click=move |_| {
-                        // Undo
-                        let mut all_strokes = strokes.get();
-                        if let Some(last) = all_strokes.pop() {
-                            let mut redo = undo_stack.get();
-                            redo.push(last);
-                            set_undo_stack.set(redo);
-                            set_strokes.set(all_strokes);
-                            redraw_all();
-                        }
-                    }
+                    title="Undo (Ctrl+Z)"
+                    prop:disabled=move || room.room.get().is_none() && !history.with(|h| h.can_undo())
+                    on:click=move |_| undo()
                 >
                     "↶️ Undo"
                 </button>
+                <button
+                    class="action-btn"
+                    title="Redo (Ctrl+Y)"
+                    prop:disabled=move || room.room.get().is_some() || !history.with(|h| h.can_redo())
+                    on:click=move |_| redo()
+                >
+                    "↷ Redo"
+                </button>
+
+                <Show when=move || !selection.get().is_empty()>
+                    <button class="action-btn" on:click=move |_| delete_selection()>
+                        "✂️ Delete"
+                    </button>
+                </Show>
 
                 <button
                     class="action-btn"
                     on:click=move |_| {
                         // Clear
-                        set_strokes.set(Vec::new());
-                        set_undo_stack.set(Vec::new());
-                        redraw_all();
+                        if room.is_connected() {
+                            room.clear();
+                            return;
+                        }
+                        if let Some(edit) = strokes.with_untracked(|all| BoardEdit::clear(all)) {
+                            commit_edit(edit);
+                        }
+                        set_selection.set(Vec::new());
                     }
                 >
                     "🗑️ Clear"
                 </button>
             </div>
 
-            // Context Menu (Right-click)
+            // Board picker, autosave status and revision history
+            <div class="board-panel">
+                <div class="board-row">
+                    <select
+                        class="board-select"
+                        on:change=move |ev| {
+                            if let Ok(id) = event_target_value(&ev).parse::<Uuid>() {
+                                open_board(id);
+                            }
+                        }
+                    >
+                        {move || boards.get().into_iter().map(|board| {
+                            let selected = board_id.get() == Some(board.id);
+                            view! {
+                                <option value=board.id.to_string() selected=selected>{board.name}</option>
+                            }
+                        }).collect_view()}
+                    </select>
+                    <button class="action-btn" on:click=new_board>"＋ New"</button>
+                </div>
+                <div class="board-row">
+                    <input
+                        class="board-name"
+                        type="text"
+                        prop:value=board_name
+                        on:change=on_rename
+                    />
+                    <button
+                        class="action-btn"
+                        class:active=show_history
+                        on:click=move |_| {
+                            let open = !show_history.get_untracked();
+                            set_show_history.set(open);
+                            if !open {
+                                set_preview.set(None);
+                                redraw_all();
+                            } else if let Some(id) = board_id.get_untracked() {
+                                refresh_revisions(id);
+                            }
+                        }
+                    >
+                        "🕘 History"
+                    </button>
+                </div>
+                <div class="board-status">{move || save_status.get()}</div>
+
+                <div class="board-row">
+                    <button class="action-btn" on:click=export_svg>"SVG"</button>
+                    <button class="action-btn" on:click=export_png>"PNG"</button>
+                    <button class="action-btn" on:click=export_json>"JSON"</button>
+                    <label class="action-btn">
+                        "⤒ Import"
+                        <input type="file" accept=".json,application/json" style="display: none;" on:change=on_import/>
+                    </label>
+                </div>
+
+                <div class="board-row">
+                    <input
+                        class="board-name"
+                        type="text"
+                        placeholder="Board ID (blank: this board)"
+                        prop:value=room_input
+                        prop:disabled=move || room.room.get().is_some()
+                        on:input=move |ev| set_room_input.set(event_target_value(&ev))
+                    />
+                    <Show
+                        when=move || room.room.get().is_some()
+                        fallback=move || view! {
+                            <button
+                                class="action-btn"
+                                on:click=move |_| {
+                                    let input = room_input.get_untracked();
+                                    let board = if input.trim().is_empty() {
+                                        board_id.get_untracked()
+                                    } else {
+                                        Uuid::parse_str(input.trim()).ok()
+                                    };
+                                    let Some(board) = board else {
+                                        set_save_status.set("Open a saved board or enter a board ID to go live".to_string());
+                                        return;
+                                    };
+                                    // Room edits replace the local history
+                                    forget_history();
+                                    room.join(board);
+                                }
+                            >
+                                "👥 Join"
+                            </button>
+                        }
+                    >
+                        <button class="action-btn" on:click=move |_| room.leave()>"Leave"</button>
+                    </Show>
+                </div>
+                <Show when=move || board_id.get().is_some()>
+                    <div class="board-row">
+                        <input
+                            class="board-name"
+                            type="text"
+                            placeholder="Share with username"
+                            prop:value=share_input
+                            on:input=move |ev| set_share_input.set(event_target_value(&ev))
+                        />
+                        <button
+                            class="action-btn"
+                            on:click=move |_| {
+                                let Some(id) = board_id.get_untracked() else {
+                                    return;
+                                };
+                                let username = share_input.get_untracked();
+                                if username.trim().is_empty() {
+                                    return;
+                                }
+                                spawn_local(async move {
+                                    match share_board(id, username.clone()).await {
+                                        Ok(()) => {
+                                            set_share_input.set(String::new());
+                                            set_save_status.set(format!("Shared with {} (board ID {})", username.trim(), id));
+                                        }
+                                        Err(e) => set_save_status.set(format!("Share failed: {}", e)),
+                                    }
+                                });
+                            }
+                        >
+                            "🔗 Share"
+                        </button>
+                    </div>
+                </Show>
+                <div class="board-status">{move || room.status.get()}</div>
+                <Show when=move || !room.participants.get().is_empty()>
+                    <div class="room-peers">
+                        {move || {
+                            let names: Vec<String> = room.participants.get().into_iter().map(|p| p.name).collect();
+                            format!("{} here: {}", names.len(), names.join(", "))
+                        }}
+                    </div>
+                </Show>
+
+                <Show when=move || show_history.get()>
+                    <div class="revision-list">
+                        {move || revisions.get().into_iter().map(|rev| {
+                            let revision = rev.revision;
+                            let is_previewed = move || preview.with(|p| p.as_ref().is_some_and(|p| p.revision == revision));
+                            view! {
+                                <div
+                                    class="revision-item"
+                                    class:active=is_previewed
+                                    on:click=move |_| preview_revision(revision)
+                                >
+                                    {format!(
+                                        "rev {} · {} strokes · {}",
+                                        rev.revision,
+                                        rev.stroke_count,
+                                        rev.created_at.format("%Y-%m-%d %H:%M")
+                                    )}
+                                </div>
+                            }
+                        }).collect_view()}
+                    </div>
+                    <Show when=move || preview.get().is_some()>
+                        <div class="board-row">
+                            <button class="action-btn" on:click=restore_preview>"⟲ Restore"</button>
+                            <button
+                                class="action-btn"
+                                on:click=move |_| {
+                                    set_preview.set(None);
+                                    redraw_all();
+                                }
+                            >
+                                "Back to current"
+                            </button>
+                        </div>
+                    </Show>
+                </Show>
+            </div>
+
+            // Inline text editor, placed over the text's board position
+            <Show when=move || text_editor.get().is_some()>
+                <textarea
+                    ref=text_input_ref
+                    class="text-editor"
+                    rows="1"
+                    style=move || text_editor.with(|editor| {
+                        let Some(editor) = editor else {
+                            return String::new();
+                        };
+                        let size = text_font_size(editor.thickness);
+                        let view = viewport.get();
+                        let (left, top) = view.to_screen(editor.at.x, editor.at.y);
+                        format!(
+                            "left: {}px; top: {}px; font-size: {}px; color: {};",
+                            left,
+                            top,
+                            size * view.zoom,
+                            editor.color,
+                        )
+                    })
+                    prop:value=move || text_editor.with(|e| e.as_ref().map(|e| e.value.clone()).unwrap_or_default())
+                    on:input=move |ev| {
+                        let value = event_target_value(&ev);
+                        set_text_editor.update(|editor| {
+                            if let Some(editor) = editor {
+                                editor.value = value;
+                            }
+                        });
+                    }
+                    on:keydown=on_text_key
+                    on:blur=move |_| commit_text()
+                ></textarea>
+            </Show>
+
+            // Other participants' pointers
+            {move || room.cursors.get().into_iter().map(|(_, (name, x, y))| {
+                let (left, top) = viewport.get().to_screen(x, y);
+                view! {
+                    <div class="remote-cursor" style=format!("left: {}px; top: {}px;", left, top)>
+                        <div class="remote-cursor-dot"></div>
+                        <div class="remote-cursor-name">{name}</div>
+                    </div>
+                }
+            }).collect_view()}
+
+            // Context Menu (Right-click): pick what the summoned chart shows
             <div
                 class="context-menu"
                 class:visible=move || show_context_menu.get()
-                style=format!("left: {}px; top: {}px;", context_menu_x.get(), context_menu_y.get())
-                on:click=close_context_menu
+                style=move || format!("left: {}px; top: {}px;", context_menu_x.get(), context_menu_y.get())
             >
+                <div class="chart-form">
+                    <input
+                        class="chart-form-symbol"
+                        type="text"
+                        maxlength="20"
+                        placeholder="BTCUSDT"
+                        prop:value=move || chart_symbol.get()
+                        on:input=move |ev| set_chart_symbol.set(event_target_value(&ev))
+                    />
+                    <select on:change=move |ev| set_chart_timeframe.set(event_target_value(&ev))>
+                        {CHART_TIMEFRAMES.iter().map(|tf| view! {
+                            <option value=*tf selected=move || chart_timeframe.get() == *tf>{*tf}</option>
+                        }).collect_view()}
+                    </select>
+                </div>
+                <div class="chart-form">
+                    {OVERLAY_INDICATORS.iter().map(|name| view! {
+                        <label class="chart-form-indicator">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || chart_indicators.with(|list| list.iter().any(|n| n == *name))
+                                on:change=move |_| toggle_chart_indicator(name)
+                            />
+                            {*name}
+                        </label>
+                    }).collect_view()}
+                </div>
                 <div class="context-menu-item" on:click=summon_dragrok>
                     "🐉 Summon Dragrok"
                 </div>
+                <div class="context-menu-item" on:click=close_context_menu>
+                    "Cancel"
+                </div>
             </div>
 
             // Dragrok Summoned Visual with Fire Particles
@@ -677,3 +2198,75 @@ pub fn Whiteboard(
         </div>
     }
 }
+
+// =====================
+// POINTER INPUT
+// =====================
+
+/// Board point for a pointer sample. Only pen strokes keep pressure; mice
+/// report none, and pens hovering or touching lightly can report 0.
+fn sample_point(ev: &PointerEvent, x: f64, y: f64, tool: Tool) -> StrokePoint {
+    let pressure = ev.pressure();
+    if tool != Tool::Pen || ev.pointer_type() == "mouse" || pressure <= 0.0 {
+        StrokePoint::new(x, y)
+    } else {
+        StrokePoint::with_pressure(x, y, pressure)
+    }
+}
+
+/// Points stored for a finished stroke. Freehand strokes are optionally
+/// smoothed, then decimated to a tolerance that looks the same at the
+/// current zoom; shapes only need their two defining points.
+fn finish_points(points: Vec<StrokePoint>, tool: Tool, smooth: bool, zoom: f64) -> Vec<StrokePoint> {
+    match tool {
+        Tool::Pen | Tool::Eraser => {
+            let points = if smooth { chaikin(&points, SMOOTHING_PASSES) } else { points };
+            decimate(&points, DECIMATE_TOLERANCE / zoom)
+        }
+        Tool::Line | Tool::Rectangle | Tool::Circle if points.len() > 2 => {
+            vec![points[0], points[points.len() - 1]]
+        }
+        _ => points,
+    }
+}
+
+// =====================
+// DOWNLOAD HELPERS
+// =====================
+
+/// `data:` URL for a text file, percent-encoding all but unreserved bytes.
+fn text_data_url(mime: &str, text: &str) -> String {
+    let mut url = format!("data:{};charset=utf-8,", mime);
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => url.push(byte as char),
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+/// Board name made safe for a file name, plus `extension`.
+fn export_file_name(board_name: &str, extension: &str) -> String {
+    let stem: String = board_name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let stem = if stem.is_empty() { "board".to_string() } else { stem };
+    format!("{}.{}", stem, extension)
+}
+
+/// Have the browser save `href` as `filename`.
+fn download(href: &str, filename: &str) {
+    let Some(anchor) = document()
+        .create_element("a")
+        .ok()
+        .and_then(|e| e.dyn_into::<HtmlAnchorElement>().ok())
+    else {
+        return;
+    };
+    anchor.set_href(href);
+    anchor.set_download(filename);
+    anchor.click();
+}
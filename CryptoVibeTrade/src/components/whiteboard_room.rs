@@ -0,0 +1,266 @@
+use leptos::*;
+use std::collections::HashMap;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{MessageEvent, WebSocket};
+use crate::collab::{ClientOp, Participant, RoomOp, RoomState, ServerMsg, StrokeId};
+use crate::types::Stroke;
+use uuid::Uuid;
+
+/// Minimum gap between cursor updates sent to the room
+const CURSOR_INTERVAL_MS: u64 = 50;
+
+/// Browser side of a `/ws/whiteboard` room. While connected, the board's
+/// strokes are the room's sequenced strokes followed by this client's own
+/// strokes that the server has not echoed yet, so every participant
+/// converges on the server's order.
+#[derive(Clone, Copy)]
+pub struct WhiteboardRoom {
+    /// Board whose room is joined, `None` when drawing alone
+    pub room: ReadSignal<Option<Uuid>>,
+    pub status: ReadSignal<String>,
+    pub participants: ReadSignal<Vec<Participant>>,
+    /// Other participants' pointers: id -> (name, x, y) in board coordinates
+    pub cursors: ReadSignal<HashMap<u64, (String, f64, f64)>>,
+    set_room: WriteSignal<Option<Uuid>>,
+    set_status: WriteSignal<String>,
+    set_participants: WriteSignal<Vec<Participant>>,
+    set_cursors: WriteSignal<HashMap<u64, (String, f64, f64)>>,
+    set_strokes: WriteSignal<Vec<Stroke>>,
+    socket: StoredValue<Option<WebSocket>>,
+    state: StoredValue<RoomState>,
+    pending: StoredValue<Vec<(StrokeId, Stroke)>>,
+    participant: StoredValue<Option<u64>>,
+    next_stroke: StoredValue<u64>,
+    cursor: StoredValue<Option<(f64, f64)>>,
+    cursor_scheduled: StoredValue<bool>,
+}
+
+impl WhiteboardRoom {
+    pub fn is_connected(&self) -> bool {
+        self.participant.get_value().is_some()
+    }
+
+    /// Connect to the room of `board`; the room's strokes replace the
+    /// board. A room opens with the board's saved strokes. Only the board's
+    /// owner and the users it is shared with are let in.
+    pub fn join(self, board: Uuid) {
+        self.leave();
+
+        let location = window().location();
+        let scheme = if location.protocol().unwrap_or_default() == "https:" { "wss" } else { "ws" };
+        let host = location.host().unwrap_or_default();
+        let ws = match WebSocket::new(&format!("{}://{}/ws/whiteboard?board={}", scheme, host, board)) {
+            Ok(ws) => ws,
+            Err(e) => {
+                self.set_status.set(format!("Could not connect: {:?}", e));
+                return;
+            }
+        };
+
+        let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<ServerMsg>(&text) {
+                Ok(message) => self.handle(message),
+                Err(e) => leptos::logging::log!("Bad whiteboard message: {:?}", e),
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        let ws_closed = ws.clone();
+        let onclose = Closure::wrap(Box::new(move |_: web_sys::CloseEvent| {
+            // Ignore sockets replaced by a later join
+            let current = self
+                .socket
+                .try_with_value(|s| s.as_ref().is_some_and(|s| *s == ws_closed))
+                .unwrap_or(false);
+            if current {
+                // The server refuses boards that are not shared with us
+                let status = if self.is_connected() {
+                    "Disconnected from room"
+                } else {
+                    "Could not join: the board is not shared with you"
+                };
+                self.disconnected(status.to_string());
+            }
+        }) as Box<dyn FnMut(_)>);
+
+        ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+        onmessage.forget();
+        onclose.forget();
+
+        self.socket.set_value(Some(ws));
+        self.set_room.set(Some(board));
+        self.set_status.set(format!("Joining {}…", board));
+    }
+
+    /// Disconnect; the board keeps the strokes it has.
+    pub fn leave(self) {
+        if let Some(ws) = self.socket.get_value() {
+            self.socket.set_value(None);
+            let _ = ws.close();
+        }
+        self.disconnected(String::new());
+    }
+
+    fn disconnected(self, status: String) {
+        self.socket.set_value(None);
+        self.participant.set_value(None);
+        self.pending.set_value(Vec::new());
+        self.state.set_value(RoomState::default());
+        self.set_room.set(None);
+        self.set_participants.set(Vec::new());
+        self.set_cursors.set(HashMap::new());
+        self.set_status.set(status);
+    }
+
+    fn send(&self, op: &ClientOp) {
+        let Some(ws) = self.socket.get_value() else {
+            return;
+        };
+        if ws.ready_state() != WebSocket::OPEN {
+            return;
+        }
+        if let Ok(text) = serde_json::to_string(op) {
+            let _ = ws.send_with_str(&text);
+        }
+    }
+
+    /// Room strokes in sequence order, then our unconfirmed ones.
+    fn publish_strokes(&self) {
+        let mut strokes: Vec<Stroke> = self.state.with_value(|state| {
+            state.strokes.iter().map(|s| s.stroke.clone()).collect()
+        });
+        self.pending.with_value(|pending| {
+            strokes.extend(pending.iter().map(|(_, stroke)| stroke.clone()));
+        });
+        self.set_strokes.set(strokes);
+    }
+
+    pub fn add_stroke(self, stroke: Stroke) {
+        let Some(participant) = self.participant.get_value() else {
+            return;
+        };
+        self.next_stroke.update_value(|n| *n += 1);
+        let id = StrokeId { participant, n: self.next_stroke.get_value() };
+        self.pending.update_value(|pending| pending.push((id, stroke.clone())));
+        self.send(&ClientOp::AddStroke { id, stroke });
+        self.publish_strokes();
+    }
+
+    pub fn undo(self) {
+        self.send(&ClientOp::Undo);
+    }
+
+    pub fn clear(self) {
+        self.send(&ClientOp::Clear);
+    }
+
+    /// Share the pointer position, at most every `CURSOR_INTERVAL_MS`.
+    pub fn move_cursor(self, x: f64, y: f64) {
+        if !self.is_connected() {
+            return;
+        }
+        self.cursor.set_value(Some((x, y)));
+        if self.cursor_scheduled.get_value() {
+            return;
+        }
+        self.cursor_scheduled.set_value(true);
+        set_timeout(
+            move || {
+                self.cursor_scheduled.set_value(false);
+                if let Some((x, y)) = self.cursor.get_value() {
+                    self.send(&ClientOp::Cursor { x, y });
+                }
+            },
+            Duration::from_millis(CURSOR_INTERVAL_MS),
+        );
+    }
+
+    fn handle(self, message: ServerMsg) {
+        match message {
+            ServerMsg::Welcome { participant, state, participants } => {
+                self.participant.set_value(Some(participant));
+                self.pending.update_value(|pending| pending.retain(|(id, _)| !state.contains(*id)));
+                self.state.set_value(state);
+                self.set_participants.set(participants);
+                let room = self.room.get_untracked().map(|id| id.to_string()).unwrap_or_default();
+                self.set_status.set(format!("Live in {}", room));
+                self.publish_strokes();
+            }
+            ServerMsg::Op { seq, op } => {
+                let applied = self.state.try_update_value(|state| state.apply(seq, &op)).unwrap_or(false);
+                if !applied {
+                    return;
+                }
+                if let RoomOp::Add { stroke } = &op {
+                    self.pending.update_value(|pending| pending.retain(|(id, _)| *id != stroke.id));
+                }
+                self.publish_strokes();
+            }
+            ServerMsg::Cursor { participant, name, x, y } => {
+                self.set_cursors.update(|cursors| {
+                    cursors.insert(participant, (name, x, y));
+                });
+            }
+            ServerMsg::Joined { participant } => {
+                if Some(participant.id) != self.participant.get_value() {
+                    self.set_participants.update(|list| list.push(participant));
+                }
+            }
+            ServerMsg::Left { participant } => {
+                self.set_participants.update(|list| list.retain(|p| p.id != participant));
+                self.set_cursors.update(|cursors| {
+                    cursors.remove(&participant);
+                });
+            }
+            ServerMsg::Rejected { id, reason } => {
+                if let Some(id) = id {
+                    self.pending.update_value(|pending| pending.retain(|(pending_id, _)| *pending_id != id));
+                    self.publish_strokes();
+                }
+                self.set_status.set(format!("Rejected: {}", reason));
+            }
+        }
+    }
+}
+
+/// Room connection for a whiteboard whose strokes live in `set_strokes`.
+/// The socket is closed when the calling component is cleaned up.
+pub fn use_whiteboard_room(set_strokes: WriteSignal<Vec<Stroke>>) -> WhiteboardRoom {
+    let (room, set_room) = create_signal(None::<Uuid>);
+    let (status, set_status) = create_signal(String::new());
+    let (participants, set_participants) = create_signal(Vec::<Participant>::new());
+    let (cursors, set_cursors) = create_signal(HashMap::new());
+
+    let handle = WhiteboardRoom {
+        room,
+        status,
+        participants,
+        cursors,
+        set_room,
+        set_status,
+        set_participants,
+        set_cursors,
+        set_strokes,
+        socket: store_value(None),
+        state: store_value(RoomState::default()),
+        pending: store_value(Vec::new()),
+        participant: store_value(None),
+        next_stroke: store_value(0),
+        cursor: store_value(None),
+        cursor_scheduled: store_value(false),
+    };
+
+    on_cleanup(move || {
+        if let Some(ws) = handle.socket.get_value() {
+            handle.socket.set_value(None);
+            let _ = ws.close();
+        }
+    });
+
+    handle
+}
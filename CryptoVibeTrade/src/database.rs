@@ -1,8 +1,17 @@
 use anyhow::Result;
+use crate::alerts::{AlertFired, AlertRule, NewAlertRule};
+use crate::boards::{Board, BoardRevision, BoardSummary, MAX_REVISIONS};
+use crate::types::{ChartInvocation, Stroke};
+use crate::wallet_auth::{
+    wallet_email, wallet_usernames, WalletChallenge, MAX_OPEN_CHALLENGES, MAX_OPEN_CHALLENGES_PER_WALLET,
+};
 use argon2::password_hash::{rand_core::OsRng, SaltString};
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use chrono::{DateTime, NaiveDate, Utc};
+use crate::migrations::Migrator;
+use crate::nft_gating::{GatedFeature, NewNftCollection, NftAccess, NftCollection, NftEntitlement};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
@@ -16,63 +25,21 @@ impl Database {
         Ok(Database { pool })
     }
 
+    /// Apply every pending migration from `migrations/`.
     pub async fn run_migrations(&self) -> Result<()> {
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS users (
-                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-                username VARCHAR(100) UNIQUE NOT NULL,
-                email VARCHAR(255) UNIQUE NOT NULL,
-                full_name VARCHAR(255),
-                birth_date DATE,
-                password_hash TEXT NOT NULL,
-                is_admin BOOLEAN DEFAULT FALSE NOT NULL,
-                is_active BOOLEAN DEFAULT TRUE NOT NULL,
-                created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
-                updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
-            );
-
-            ALTER TABLE users
-                ADD COLUMN IF NOT EXISTS full_name VARCHAR(255),
-                ADD COLUMN IF NOT EXISTS birth_date DATE;
-
-            CREATE INDEX IF NOT EXISTS idx_users_username ON users(username);
-            CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
-
-            CREATE TABLE IF NOT EXISTS login_sessions (
-                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-                token TEXT UNIQUE NOT NULL,
-                expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
-                created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
-                valid BOOLEAN DEFAULT TRUE NOT NULL
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_login_sessions_token ON login_sessions(token);
-            CREATE INDEX IF NOT EXISTS idx_login_sessions_user_id ON login_sessions(user_id);
-
-            CREATE OR REPLACE FUNCTION update_updated_at_column()
-            RETURNS TRIGGER AS $$
-            BEGIN
-                NEW.updated_at = NOW();
-                RETURN NEW;
-            END;
-            $$ language 'plpgsql';
-
-            DROP TRIGGER IF EXISTS update_users_updated_at ON users;
-            CREATE TRIGGER update_users_updated_at
-                BEFORE UPDATE ON users
-                FOR EACH ROW
-                EXECUTE FUNCTION update_updated_at_column();
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        let applied = self.migrator().up(None).await?;
 
-        tracing::info!("Database migrations completed successfully");
+        tracing::info!(
+            "Database migrations completed successfully ({} applied)",
+            applied.len()
+        );
         Ok(())
     }
 
+    pub fn migrator(&self) -> Migrator<'_> {
+        Migrator::new(&self.pool)
+    }
+
     pub async fn seed_admin_user(&self) -> Result<()> {
         // Check if admin user already exists
         if self.get_user_by_username("fenrir").await?.is_some() {
@@ -234,6 +201,622 @@ impl Database {
             .await?;
         Ok(())
     }
+
+    // =====================
+    // Wallets
+    // =====================
+
+    /// Store a sign-in challenge and drop challenges that can no longer be
+    /// redeemed. Returns false, storing nothing, when the wallet or the
+    /// whole table already has as many open challenges as allowed.
+    pub async fn create_wallet_challenge(&self, challenge: &WalletChallenge) -> Result<bool> {
+        sqlx::query("DELETE FROM wallet_nonces WHERE expires_at < NOW() OR used_at IS NOT NULL")
+            .execute(&self.pool)
+            .await?;
+        let row = sqlx::query(
+            r#"
+            INSERT INTO wallet_nonces (nonce, wallet, message, expires_at)
+            SELECT $1, $2, $3, $4
+            WHERE (SELECT COUNT(*) FROM wallet_nonces WHERE wallet = $2) < $5
+              AND (SELECT COUNT(*) FROM wallet_nonces) < $6
+            RETURNING nonce
+            "#,
+        )
+        .bind(&challenge.nonce)
+        .bind(&challenge.wallet)
+        .bind(&challenge.message)
+        .bind(challenge.expires_at)
+        .bind(MAX_OPEN_CHALLENGES_PER_WALLET)
+        .bind(MAX_OPEN_CHALLENGES)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    /// Mark a challenge used and return the message that was issued, or
+    /// `None` when it is unknown, expired, already used or for another wallet.
+    pub async fn redeem_wallet_challenge(&self, wallet: &str, nonce: &str) -> Result<Option<String>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE wallet_nonces SET used_at = NOW()
+            WHERE nonce = $1 AND wallet = $2 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING message
+            "#,
+        )
+        .bind(nonce)
+        .bind(wallet)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| row.try_get("message")).transpose().map_err(Into::into)
+    }
+
+    pub async fn get_user_by_wallet(&self, address: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT u.*
+            FROM users u
+            INNER JOIN user_wallets w ON u.id = w.user_id
+            WHERE w.address = $1 AND u.is_active = TRUE
+            "#,
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    /// Link `address` to the user, or refresh its sign-in time. Returns
+    /// false when the wallet already belongs to a different user.
+    pub async fn link_wallet(&self, user_id: &Uuid, address: &str) -> Result<bool> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO user_wallets (address, user_id) VALUES ($1, $2)
+            ON CONFLICT (address) DO UPDATE SET last_signed_in_at = NOW()
+            WHERE user_wallets.user_id = EXCLUDED.user_id
+            RETURNING address
+            "#,
+        )
+        .bind(address)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    /// Create an account for a wallet signing in for the first time. It has
+    /// no usable password; the wallet signature is its only credential.
+    pub async fn create_wallet_user(&self, address: &str) -> Result<User> {
+        let password_hash = Self::hash_password(&Uuid::new_v4().to_string())?;
+        let mut tx = self.pool.begin().await?;
+
+        let mut created = None;
+        for username in wallet_usernames(address) {
+            created = sqlx::query_as::<_, User>(
+                r#"
+                INSERT INTO users (username, email, password_hash)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING
+                RETURNING *
+                "#,
+            )
+            .bind(&username)
+            .bind(wallet_email(address))
+            .bind(&password_hash)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if created.is_some() {
+                break;
+            }
+        }
+        let user = created.ok_or_else(|| anyhow::anyhow!("No free username for wallet {}", address))?;
+
+        sqlx::query("INSERT INTO user_wallets (address, user_id) VALUES ($1, $2)")
+            .bind(address)
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    /// Wallets the user has proven they own, most recently used first.
+    pub async fn wallets_for_user(&self, user_id: &Uuid) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            "SELECT address FROM user_wallets WHERE user_id = $1 ORDER BY last_signed_in_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| row.try_get("address").map_err(Into::into))
+            .collect()
+    }
+
+    pub async fn user_owns_wallet(&self, user_id: &Uuid, address: &str) -> Result<bool> {
+        let row = sqlx::query("SELECT 1 FROM user_wallets WHERE user_id = $1 AND address = $2")
+            .bind(user_id)
+            .bind(address)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    // =====================
+    // NFT collections
+    // =====================
+
+    pub async fn list_nft_collections(&self) -> Result<Vec<NftCollection>> {
+        let collections = sqlx::query_as::<_, NftCollection>(
+            "SELECT * FROM nft_collections ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(collections)
+    }
+
+    /// Add a collection to the allow-list, or replace its label and features.
+    pub async fn save_nft_collection(
+        &self,
+        created_by: &Uuid,
+        collection: &NewNftCollection,
+    ) -> Result<NftCollection> {
+        let row = sqlx::query_as::<_, NftCollection>(
+            r#"
+            INSERT INTO nft_collections (address, label, features, created_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (address) DO UPDATE SET label = EXCLUDED.label, features = EXCLUDED.features
+            RETURNING *
+            "#,
+        )
+        .bind(&collection.address)
+        .bind(&collection.label)
+        .bind(Json(&collection.features))
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Returns false when the collection was not on the allow-list.
+    pub async fn delete_nft_collection(&self, address: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM nft_collections WHERE address = $1")
+            .bind(address)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // =====================
+    // NFT entitlements
+    // =====================
+
+    pub async fn save_nft_entitlement(
+        &self,
+        user_id: &Uuid,
+        wallet: &str,
+        access: &NftAccess,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO nft_entitlements (user_id, wallet, collections, features, verified_at, expires_at)
+            VALUES ($1, $2, $3, $4, NOW(), $5)
+            ON CONFLICT (user_id, wallet) DO UPDATE SET
+                collections = EXCLUDED.collections,
+                features = EXCLUDED.features,
+                verified_at = EXCLUDED.verified_at,
+                expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(wallet)
+        .bind(Json(&access.collections))
+        .bind(Json(&access.features))
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_nft_entitlement(&self, user_id: &Uuid, wallet: &str) -> Result<()> {
+        sqlx::query("DELETE FROM nft_entitlements WHERE user_id = $1 AND wallet = $2")
+            .bind(user_id)
+            .bind(wallet)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Unexpired entitlements of one user.
+    pub async fn nft_entitlements_for_user(&self, user_id: &Uuid) -> Result<Vec<NftEntitlement>> {
+        let entitlements = sqlx::query_as::<_, NftEntitlement>(
+            "SELECT * FROM nft_entitlements WHERE user_id = $1 AND expires_at > NOW() ORDER BY verified_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entitlements)
+    }
+
+    /// Unexpired entitlements last verified before `verified_before`.
+    pub async fn stale_nft_entitlements(&self, verified_before: DateTime<Utc>) -> Result<Vec<NftEntitlement>> {
+        let entitlements = sqlx::query_as::<_, NftEntitlement>(
+            "SELECT * FROM nft_entitlements WHERE verified_at < $1 AND expires_at > NOW() ORDER BY verified_at",
+        )
+        .bind(verified_before)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entitlements)
+    }
+
+    /// Whether any of the user's unexpired entitlements grants `feature`.
+    pub async fn user_has_feature(&self, user_id: &Uuid, feature: GatedFeature) -> Result<bool> {
+        let row = sqlx::query(
+            r#"
+            SELECT 1 FROM nft_entitlements
+            WHERE user_id = $1 AND expires_at > NOW() AND features @> $2
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(Json([feature]))
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    // =====================
+    // Alert rules
+    // =====================
+
+    pub async fn enabled_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        let rules = sqlx::query_as::<_, AlertRule>("SELECT * FROM alert_rules WHERE enabled = TRUE")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rules)
+    }
+
+    pub async fn alert_rules_for_user(&self, user_id: &Uuid) -> Result<Vec<AlertRule>> {
+        let rules = sqlx::query_as::<_, AlertRule>(
+            "SELECT * FROM alert_rules WHERE user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rules)
+    }
+
+    pub async fn create_alert_rule(&self, user_id: &Uuid, rule: &NewAlertRule) -> Result<AlertRule> {
+        let row = sqlx::query_as::<_, AlertRule>(
+            r#"
+            INSERT INTO alert_rules (user_id, symbol, interval, condition, webhook_url, cooldown_secs)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(rule.symbol.to_uppercase())
+        .bind(&rule.interval)
+        .bind(Json(&rule.condition))
+        .bind(&rule.webhook_url)
+        .bind(rule.cooldown_secs)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Returns false when the rule does not exist or belongs to someone else.
+    pub async fn set_alert_rule_enabled(&self, user_id: &Uuid, rule_id: &Uuid, enabled: bool) -> Result<bool> {
+        let result = sqlx::query("UPDATE alert_rules SET enabled = $3 WHERE id = $1 AND user_id = $2")
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(enabled)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns false when the rule does not exist or belongs to someone else.
+    pub async fn delete_alert_rule(&self, user_id: &Uuid, rule_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM alert_rules WHERE id = $1 AND user_id = $2")
+            .bind(rule_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Log a fired alert and persist the rule's cooldown start.
+    pub async fn record_alert_event(&self, alert: &AlertFired, webhook_status: Option<i32>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO alert_events (rule_id, user_id, message, intensity, observed, webhook_status, triggered_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(alert.rule_id)
+        .bind(alert.user_id)
+        .bind(&alert.event.message)
+        .bind(alert.event.intensity as i16)
+        .bind(alert.observed)
+        .bind(webhook_status)
+        .bind(alert.triggered_at)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE alert_rules SET last_triggered_at = $2 WHERE id = $1")
+            .bind(alert.rule_id)
+            .bind(alert.triggered_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // =====================
+    // Whiteboards
+    // =====================
+
+    pub async fn create_board(&self, user_id: &Uuid, name: &str) -> Result<BoardSummary> {
+        let row = sqlx::query(
+            "INSERT INTO boards (user_id, name) VALUES ($1, $2) RETURNING id, name, revision, updated_at",
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await?;
+        board_summary(&row)
+    }
+
+    pub async fn list_boards(&self, user_id: &Uuid) -> Result<Vec<BoardSummary>> {
+        let rows = sqlx::query(
+            "SELECT id, name, revision, updated_at FROM boards WHERE user_id = $1 ORDER BY updated_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(board_summary).collect()
+    }
+
+    /// Returns false when the board does not exist or belongs to someone else.
+    pub async fn rename_board(&self, user_id: &Uuid, board_id: &Uuid, name: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE boards SET name = $3 WHERE id = $1 AND user_id = $2")
+            .bind(board_id)
+            .bind(user_id)
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn load_board(&self, user_id: &Uuid, board_id: &Uuid) -> Result<Option<Board>> {
+        let row = sqlx::query(
+            "SELECT id, name, strokes, charts, revision, updated_at FROM boards WHERE id = $1 AND user_id = $2",
+        )
+        .bind(board_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let Json(strokes) = row.try_get("strokes")?;
+                let Json(charts) = row.try_get("charts")?;
+                Ok(Some(Board {
+                    id: row.try_get("id")?,
+                    name: row.try_get("name")?,
+                    strokes,
+                    charts,
+                    revision: row.try_get("revision")?,
+                    updated_at: row.try_get("updated_at")?,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Saved strokes of `board_id` when `user_id` owns it or was invited
+    /// to it; `None` otherwise.
+    pub async fn shared_board_strokes(&self, user_id: &Uuid, board_id: &Uuid) -> Result<Option<Vec<Stroke>>> {
+        let row = sqlx::query(
+            r#"
+            SELECT b.strokes FROM boards b
+            WHERE b.id = $1 AND (
+                b.user_id = $2
+                OR EXISTS (SELECT 1 FROM board_shares s WHERE s.board_id = b.id AND s.user_id = $2)
+            )
+            "#,
+        )
+        .bind(board_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let Json(strokes) = row.try_get("strokes")?;
+                Ok(Some(strokes))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Invite `username` to a board owned by `owner_id`. Returns false when
+    /// the board is not the owner's or the user does not exist.
+    pub async fn share_board(&self, owner_id: &Uuid, board_id: &Uuid, username: &str) -> Result<bool> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO board_shares (board_id, user_id)
+            SELECT b.id, u.id FROM boards b, users u
+            WHERE b.id = $1 AND b.user_id = $2 AND u.username = $3 AND u.id <> b.user_id
+            ON CONFLICT (board_id, user_id) DO UPDATE SET created_at = board_shares.created_at
+            RETURNING board_id
+            "#,
+        )
+        .bind(board_id)
+        .bind(owner_id)
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    pub async fn unshare_board(&self, owner_id: &Uuid, board_id: &Uuid, username: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM board_shares s
+            USING boards b, users u
+            WHERE s.board_id = b.id AND s.user_id = u.id
+              AND b.id = $1 AND b.user_id = $2 AND u.username = $3
+            "#,
+        )
+        .bind(board_id)
+        .bind(owner_id)
+        .bind(username)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Usernames invited to a board owned by `owner_id`.
+    pub async fn board_shares(&self, owner_id: &Uuid, board_id: &Uuid) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar(
+            r#"
+            SELECT u.username FROM board_shares s
+            JOIN boards b ON b.id = s.board_id
+            JOIN users u ON u.id = s.user_id
+            WHERE b.id = $1 AND b.user_id = $2
+            ORDER BY u.username
+            "#,
+        )
+        .bind(board_id)
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(names)
+    }
+
+    /// Store `strokes` and `charts` as the board's next revision and prune
+    /// history beyond `MAX_REVISIONS`. Returns the new revision, or `None`
+    /// when the board does not exist or belongs to someone else.
+    pub async fn save_board(
+        &self,
+        user_id: &Uuid,
+        board_id: &Uuid,
+        strokes: &[Stroke],
+        charts: &[ChartInvocation],
+    ) -> Result<Option<i32>> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE boards SET strokes = $3, charts = $4, revision = revision + 1
+            WHERE id = $1 AND user_id = $2
+            RETURNING revision
+            "#,
+        )
+        .bind(board_id)
+        .bind(user_id)
+        .bind(Json(strokes))
+        .bind(Json(charts))
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let revision: i32 = row.try_get("revision")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO board_revisions (board_id, revision, strokes, stroke_count, charts)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(board_id)
+        .bind(revision)
+        .bind(Json(strokes))
+        .bind(strokes.len() as i32)
+        .bind(Json(charts))
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM board_revisions WHERE board_id = $1 AND revision <= $2")
+            .bind(board_id)
+            .bind(revision - MAX_REVISIONS)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(revision))
+    }
+
+    /// Newest first. Empty when the board is not the user's.
+    pub async fn list_board_revisions(&self, user_id: &Uuid, board_id: &Uuid) -> Result<Vec<BoardRevision>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT r.revision, r.stroke_count, r.created_at
+            FROM board_revisions r
+            JOIN boards b ON b.id = r.board_id
+            WHERE r.board_id = $1 AND b.user_id = $2
+            ORDER BY r.revision DESC
+            "#,
+        )
+        .bind(board_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(BoardRevision {
+                    revision: row.try_get("revision")?,
+                    stroke_count: row.try_get("stroke_count")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Strokes and charts of one revision.
+    pub async fn load_board_revision(
+        &self,
+        user_id: &Uuid,
+        board_id: &Uuid,
+        revision: i32,
+    ) -> Result<Option<(Vec<Stroke>, Vec<ChartInvocation>)>> {
+        let row = sqlx::query(
+            r#"
+            SELECT r.strokes, r.charts
+            FROM board_revisions r
+            JOIN boards b ON b.id = r.board_id
+            WHERE r.board_id = $1 AND b.user_id = $2 AND r.revision = $3
+            "#,
+        )
+        .bind(board_id)
+        .bind(user_id)
+        .bind(revision)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let Json(strokes) = row.try_get("strokes")?;
+                let Json(charts) = row.try_get("charts")?;
+                Ok(Some((strokes, charts)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn board_summary(row: &sqlx::postgres::PgRow) -> Result<BoardSummary> {
+    Ok(BoardSummary {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        revision: row.try_get("revision")?,
+        updated_at: row.try_get("updated_at")?,
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -267,3 +850,276 @@ impl sqlx::FromRow<'_, sqlx::postgres::PgRow> for User {
         })
     }
 }
+
+impl sqlx::FromRow<'_, sqlx::postgres::PgRow> for AlertRule {
+    fn from_row(row: &sqlx::postgres::PgRow) -> sqlx::Result<Self> {
+        let Json(condition) = row.try_get("condition")?;
+        Ok(AlertRule {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            symbol: row.try_get("symbol")?,
+            interval: row.try_get("interval")?,
+            condition,
+            webhook_url: row.try_get("webhook_url")?,
+            cooldown_secs: row.try_get("cooldown_secs")?,
+            enabled: row.try_get("enabled")?,
+            last_triggered_at: row.try_get("last_triggered_at")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl sqlx::FromRow<'_, sqlx::postgres::PgRow> for NftCollection {
+    fn from_row(row: &sqlx::postgres::PgRow) -> sqlx::Result<Self> {
+        let Json(features) = row.try_get("features")?;
+        Ok(NftCollection {
+            address: row.try_get("address")?,
+            label: row.try_get("label")?,
+            features,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl sqlx::FromRow<'_, sqlx::postgres::PgRow> for NftEntitlement {
+    fn from_row(row: &sqlx::postgres::PgRow) -> sqlx::Result<Self> {
+        let Json(collections) = row.try_get("collections")?;
+        let Json(features) = row.try_get("features")?;
+        Ok(NftEntitlement {
+            user_id: row.try_get("user_id")?,
+            wallet: row.try_get("wallet")?,
+            collections,
+            features,
+            verified_at: row.try_get("verified_at")?,
+            expires_at: row.try_get("expires_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::tests::{drop_scratch_database, scratch_database};
+    use crate::types::{ChartPosition, Color, StrokePoint, Tool};
+    use crate::wallet_auth::{wallet_usernames, MAX_OPEN_CHALLENGES_PER_WALLET};
+
+    /// Migrated scratch database; `None` without `TEST_DATABASE_URL`.
+    async fn test_database() -> Option<(Database, impl std::future::Future<Output = ()>)> {
+        let (admin, pool, name) = scratch_database().await?;
+        let db = Database { pool: pool.clone() };
+        db.run_migrations().await.unwrap();
+        Some((db, drop_scratch_database(admin, pool, name)))
+    }
+
+    fn challenge(wallet: &str, nonce: &str) -> WalletChallenge {
+        WalletChallenge::new("cvt.test", wallet, nonce.to_string(), Utc::now())
+    }
+
+    #[tokio::test]
+    async fn wallet_challenges_are_single_use() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        let issued = challenge("WalletA", "nonce-1");
+        assert!(db.create_wallet_challenge(&issued).await.unwrap());
+
+        assert_eq!(db.redeem_wallet_challenge("WalletB", "nonce-1").await.unwrap(), None);
+        assert_eq!(
+            db.redeem_wallet_challenge("WalletA", "nonce-1").await.unwrap(),
+            Some(issued.message.clone())
+        );
+        assert_eq!(db.redeem_wallet_challenge("WalletA", "nonce-1").await.unwrap(), None);
+
+        let mut expired = challenge("WalletA", "nonce-2");
+        expired.expires_at = Utc::now() - chrono::Duration::seconds(1);
+        assert!(db.create_wallet_challenge(&expired).await.unwrap());
+        assert_eq!(db.redeem_wallet_challenge("WalletA", "nonce-2").await.unwrap(), None);
+
+        // Issuing another challenge purges used and expired ones
+        assert!(db.create_wallet_challenge(&challenge("WalletA", "nonce-3")).await.unwrap());
+        let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM wallet_nonces")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(left, 1);
+
+        cleanup.await;
+    }
+
+    #[tokio::test]
+    async fn open_wallet_challenges_are_capped() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        for n in 0..MAX_OPEN_CHALLENGES_PER_WALLET {
+            assert!(db.create_wallet_challenge(&challenge("WalletA", &format!("a{}", n))).await.unwrap());
+        }
+        assert!(!db.create_wallet_challenge(&challenge("WalletA", "one-too-many")).await.unwrap());
+        assert!(db.create_wallet_challenge(&challenge("WalletB", "b0")).await.unwrap());
+
+        // Redeeming frees a slot once the next challenge purges it
+        assert!(db.redeem_wallet_challenge("WalletA", "a0").await.unwrap().is_some());
+        assert!(db.create_wallet_challenge(&challenge("WalletA", "a-next")).await.unwrap());
+
+        cleanup.await;
+    }
+
+    #[tokio::test]
+    async fn wallet_user_falls_back_to_full_address_username() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        let address = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        let [short, full] = wallet_usernames(address);
+        // Taken before the reservation existed, or by a wallet sharing the prefix
+        db.create_user(&short, "legacy@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+
+        let user = db.create_wallet_user(address).await.unwrap();
+        assert_eq!(user.username, full);
+        assert_eq!(db.get_user_by_wallet(address).await.unwrap().map(|u| u.id), Some(user.id));
+
+        cleanup.await;
+    }
+
+    fn pen(points: &[(f64, f64)]) -> Stroke {
+        Stroke {
+            points: points.iter().map(|&(x, y)| StrokePoint::new(x, y)).collect(),
+            color: Color::White,
+            thickness: 2.0,
+            tool: Tool::Pen,
+            text: None,
+        }
+    }
+
+    fn chart(symbol: &str) -> ChartInvocation {
+        ChartInvocation {
+            symbol: symbol.to_string(),
+            timeframe: "1h".to_string(),
+            indicators: vec!["rsi".to_string()],
+            position: ChartPosition { x: 0.0, y: 0.0, width: 40.0, height: 30.0 },
+        }
+    }
+
+    #[tokio::test]
+    async fn board_saves_are_numbered_revisions() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        let owner = db
+            .create_user("owner", "owner@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+        let board = db.create_board(&owner.id, "Plan").await.unwrap();
+        assert_eq!(board.revision, 0);
+
+        let first = vec![pen(&[(0.0, 0.0), (10.0, 10.0)])];
+        let second = vec![first[0].clone(), pen(&[(5.0, 5.0), (20.0, 0.0)])];
+        let charts = vec![chart("SOLUSDT")];
+        assert_eq!(db.save_board(&owner.id, &board.id, &first, &[]).await.unwrap(), Some(1));
+        assert_eq!(db.save_board(&owner.id, &board.id, &second, &charts).await.unwrap(), Some(2));
+
+        let history = db.list_board_revisions(&owner.id, &board.id).await.unwrap();
+        assert_eq!(
+            history.iter().map(|r| (r.revision, r.stroke_count)).collect::<Vec<_>>(),
+            vec![(2, 2), (1, 1)]
+        );
+        assert_eq!(
+            db.load_board_revision(&owner.id, &board.id, 1).await.unwrap(),
+            Some((first.clone(), vec![]))
+        );
+        assert_eq!(
+            db.load_board_revision(&owner.id, &board.id, 2).await.unwrap(),
+            Some((second, charts))
+        );
+        assert_eq!(db.load_board_revision(&owner.id, &board.id, 3).await.unwrap(), None);
+
+        // Restoring saves the old content as a new revision
+        let (strokes, charts) = db.load_board_revision(&owner.id, &board.id, 1).await.unwrap().unwrap();
+        assert_eq!(db.save_board(&owner.id, &board.id, &strokes, &charts).await.unwrap(), Some(3));
+        let current = db.load_board(&owner.id, &board.id).await.unwrap().unwrap();
+        assert_eq!((current.revision, current.strokes, current.charts), (3, first, vec![]));
+        assert_eq!(db.list_board_revisions(&owner.id, &board.id).await.unwrap().len(), 3);
+
+        cleanup.await;
+    }
+
+    #[tokio::test]
+    async fn boards_are_saved_and_read_by_their_owner_only() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        let owner = db
+            .create_user("owner", "owner@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+        let other = db
+            .create_user("other", "other@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+        let board = db.create_board(&owner.id, "Plan").await.unwrap();
+        let strokes = vec![pen(&[(0.0, 0.0), (10.0, 10.0)])];
+        assert_eq!(db.save_board(&owner.id, &board.id, &strokes, &[]).await.unwrap(), Some(1));
+
+        assert_eq!(db.save_board(&other.id, &board.id, &[], &[]).await.unwrap(), None);
+        assert!(db.list_board_revisions(&other.id, &board.id).await.unwrap().is_empty());
+        assert_eq!(db.load_board_revision(&other.id, &board.id, 1).await.unwrap(), None);
+        assert!(db.load_board(&other.id, &board.id).await.unwrap().is_none());
+
+        // The rejected save left the owner's board untouched
+        let current = db.load_board(&owner.id, &board.id).await.unwrap().unwrap();
+        assert_eq!((current.revision, current.strokes), (1, strokes));
+
+        cleanup.await;
+    }
+
+    #[tokio::test]
+    async fn shared_users_read_the_owners_strokes() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        let owner = db
+            .create_user("owner", "owner@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+        let guest = db
+            .create_user("guest", "guest@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+        let board = db.create_board(&owner.id, "Plan").await.unwrap();
+        let strokes = vec![pen(&[(0.0, 0.0), (10.0, 10.0)])];
+        db.save_board(&owner.id, &board.id, &strokes, &[]).await.unwrap();
+
+        assert_eq!(db.shared_board_strokes(&owner.id, &board.id).await.unwrap(), Some(strokes.clone()));
+        assert_eq!(db.shared_board_strokes(&guest.id, &board.id).await.unwrap(), None);
+        assert!(db.share_board(&owner.id, &board.id, "guest").await.unwrap());
+        assert_eq!(db.shared_board_strokes(&guest.id, &board.id).await.unwrap(), Some(strokes));
+        assert_eq!(db.shared_board_strokes(&guest.id, &Uuid::new_v4()).await.unwrap(), None);
+
+        cleanup.await;
+    }
+
+    #[tokio::test]
+    async fn board_history_is_pruned_to_max_revisions() {
+        let Some((db, cleanup)) = test_database().await else {
+            return;
+        };
+        let owner = db
+            .create_user("owner", "owner@example.com", None, None, "password123", false)
+            .await
+            .unwrap();
+        let board = db.create_board(&owner.id, "Plan").await.unwrap();
+        for _ in 0..MAX_REVISIONS + 2 {
+            db.save_board(&owner.id, &board.id, &[], &[]).await.unwrap();
+        }
+
+        let history = db.list_board_revisions(&owner.id, &board.id).await.unwrap();
+        assert_eq!(history.len(), MAX_REVISIONS as usize);
+        assert_eq!(history.first().map(|r| r.revision), Some(MAX_REVISIONS + 2));
+        assert_eq!(history.last().map(|r| r.revision), Some(3));
+        assert_eq!(db.load_board_revision(&owner.id, &board.id, 2).await.unwrap(), None);
+
+        cleanup.await;
+    }
+}
@@ -0,0 +1,108 @@
+use crate::session::CurrentUser;
+use crate::types::DragrokEvent;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+// =====================
+// Event bus
+// =====================
+
+/// A `DragrokEvent` and who may see it.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    /// `None` = every connected client
+    pub recipient: Option<Uuid>,
+    pub event: DragrokEvent,
+}
+
+impl Envelope {
+    fn visible_to(&self, user_id: Option<Uuid>) -> bool {
+        match self.recipient {
+            None => true,
+            Some(recipient) => user_id == Some(recipient),
+        }
+    }
+}
+
+/// In-process fan-out of dragon events to every `/ws/events` client.
+pub struct EventBus {
+    tx: broadcast::Sender<Envelope>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        EventBus { tx }
+    }
+
+    /// Send to every connected client.
+    pub fn publish(&self, event: DragrokEvent) {
+        let _ = self.tx.send(Envelope {
+            recipient: None,
+            event,
+        });
+    }
+
+    /// Send only to the sessions of one user.
+    pub fn publish_to(&self, user_id: Uuid, event: DragrokEvent) {
+        let _ = self.tx.send(Envelope {
+            recipient: Some(user_id),
+            event,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Envelope> {
+        self.tx.subscribe()
+    }
+}
+
+static EVENTS: OnceLock<EventBus> = OnceLock::new();
+
+pub fn event_bus() -> &'static EventBus {
+    EVENTS.get_or_init(|| EventBus::new(256))
+}
+
+// =====================
+// Browser WebSocket (/ws/events)
+// =====================
+
+/// `GET /ws/events`: pushes each `DragrokEvent` as JSON. Anonymous
+/// clients only get broadcast events; logged-in users also get their own
+/// (e.g. fired price alerts).
+pub async fn events_ws_handler(
+    ws: axum::extract::ws::WebSocketUpgrade,
+    user: Option<CurrentUser>,
+) -> axum::response::Response {
+    let user_id = user.map(|CurrentUser(user)| user.id);
+    ws.on_upgrade(move |socket| events_socket(socket, user_id))
+}
+
+async fn events_socket(mut socket: axum::extract::ws::WebSocket, user_id: Option<Uuid>) {
+    use axum::extract::ws::Message as WsMessage;
+
+    let mut events = event_bus().subscribe();
+
+    loop {
+        tokio::select! {
+            envelope = events.recv() => match envelope {
+                Ok(envelope) if envelope.visible_to(user_id) => {
+                    let Ok(text) = serde_json::to_string(&envelope.event) else {
+                        continue;
+                    };
+                    if socket.send(WsMessage::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                // Dragon events are ephemeral; dropping a few is fine
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
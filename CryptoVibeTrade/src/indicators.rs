@@ -0,0 +1,771 @@
+use crate::types::Candle;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// =====================
+// Common trait
+// =====================
+
+/// An incrementally computed technical indicator. `update` consumes one
+/// closed candle and returns the value at that candle, or `None` while the
+/// indicator is still warming up.
+pub trait Indicator {
+    type Output: Clone;
+
+    fn name(&self) -> String;
+
+    fn update(&mut self, candle: &Candle) -> Option<Self::Output>;
+
+    fn reset(&mut self);
+}
+
+/// Run an indicator over a full candle history.
+pub fn compute<I: Indicator>(indicator: &mut I, candles: &[Candle]) -> Vec<Option<I::Output>> {
+    candles.iter().map(|c| indicator.update(c)).collect()
+}
+
+/// Wraps an indicator for a live feed where the last candle keeps changing
+/// until it closes. Updates with the same open time as the previous one
+/// are recomputed from the state saved before that candle, so a forming
+/// candle is never counted twice.
+#[derive(Debug, Clone)]
+pub struct StreamingIndicator<I: Indicator + Clone> {
+    /// State after the last *previous* candle
+    committed: I,
+    /// State including the current (possibly forming) candle
+    current: I,
+    last_time: Option<i64>,
+    values: Vec<Option<I::Output>>,
+    max_len: usize,
+}
+
+impl<I: Indicator + Clone> StreamingIndicator<I> {
+    pub fn new(indicator: I, max_len: usize) -> Self {
+        StreamingIndicator {
+            committed: indicator.clone(),
+            current: indicator,
+            last_time: None,
+            values: Vec::new(),
+            max_len,
+        }
+    }
+
+    pub fn push(&mut self, candle: &Candle) -> Option<I::Output> {
+        match self.last_time {
+            Some(time) if time == candle.time => {
+                self.current = self.committed.clone();
+                let value = self.current.update(candle);
+                if let Some(last) = self.values.last_mut() {
+                    *last = value.clone();
+                }
+                value
+            }
+            Some(time) if time > candle.time => {
+                // Stale update, keep the latest value
+                self.values.last().cloned().flatten()
+            }
+            _ => {
+                self.committed = self.current.clone();
+                let value = self.current.update(candle);
+                self.values.push(value.clone());
+                if self.values.len() > self.max_len {
+                    let excess = self.values.len() - self.max_len;
+                    self.values.drain(..excess);
+                }
+                self.last_time = Some(candle.time);
+                value
+            }
+        }
+    }
+
+    pub fn latest(&self) -> Option<&I::Output> {
+        self.values.last().and_then(|v| v.as_ref())
+    }
+
+    /// Values aligned with the last `values().len()` candles pushed.
+    pub fn values(&self) -> &[Option<I::Output>] {
+        &self.values
+    }
+
+    pub fn name(&self) -> String {
+        self.current.name()
+    }
+}
+
+// =====================
+// SMA / EMA
+// =====================
+
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Sma {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    pub fn next(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn name(&self) -> String {
+        format!("SMA({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.next(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Sma::new(self.period);
+    }
+}
+
+/// Exponential moving average seeded with the SMA of the first `period`
+/// values, smoothing factor `2 / (period + 1)`.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Ema {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    pub fn next(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(prev) => Some(prev + self.alpha * (value - prev)),
+            None => self.seed.next(value),
+        };
+        self.value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+impl Default for Ema {
+    fn default() -> Self {
+        Ema::new(20)
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn name(&self) -> String {
+        format!("EMA({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.next(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Ema::new(self.period);
+    }
+}
+
+// =====================
+// RSI
+// =====================
+
+/// Relative Strength Index with Wilder smoothing. The first value appears
+/// after `period` price changes (`period + 1` candles).
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    prev_close: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Rsi {
+            period: period.max(1),
+            prev_close: None,
+            changes: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+
+    pub fn next(&mut self, close: f64) -> Option<f64> {
+        let prev = self.prev_close.replace(close)?;
+        let change = close - prev;
+        let gain = change.max(0.0);
+        let loss = (-change).max(0.0);
+        let n = self.period as f64;
+
+        self.changes += 1;
+        if self.changes <= self.period {
+            // Simple average over the first `period` changes
+            self.avg_gain += gain / n;
+            self.avg_loss += loss / n;
+            if self.changes < self.period {
+                return None;
+            }
+        } else {
+            self.avg_gain = (self.avg_gain * (n - 1.0) + gain) / n;
+            self.avg_loss = (self.avg_loss * (n - 1.0) + loss) / n;
+        }
+
+        if self.avg_loss == 0.0 {
+            return Some(if self.avg_gain == 0.0 { 50.0 } else { 100.0 });
+        }
+        let rs = self.avg_gain / self.avg_loss;
+        Some(100.0 - 100.0 / (1.0 + rs))
+    }
+}
+
+impl Default for Rsi {
+    fn default() -> Self {
+        Rsi::new(14)
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn name(&self) -> String {
+        format!("RSI({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.next(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Rsi::new(self.period);
+    }
+}
+
+// =====================
+// MACD
+// =====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// MACD line (fast EMA - slow EMA), its signal EMA and the histogram.
+/// Emits once the signal line is seeded.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast_period: usize,
+    slow_period: usize,
+    signal_period: usize,
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Macd {
+            fast_period: fast,
+            slow_period: slow,
+            signal_period: signal,
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+
+    pub fn next(&mut self, close: f64) -> Option<MacdValue> {
+        let fast = self.fast.next(close);
+        let slow = self.slow.next(close);
+        let macd = fast? - slow?;
+        let signal = self.signal.next(macd)?;
+        Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
+
+impl Default for Macd {
+    fn default() -> Self {
+        Macd::new(12, 26, 9)
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn name(&self) -> String {
+        format!(
+            "MACD({},{},{})",
+            self.fast_period, self.slow_period, self.signal_period
+        )
+    }
+
+    fn update(&mut self, candle: &Candle) -> Option<MacdValue> {
+        self.next(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Macd::new(self.fast_period, self.slow_period, self.signal_period);
+    }
+}
+
+// =====================
+// Bollinger Bands
+// =====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BollingerValue {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// SMA middle band with bands `multiplier` population standard
+/// deviations away.
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    multiplier: f64,
+    window: VecDeque<f64>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        let period = period.max(1);
+        Bollinger {
+            period,
+            multiplier,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn next(&mut self, close: f64) -> Option<BollingerValue> {
+        self.window.push_back(close);
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let n = self.period as f64;
+        let mean = self.window.iter().sum::<f64>() / n;
+        let variance = self.window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let width = self.multiplier * variance.sqrt();
+
+        Some(BollingerValue {
+            upper: mean + width,
+            middle: mean,
+            lower: mean - width,
+        })
+    }
+}
+
+impl Default for Bollinger {
+    fn default() -> Self {
+        Bollinger::new(20, 2.0)
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerValue;
+
+    fn name(&self) -> String {
+        format!("BB({},{})", self.period, self.multiplier)
+    }
+
+    fn update(&mut self, candle: &Candle) -> Option<BollingerValue> {
+        self.next(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Bollinger::new(self.period, self.multiplier);
+    }
+}
+
+// =====================
+// Ichimoku Cloud
+// =====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IchimokuValue {
+    /// Conversion line: midpoint of the `tenkan` period high/low
+    pub tenkan: f64,
+    /// Base line: midpoint of the `kijun` period high/low
+    pub kijun: f64,
+    /// Leading span A computed at this candle, plotted `displacement` ahead
+    pub senkou_a: Option<f64>,
+    /// Leading span B computed at this candle, plotted `displacement` ahead
+    pub senkou_b: Option<f64>,
+    /// Span A / B values that project onto this candle (the cloud here)
+    pub cloud_a: Option<f64>,
+    pub cloud_b: Option<f64>,
+    /// Lagging span: this close, plotted `displacement` behind
+    pub chikou: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ichimoku {
+    tenkan_period: usize,
+    kijun_period: usize,
+    senkou_b_period: usize,
+    displacement: usize,
+    window: VecDeque<(f64, f64)>,
+    /// Leading spans computed on previous candles, oldest first
+    leading: VecDeque<(Option<f64>, Option<f64>)>,
+}
+
+impl Ichimoku {
+    pub fn new(tenkan: usize, kijun: usize, senkou_b: usize, displacement: usize) -> Self {
+        Ichimoku {
+            tenkan_period: tenkan.max(1),
+            kijun_period: kijun.max(1),
+            senkou_b_period: senkou_b.max(1),
+            displacement: displacement.max(1),
+            window: VecDeque::new(),
+            leading: VecDeque::new(),
+        }
+    }
+
+    pub fn displacement(&self) -> usize {
+        self.displacement
+    }
+
+    fn midpoint(&self, period: usize) -> Option<f64> {
+        if self.window.len() < period {
+            return None;
+        }
+        let (high, low) = self
+            .window
+            .iter()
+            .rev()
+            .take(period)
+            .fold((f64::MIN, f64::MAX), |(h, l), (ch, cl)| (h.max(*ch), l.min(*cl)));
+        Some((high + low) / 2.0)
+    }
+}
+
+impl Default for Ichimoku {
+    fn default() -> Self {
+        Ichimoku::new(9, 26, 52, 26)
+    }
+}
+
+impl Indicator for Ichimoku {
+    type Output = IchimokuValue;
+
+    fn name(&self) -> String {
+        format!(
+            "Ichimoku({},{},{})",
+            self.tenkan_period, self.kijun_period, self.senkou_b_period
+        )
+    }
+
+    fn update(&mut self, candle: &Candle) -> Option<IchimokuValue> {
+        let longest = self
+            .tenkan_period
+            .max(self.kijun_period)
+            .max(self.senkou_b_period);
+        self.window.push_back((candle.high, candle.low));
+        if self.window.len() > longest {
+            self.window.pop_front();
+        }
+
+        let tenkan = self.midpoint(self.tenkan_period);
+        let kijun = self.midpoint(self.kijun_period);
+        let senkou_a = tenkan.zip(kijun).map(|(t, k)| (t + k) / 2.0);
+        let senkou_b = self.midpoint(self.senkou_b_period);
+
+        // Spans computed `displacement` candles ago land on this candle
+        self.leading.push_back((senkou_a, senkou_b));
+        let (cloud_a, cloud_b) = if self.leading.len() > self.displacement {
+            self.leading.pop_front().unwrap_or((None, None))
+        } else {
+            (None, None)
+        };
+
+        Some(IchimokuValue {
+            tenkan: tenkan?,
+            kijun: kijun?,
+            senkou_a,
+            senkou_b,
+            cloud_a,
+            cloud_b,
+            chikou: candle.close,
+        })
+    }
+
+    fn reset(&mut self) {
+        *self = Ichimoku::new(
+            self.tenkan_period,
+            self.kijun_period,
+            self.senkou_b_period,
+            self.displacement,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// StockCharts' 10-day moving average example.
+    const MA_CLOSES: [f64; 30] = [
+        22.2734, 22.1940, 22.0847, 22.1741, 22.1840, 22.1344, 22.2337, 22.4323, 22.2436, 22.2933,
+        22.1542, 22.3926, 22.3816, 22.6109, 23.3558, 24.0519, 23.7530, 23.8324, 23.9516, 23.6338,
+        23.8225, 23.8722, 23.6537, 23.1870, 23.0976, 23.3260, 22.6805, 23.0976, 22.4025, 22.1725,
+    ];
+    const SMA_10: [f64; 21] = [
+        22.22, 22.21, 22.23, 22.26, 22.31, 22.42, 22.61, 22.77, 22.91, 23.08, 23.21, 23.38, 23.53,
+        23.65, 23.71, 23.69, 23.61, 23.51, 23.43, 23.28, 23.13,
+    ];
+    const EMA_10: [f64; 21] = [
+        22.22, 22.21, 22.24, 22.27, 22.33, 22.52, 22.80, 22.97, 23.13, 23.28, 23.34, 23.43, 23.51,
+        23.54, 23.47, 23.40, 23.39, 23.26, 23.23, 23.08, 22.92,
+    ];
+
+    /// Wilder's 14-period RSI example as published by StockCharts.
+    const RSI_CLOSES: [f64; 33] = [
+        44.3389, 44.0902, 44.1497, 43.6124, 44.3278, 44.8264, 45.0955, 45.4245, 45.8433, 46.0826,
+        45.8931, 46.0328, 45.6140, 46.2820, 46.2820, 46.0028, 46.0328, 46.4116, 46.2222, 45.6439,
+        46.2122, 46.2521, 45.7137, 46.4515, 45.7835, 45.3548, 44.0288, 44.1783, 44.2181, 44.5672,
+        43.4205, 42.6628, 43.1314,
+    ];
+    const RSI_14: [f64; 19] = [
+        70.53, 66.32, 66.55, 69.41, 66.36, 57.97, 62.93, 63.26, 56.06, 62.38, 54.71, 50.42, 39.99,
+        41.46, 41.87, 45.46, 37.30, 33.08, 37.77,
+    ];
+
+    fn candle(time: i64, close: f64) -> Candle {
+        Candle {
+            time,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+        }
+    }
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes.iter().enumerate().map(|(i, &c)| candle(i as i64, c)).collect()
+    }
+
+    /// Values after warm-up, rounded to two decimals like the references.
+    fn rounded(values: &[Option<f64>]) -> Vec<f64> {
+        values.iter().flatten().map(|v| (v * 100.0).round() / 100.0).collect()
+    }
+
+    fn warmup<T>(values: &[Option<T>]) -> usize {
+        values.iter().take_while(|v| v.is_none()).count()
+    }
+
+    #[test]
+    fn sma_matches_reference() {
+        let values = compute(&mut Sma::new(10), &candles(&MA_CLOSES));
+        assert_eq!(warmup(&values), 9);
+        assert_eq!(rounded(&values), SMA_10);
+    }
+
+    #[test]
+    fn ema_matches_reference() {
+        let values = compute(&mut Ema::new(10), &candles(&MA_CLOSES));
+        assert_eq!(warmup(&values), 9);
+        assert_eq!(rounded(&values), EMA_10);
+    }
+
+    #[test]
+    fn rsi_matches_reference() {
+        let values = compute(&mut Rsi::new(14), &candles(&RSI_CLOSES));
+        assert_eq!(warmup(&values), 14);
+        assert_eq!(rounded(&values), RSI_14);
+    }
+
+    #[test]
+    fn rsi_edges() {
+        let rising: Vec<f64> = (0..20).map(f64::from).collect();
+        assert_eq!(compute(&mut Rsi::new(14), &candles(&rising)).last(), Some(&Some(100.0)));
+        let flat = [10.0; 20];
+        assert_eq!(compute(&mut Rsi::new(14), &candles(&flat)).last(), Some(&Some(50.0)));
+    }
+
+    #[test]
+    fn macd_on_linear_trend() {
+        // An SMA-seeded EMA of a straight line lags it by exactly
+        // (period - 1) / 2, so MACD(12,26,9) is 12.5 - 5.5 = 7 everywhere.
+        let closes: Vec<f64> = (0..60).map(f64::from).collect();
+        let values = compute(&mut Macd::default(), &candles(&closes));
+
+        // Slow EMA seeds after 26 closes, the signal after 9 MACD values
+        assert_eq!(warmup(&values), 25 + 8);
+        for value in values.iter().flatten() {
+            assert!((value.macd - 7.0).abs() < 1e-9);
+            assert!((value.signal - 7.0).abs() < 1e-9);
+            assert!(value.histogram.abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn macd_matches_ema_difference() {
+        let values = compute(&mut Macd::new(3, 6, 4), &candles(&MA_CLOSES));
+        let fast = compute(&mut Ema::new(3), &candles(&MA_CLOSES));
+        let slow = compute(&mut Ema::new(6), &candles(&MA_CLOSES));
+
+        let mut signal = Ema::new(4);
+        for (i, value) in values.iter().enumerate() {
+            let Some(macd) = fast[i].zip(slow[i]).map(|(f, s)| f - s) else {
+                assert!(value.is_none());
+                continue;
+            };
+            assert_eq!(
+                *value,
+                signal.next(macd).map(|signal| MacdValue {
+                    macd,
+                    signal,
+                    histogram: macd - signal,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn bollinger_matches_reference() {
+        // Mean 5, population standard deviation 2
+        let values = compute(&mut Bollinger::new(8, 2.0), &candles(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]));
+        assert_eq!(warmup(&values), 7);
+        assert_eq!(
+            values.last(),
+            Some(&Some(BollingerValue {
+                upper: 9.0,
+                middle: 5.0,
+                lower: 1.0,
+            }))
+        );
+
+        let flat = compute(&mut Bollinger::new(3, 2.0), &candles(&[4.0; 5]));
+        let last = flat.last().unwrap().unwrap();
+        assert_eq!((last.upper, last.middle, last.lower), (4.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn ichimoku_on_rising_ranges() {
+        // Candle t spans [t, t + 1], so a p-period midpoint is t + 1 - p/2
+        let series: Vec<Candle> = (0..8)
+            .map(|t| Candle {
+                time: t,
+                open: t as f64,
+                high: t as f64 + 1.0,
+                low: t as f64,
+                close: t as f64 + 0.5,
+                volume: 0.0,
+            })
+            .collect();
+        let values = compute(&mut Ichimoku::new(2, 3, 4, 2), &series);
+        assert_eq!(warmup(&values), 2);
+
+        let at = |t: usize| values[t].unwrap();
+        assert_eq!((at(2).tenkan, at(2).kijun), (2.0, 1.5));
+        assert_eq!((at(2).senkou_a, at(2).senkou_b), (Some(1.75), None));
+        assert_eq!(at(3).senkou_b, Some(2.0));
+        assert_eq!(at(3).chikou, 3.5);
+
+        // Leading spans land `displacement` candles later
+        assert_eq!((at(3).cloud_a, at(3).cloud_b), (None, None));
+        assert_eq!((at(4).cloud_a, at(4).cloud_b), (Some(1.75), None));
+        assert_eq!((at(5).cloud_a, at(5).cloud_b), (Some(2.75), Some(2.0)));
+        assert_eq!(at(7).cloud_a, at(5).senkou_a);
+    }
+
+    #[test]
+    fn reset_restarts_warmup() {
+        let mut rsi = Rsi::new(14);
+        compute(&mut rsi, &candles(&RSI_CLOSES));
+        rsi.reset();
+        assert_eq!(rounded(&compute(&mut rsi, &candles(&RSI_CLOSES))), RSI_14);
+    }
+
+    /// Feed every candle as three ticks of a forming candle (plus a stale
+    /// one) and check the streamed values equal a batch run over the final
+    /// candles.
+    fn assert_streaming_matches_batch<I>(indicator: I, series: &[Candle])
+    where
+        I: Indicator + Clone,
+        I::Output: PartialEq + std::fmt::Debug,
+    {
+        let batch = compute(&mut indicator.clone(), series);
+        let mut streaming = StreamingIndicator::new(indicator, series.len());
+
+        for (i, candle) in series.iter().enumerate() {
+            for tick in [0.99, 1.01] {
+                let mut forming = candle.clone();
+                forming.close *= tick;
+                forming.high = forming.high.max(forming.close);
+                forming.low = forming.low.min(forming.close);
+                streaming.push(&forming);
+            }
+            assert_eq!(streaming.push(candle), batch[i]);
+            if i > 0 {
+                // Out-of-order update for the previous candle is ignored
+                assert_eq!(streaming.push(&series[i - 1]), batch[i]);
+            }
+        }
+        assert_eq!(streaming.values(), batch.as_slice());
+    }
+
+    #[test]
+    fn streaming_matches_batch() {
+        let series = candles(&RSI_CLOSES);
+        assert_streaming_matches_batch(Sma::new(5), &series);
+        assert_streaming_matches_batch(Ema::new(5), &series);
+        assert_streaming_matches_batch(Rsi::new(14), &series);
+        assert_streaming_matches_batch(Macd::new(3, 6, 4), &series);
+        assert_streaming_matches_batch(Bollinger::new(5, 2.0), &series);
+        assert_streaming_matches_batch(Ichimoku::new(2, 3, 4, 2), &series);
+    }
+
+    #[test]
+    fn streaming_trims_to_max_len() {
+        let mut streaming = StreamingIndicator::new(Sma::new(2), 3);
+        for candle in candles(&[1.0, 2.0, 3.0, 4.0, 5.0]) {
+            streaming.push(&candle);
+        }
+        assert_eq!(streaming.values(), &[Some(2.5), Some(3.5), Some(4.5)]);
+        assert_eq!(streaming.latest(), Some(&4.5));
+        assert_eq!(streaming.name(), "SMA(2)");
+    }
+}
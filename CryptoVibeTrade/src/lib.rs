@@ -1,13 +1,30 @@
 #![forbid(unsafe_code)]
 
+pub mod ai;
+pub mod alerts;
 pub mod app;
+pub mod board_export;
+pub mod board_geometry;
+pub mod board_history;
+pub mod boards;
+pub mod collab;
 pub mod components;
 pub mod routes;
 pub mod server;
+pub mod session;
+pub mod solana_rpc;
 pub mod database;
+pub mod events;
+pub mod indicators;
+pub mod market_data;
+pub mod market_snapshot;
+pub mod migrations;
+pub mod nft_gating;
+pub mod portfolio;
 pub mod types;
+pub mod wallet_auth;
 
 pub use app::App;
-pub use server::{login, grok_analyze, ai_analyze, glm_analyze, register_user, set_database};
+pub use server::{login, logout, current_user, grok_analyze, ai_analyze, glm_analyze, register_user, set_database};
 pub use database::Database;
 pub use types::*;
@@ -0,0 +1,724 @@
+use crate::types::{upsert_candle, Candle, MarketMessage};
+use anyhow::{anyhow, Result};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tokio_tungstenite::tungstenite::Message;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A connection that stayed up this long was healthy, so the next
+/// reconnect starts again from `MIN_BACKOFF`.
+const STABLE_CONNECTION: Duration = Duration::from_secs(60);
+
+// =====================
+// Config
+// =====================
+
+#[derive(Debug, Clone)]
+pub struct MarketDataConfig {
+    /// Binance symbols, e.g. "BTCUSDT"
+    pub symbols: Vec<String>,
+    /// Kline intervals, e.g. "1m", "1h"
+    pub intervals: Vec<String>,
+    /// Candles kept per (symbol, interval)
+    pub history: usize,
+    pub ws_url: String,
+    pub rest_url: String,
+}
+
+impl MarketDataConfig {
+    pub fn from_env() -> Self {
+        let list = |key: &str, default: &str| -> Vec<String> {
+            env::var(key)
+                .unwrap_or_else(|_| default.to_string())
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+
+        MarketDataConfig {
+            // BONK and RAY price the wallet portfolio
+            symbols: list("MARKET_SYMBOLS", "BTCUSDT,SOLUSDT,ZECUSDT,BONKUSDT,RAYUSDT")
+                .into_iter()
+                .map(|s| s.to_uppercase())
+                .collect(),
+            intervals: list("MARKET_INTERVALS", "1m,1h"),
+            history: env::var("MARKET_HISTORY")
+                .ok()
+                .and_then(|h| h.parse().ok())
+                .unwrap_or(500),
+            ws_url: env::var("BINANCE_WS_URL")
+                .unwrap_or_else(|_| "wss://stream.binance.com:9443".to_string()),
+            rest_url: env::var("BINANCE_REST_URL")
+                .unwrap_or_else(|_| "https://api.binance.com".to_string()),
+        }
+    }
+
+    /// Combined-stream URL subscribing to every configured kline stream.
+    pub fn stream_url(&self) -> String {
+        let streams: Vec<String> = self
+            .symbols
+            .iter()
+            .flat_map(|symbol| {
+                self.intervals
+                    .iter()
+                    .map(move |interval| format!("{}@kline_{}", symbol.to_lowercase(), interval))
+            })
+            .collect();
+
+        format!(
+            "{}/stream?streams={}",
+            self.ws_url.trim_end_matches('/'),
+            streams.join("/")
+        )
+    }
+}
+
+/// Length of a Binance kline interval ("1s", "15m", "4h", "1d", "1w", "1M")
+/// in milliseconds, for ordering intervals; a month counts as 31 days.
+pub fn interval_ms(interval: &str) -> Option<i64> {
+    let unit = interval.chars().last()?;
+    let count: i64 = interval[..interval.len() - unit.len_utf8()].parse().ok().filter(|n| *n > 0)?;
+    let unit_ms = match unit {
+        's' => 1_000,
+        'm' => 60_000,
+        'h' => 3_600_000,
+        'd' => 86_400_000,
+        'w' => 7 * 86_400_000,
+        'M' => 31 * 86_400_000,
+        _ => return None,
+    };
+    Some(count * unit_ms)
+}
+
+// =====================
+// Kline parsing
+// =====================
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeriesKey {
+    pub symbol: String,
+    pub interval: String,
+}
+
+impl SeriesKey {
+    pub fn new(symbol: &str, interval: &str) -> Self {
+        SeriesKey {
+            symbol: symbol.to_uppercase(),
+            interval: interval.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KlineUpdate {
+    pub key: SeriesKey,
+    pub candle: Candle,
+    pub closed: bool,
+}
+
+impl KlineUpdate {
+    pub fn to_message(&self) -> MarketMessage {
+        MarketMessage::Candle {
+            symbol: self.key.symbol.clone(),
+            interval: self.key.interval.clone(),
+            candle: self.candle.clone(),
+            closed: self.closed,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct KlineEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "k")]
+    kline: Kline,
+}
+
+#[derive(Deserialize)]
+struct Kline {
+    #[serde(rename = "t")]
+    open_time: i64,
+    #[serde(rename = "i")]
+    interval: String,
+    #[serde(rename = "o")]
+    open: String,
+    #[serde(rename = "h")]
+    high: String,
+    #[serde(rename = "l")]
+    low: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "x")]
+    closed: bool,
+}
+
+fn parse_price(value: &str, field: &str) -> Result<f64> {
+    value
+        .parse::<f64>()
+        .map_err(|_| anyhow!("invalid {} in kline: {:?}", field, value))
+}
+
+/// Parse a Binance kline payload, either a raw stream event
+/// (`{"e":"kline",...}`) or a combined-stream wrapper
+/// (`{"stream":"btcusdt@kline_1m","data":{...}}`). Returns `Ok(None)` for
+/// non-kline messages.
+pub fn parse_kline_message(text: &str) -> Result<Option<KlineUpdate>> {
+    let value: serde_json::Value = serde_json::from_str(text)?;
+    let event = value.get("data").unwrap_or(&value);
+
+    if event.get("k").is_none() {
+        return Ok(None);
+    }
+
+    let event: KlineEvent = serde_json::from_value(event.clone())?;
+    let k = event.kline;
+
+    Ok(Some(KlineUpdate {
+        key: SeriesKey::new(&event.symbol, &k.interval),
+        candle: Candle {
+            time: k.open_time,
+            open: parse_price(&k.open, "open")?,
+            high: parse_price(&k.high, "high")?,
+            low: parse_price(&k.low, "low")?,
+            close: parse_price(&k.close, "close")?,
+            volume: parse_price(&k.volume, "volume")?,
+        },
+        closed: k.closed,
+    }))
+}
+
+/// Parse one row of the REST `/api/v3/klines` response:
+/// `[openTime, "open", "high", "low", "close", "volume", ...]`.
+pub fn parse_rest_kline(row: &serde_json::Value) -> Result<Candle> {
+    let field = |i: usize, name: &str| -> Result<f64> {
+        let value = row
+            .get(i)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("missing {} in kline row", name))?;
+        parse_price(value, name)
+    };
+
+    Ok(Candle {
+        time: row
+            .get(0)
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| anyhow!("missing open time in kline row"))?,
+        open: field(1, "open")?,
+        high: field(2, "high")?,
+        low: field(3, "low")?,
+        close: field(4, "close")?,
+        volume: field(5, "volume")?,
+    })
+}
+
+// =====================
+// Candle store
+// =====================
+
+/// Rolling candle window per (symbol, interval).
+#[derive(Debug, Default)]
+pub struct CandleStore {
+    series: HashMap<SeriesKey, Vec<Candle>>,
+    capacity: usize,
+}
+
+impl CandleStore {
+    pub fn new(capacity: usize) -> Self {
+        CandleStore {
+            series: HashMap::new(),
+            capacity,
+        }
+    }
+
+    pub fn apply(&mut self, update: &KlineUpdate) {
+        let candles = self.series.entry(update.key.clone()).or_default();
+        upsert_candle(candles, update.candle.clone(), self.capacity);
+    }
+
+    pub fn replace(&mut self, key: SeriesKey, mut candles: Vec<Candle>) {
+        candles.sort_by_key(|c| c.time);
+        if candles.len() > self.capacity {
+            candles.drain(..candles.len() - self.capacity);
+        }
+        self.series.insert(key, candles);
+    }
+
+    pub fn candles(&self, key: &SeriesKey) -> Vec<Candle> {
+        self.series.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn latest(&self, key: &SeriesKey) -> Option<&Candle> {
+        self.series.get(key).and_then(|c| c.last())
+    }
+}
+
+// =====================
+// Service
+// =====================
+
+/// Shared market data: one upstream Binance connection feeding a rolling
+/// store, with every update fanned out to subscribers.
+#[derive(Clone)]
+pub struct MarketData {
+    config: Arc<MarketDataConfig>,
+    store: Arc<RwLock<CandleStore>>,
+    updates: broadcast::Sender<KlineUpdate>,
+}
+
+impl MarketData {
+    pub fn new(config: MarketDataConfig) -> Self {
+        let (updates, _) = broadcast::channel(1024);
+        MarketData {
+            store: Arc::new(RwLock::new(CandleStore::new(config.history))),
+            config: Arc::new(config),
+            updates,
+        }
+    }
+
+    pub fn config(&self) -> &MarketDataConfig {
+        &self.config
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<KlineUpdate> {
+        self.updates.subscribe()
+    }
+
+    pub async fn candles(&self, symbol: &str, interval: &str) -> Vec<Candle> {
+        self.store.read().await.candles(&SeriesKey::new(symbol, interval))
+    }
+
+    pub async fn latest(&self, symbol: &str, interval: &str) -> Option<Candle> {
+        self.store
+            .read()
+            .await
+            .latest(&SeriesKey::new(symbol, interval))
+            .cloned()
+    }
+
+    pub async fn snapshot(&self, symbol: &str, interval: &str) -> MarketMessage {
+        MarketMessage::Snapshot {
+            symbol: symbol.to_uppercase(),
+            interval: interval.to_string(),
+            candles: self.candles(symbol, interval).await,
+        }
+    }
+
+    /// Record an update and broadcast it (also used to feed the store in
+    /// tests or replays without a network connection).
+    pub async fn ingest(&self, update: KlineUpdate) {
+        self.store.write().await.apply(&update);
+        // No subscribers is fine
+        let _ = self.updates.send(update);
+    }
+
+    /// Spawn the background task: load REST history, then follow the
+    /// kline WebSocket, reconnecting with backoff. Every reconnect reloads
+    /// the REST history so candles missed while disconnected are backfilled.
+    pub fn spawn(&self) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            service.load_history().await;
+
+            let mut backoff = MIN_BACKOFF;
+            let mut backfill = false;
+            loop {
+                let connected_at = Instant::now();
+                match service.follow_stream(backfill).await {
+                    Ok(()) => tracing::warn!("Binance kline stream closed, reconnecting"),
+                    Err(e) => tracing::warn!("Binance kline stream error: {}", e),
+                }
+                backoff = next_backoff(backoff, connected_at.elapsed());
+                backfill = true;
+                tokio::time::sleep(backoff).await;
+            }
+        });
+    }
+
+    /// Load REST history for every configured series. A failing series
+    /// (e.g. a delisted or mistyped symbol) is logged and skipped so the
+    /// others still load.
+    async fn load_history(&self) {
+        let client = reqwest::Client::new();
+
+        for symbol in &self.config.symbols {
+            for interval in &self.config.intervals {
+                if let Err(e) = self.load_series(&client, symbol, interval).await {
+                    tracing::warn!("Loading {} {} history failed: {}", symbol, interval, e);
+                }
+            }
+        }
+    }
+
+    async fn load_series(&self, client: &reqwest::Client, symbol: &str, interval: &str) -> Result<()> {
+        let url = format!(
+            "{}/api/v3/klines?symbol={}&interval={}&limit={}",
+            self.config.rest_url.trim_end_matches('/'),
+            symbol,
+            interval,
+            self.config.history.min(1000)
+        );
+        let rows = client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<serde_json::Value>>()
+            .await?;
+
+        let candles = rows
+            .iter()
+            .map(parse_rest_kline)
+            .collect::<Result<Vec<_>>>()?;
+
+        tracing::info!("Loaded {} {} {} candles", candles.len(), symbol, interval);
+        self.store
+            .write()
+            .await
+            .replace(SeriesKey::new(symbol, interval), candles);
+        Ok(())
+    }
+
+    /// Read the kline stream until it closes. With `backfill`, history is
+    /// reloaded once connected; updates arriving meanwhile queue on the
+    /// socket and are applied on top.
+    async fn follow_stream(&self, backfill: bool) -> Result<()> {
+        let url = self.config.stream_url();
+        let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str()).await?;
+        tracing::info!("Connected to Binance kline stream: {}", url);
+
+        if backfill {
+            self.load_history().await;
+        }
+
+        while let Some(message) = socket.next().await {
+            match message? {
+                Message::Text(text) => match parse_kline_message(&text) {
+                    Ok(Some(update)) => self.ingest(update).await,
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!("Ignoring malformed kline message: {}", e),
+                },
+                Message::Ping(payload) => socket.send(Message::Pong(payload)).await?,
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Delay before the next reconnect: back to `MIN_BACKOFF` after a stable
+/// connection, otherwise double the previous delay up to `MAX_BACKOFF`.
+fn next_backoff(previous: Duration, connected_for: Duration) -> Duration {
+    if connected_for >= STABLE_CONNECTION {
+        MIN_BACKOFF
+    } else {
+        (previous * 2).min(MAX_BACKOFF)
+    }
+}
+
+static MARKET_DATA: OnceLock<MarketData> = OnceLock::new();
+
+/// Create the process-wide market data service and start its upstream
+/// task. Later calls return the already running instance.
+pub fn start_market_data(config: MarketDataConfig) -> &'static MarketData {
+    MARKET_DATA.get_or_init(|| {
+        let service = MarketData::new(config);
+        service.spawn();
+        service
+    })
+}
+
+pub fn market_data() -> Option<&'static MarketData> {
+    MARKET_DATA.get()
+}
+
+// =====================
+// Browser WebSocket (/ws/market)
+// =====================
+
+#[derive(Debug, Deserialize)]
+pub struct MarketQuery {
+    pub symbol: Option<String>,
+    pub interval: Option<String>,
+}
+
+/// `GET /ws/market?symbol=BTCUSDT&interval=1m`: sends a snapshot of the
+/// rolling window, then every update for that series.
+pub async fn market_ws_handler(
+    ws: axum::extract::ws::WebSocketUpgrade,
+    axum::extract::Query(query): axum::extract::Query<MarketQuery>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    let Some(market) = market_data() else {
+        return (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            "Market data service not running",
+        )
+            .into_response();
+    };
+
+    let key = SeriesKey::new(
+        query.symbol.as_deref().unwrap_or("BTCUSDT"),
+        query.interval.as_deref().unwrap_or("1m"),
+    );
+
+    ws.on_upgrade(move |socket| market_socket(socket, market, key))
+}
+
+async fn market_socket(
+    mut socket: axum::extract::ws::WebSocket,
+    market: &'static MarketData,
+    key: SeriesKey,
+) {
+    use axum::extract::ws::Message as WsMessage;
+
+    async fn send(socket: &mut axum::extract::ws::WebSocket, message: &MarketMessage) -> bool {
+        match serde_json::to_string(message) {
+            Ok(text) => socket.send(WsMessage::Text(text)).await.is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    // Subscribe before taking the snapshot so no update falls in between
+    let mut updates = market.subscribe();
+    if !send(&mut socket, &market.snapshot(&key.symbol, &key.interval).await).await {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(update) if update.key == key => {
+                    if !send(&mut socket, &update.to_message()).await {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    // Too slow to keep up: resync with a fresh snapshot
+                    if !send(&mut socket, &market.snapshot(&key.symbol, &key.interval).await).await {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const OPEN_KLINE: &str = r#"{"stream":"btcusdt@kline_1m","data":{"e":"kline","E":1700000030000,"s":"BTCUSDT","k":{"t":1700000000000,"T":1700000059999,"s":"BTCUSDT","i":"1m","f":100,"L":200,"o":"37000.10","c":"37010.50","h":"37020.00","l":"36990.00","v":"12.5","n":100,"x":false,"q":"462500.0","V":"6.0","Q":"222000.0","B":"0"}}}"#;
+
+    const CLOSED_KLINE: &str = r#"{"e":"kline","E":1700000060000,"s":"SOLUSDT","k":{"t":1700000000000,"T":1700000059999,"s":"SOLUSDT","i":"1h","o":"55.0","c":"56.5","h":"57.0","l":"54.5","v":"1000","x":true}}"#;
+
+    fn config() -> MarketDataConfig {
+        MarketDataConfig {
+            symbols: vec!["BTCUSDT".to_string(), "SOLUSDT".to_string()],
+            intervals: vec!["1m".to_string(), "1h".to_string()],
+            history: 3,
+            ws_url: "wss://stream.binance.com:9443/".to_string(),
+            rest_url: "https://api.binance.com".to_string(),
+        }
+    }
+
+    fn update(time: i64, close: f64) -> KlineUpdate {
+        KlineUpdate {
+            key: SeriesKey::new("btcusdt", "1m"),
+            candle: Candle {
+                time,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1.0,
+            },
+            closed: false,
+        }
+    }
+
+    #[test]
+    fn parses_open_kline_from_combined_stream() {
+        let update = parse_kline_message(OPEN_KLINE).unwrap().unwrap();
+        assert_eq!(update.key, SeriesKey::new("BTCUSDT", "1m"));
+        assert!(!update.closed);
+        assert_eq!(update.candle.time, 1_700_000_000_000);
+        assert_eq!(update.candle.open, 37000.10);
+        assert_eq!(update.candle.high, 37020.00);
+        assert_eq!(update.candle.low, 36990.00);
+        assert_eq!(update.candle.close, 37010.50);
+        assert_eq!(update.candle.volume, 12.5);
+    }
+
+    #[test]
+    fn parses_closed_raw_kline() {
+        let update = parse_kline_message(CLOSED_KLINE).unwrap().unwrap();
+        assert_eq!(update.key, SeriesKey::new("SOLUSDT", "1h"));
+        assert!(update.closed);
+        assert_eq!(update.candle.close, 56.5);
+    }
+
+    #[test]
+    fn ignores_non_kline_messages() {
+        assert!(parse_kline_message(r#"{"result":null,"id":1}"#).unwrap().is_none());
+        assert!(parse_kline_message(r#"{"stream":"x","data":{"e":"trade"}}"#).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_klines() {
+        assert!(parse_kline_message("not json").is_err());
+        // Missing close price
+        assert!(parse_kline_message(r#"{"s":"BTCUSDT","k":{"t":1,"i":"1m","o":"1","h":"1","l":"1","v":"1","x":false}}"#).is_err());
+        // Price that is not a number
+        let bad_price = CLOSED_KLINE.replace(r#""c":"56.5""#, r#""c":"n/a""#);
+        let error = parse_kline_message(&bad_price).unwrap_err();
+        assert!(error.to_string().contains("invalid close"));
+    }
+
+    #[test]
+    fn parses_rest_kline_row() {
+        let row = json!([
+            1700000000000i64, "37000.10", "37020.00", "36990.00", "37010.50", "12.5",
+            1700000059999i64, "462500.0", 100, "6.0", "222000.0", "0"
+        ]);
+        let candle = parse_rest_kline(&row).unwrap();
+        assert_eq!(candle.time, 1_700_000_000_000);
+        assert_eq!(candle.open, 37000.10);
+        assert_eq!(candle.close, 37010.50);
+        assert_eq!(candle.volume, 12.5);
+    }
+
+    #[test]
+    fn rejects_malformed_rest_rows() {
+        assert!(parse_rest_kline(&json!(["1700000000000", "1", "1", "1", "1", "1"])).is_err());
+        assert!(parse_rest_kline(&json!([1700000000000i64, "1", "1", "1"])).is_err());
+        assert!(parse_rest_kline(&json!([1700000000000i64, 1.0, 1.0, 1.0, 1.0, 1.0])).is_err());
+        assert!(parse_rest_kline(&json!({"open": "1"})).is_err());
+    }
+
+    #[test]
+    fn store_upserts_and_caps_history() {
+        let mut store = CandleStore::new(3);
+        let key = SeriesKey::new("BTCUSDT", "1m");
+
+        store.apply(&update(1, 10.0));
+        store.apply(&update(1, 11.0));
+        assert_eq!(store.candles(&key).len(), 1);
+        assert_eq!(store.latest(&key).unwrap().close, 11.0);
+
+        for time in 2..=5 {
+            store.apply(&update(time, time as f64));
+        }
+        let times: Vec<i64> = store.candles(&key).iter().map(|c| c.time).collect();
+        assert_eq!(times, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn store_replace_sorts_and_trims() {
+        let mut store = CandleStore::new(3);
+        let key = SeriesKey::new("BTCUSDT", "1m");
+        let candles = [5, 1, 4, 2, 3].iter().map(|&t| update(t, 1.0).candle).collect();
+        store.replace(key.clone(), candles);
+
+        let times: Vec<i64> = store.candles(&key).iter().map(|c| c.time).collect();
+        assert_eq!(times, vec![3, 4, 5]);
+        assert!(store.candles(&SeriesKey::new("ETHUSDT", "1m")).is_empty());
+    }
+
+    #[test]
+    fn stream_url_lists_every_series() {
+        assert_eq!(
+            config().stream_url(),
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@kline_1m/btcusdt@kline_1h/solusdt@kline_1m/solusdt@kline_1h"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_and_resets_after_stable_connection() {
+        let flaky = Duration::from_secs(2);
+        assert_eq!(next_backoff(MIN_BACKOFF, flaky), Duration::from_secs(2));
+        assert_eq!(next_backoff(Duration::from_secs(40), flaky), MAX_BACKOFF);
+        assert_eq!(next_backoff(MAX_BACKOFF, STABLE_CONNECTION), MIN_BACKOFF);
+    }
+
+    #[test]
+    fn interval_lengths() {
+        assert_eq!(interval_ms("1s"), Some(1_000));
+        assert_eq!(interval_ms("15m"), Some(15 * 60_000));
+        assert_eq!(interval_ms("4h"), Some(4 * 3_600_000));
+        assert_eq!(interval_ms("1d"), Some(86_400_000));
+        assert!(interval_ms("1M") > interval_ms("1w"));
+        for bad in ["", "m", "0m", "-1m", "1x", "1H"] {
+            assert_eq!(interval_ms(bad), None, "{:?}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn ingest_stores_and_broadcasts() {
+        let market = MarketData::new(config());
+        let mut updates = market.subscribe();
+
+        market.ingest(update(1, 10.0)).await;
+
+        assert_eq!(market.candles("btcusdt", "1m").await.len(), 1);
+        assert_eq!(market.latest("BTCUSDT", "1m").await.unwrap().close, 10.0);
+        assert_eq!(updates.recv().await.unwrap().candle.time, 1);
+    }
+
+    #[tokio::test]
+    async fn failing_symbol_does_not_block_the_others() {
+        use axum::extract::Query;
+        use axum::http::StatusCode;
+        use axum::response::IntoResponse;
+
+        async fn klines(Query(query): Query<HashMap<String, String>>) -> axum::response::Response {
+            if query["symbol"] == "NOPEUSDT" {
+                return (StatusCode::BAD_REQUEST, r#"{"code":-1121,"msg":"Invalid symbol."}"#).into_response();
+            }
+            axum::Json(json!([
+                [1_700_000_000_000i64, "1.0", "2.0", "0.5", "1.5", "10", 1_700_000_059_999i64],
+                [1_700_000_060_000i64, "1.5", "2.5", "1.0", "2.0", "12", 1_700_000_119_999i64],
+            ]))
+            .into_response()
+        }
+
+        let app = axum::Router::new().route("/api/v3/klines", axum::routing::get(klines));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rest_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let market = MarketData::new(MarketDataConfig {
+            symbols: vec!["NOPEUSDT".to_string(), "BTCUSDT".to_string()],
+            rest_url,
+            ..config()
+        });
+        market.load_history().await;
+
+        assert!(market.candles("NOPEUSDT", "1m").await.is_empty());
+        for interval in ["1m", "1h"] {
+            let candles = market.candles("BTCUSDT", interval).await;
+            assert_eq!(candles.len(), 2);
+            assert_eq!(candles[1].close, 2.0);
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use crate::indicators::{
+    Bollinger, BollingerValue, Ema, Ichimoku, IchimokuValue, Indicator, Macd, MacdValue, Rsi, Sma,
+};
+use crate::market_data::market_data;
+use crate::types::Candle;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Candles quoted verbatim in the prompt
+const PROMPT_CANDLES: usize = 12;
+/// Bars on each side a swing high/low must dominate
+const SWING_STRENGTH: usize = 3;
+/// Most recent swing highs (and lows) kept in the snapshot
+const MAX_SWINGS: usize = 4;
+
+// =====================
+// Snapshot types
+// =====================
+
+/// Real numbers for one asset, built server-side from the live candle
+/// feed and rendered into AI prompts so the model's levels are grounded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSnapshot {
+    pub symbol: String,
+    pub interval: String,
+    pub last_price: f64,
+    /// Close-to-close change over the whole candle window, in percent
+    pub change_pct: f64,
+    pub window_high: f64,
+    pub window_low: f64,
+    /// Total candles the indicators were computed from
+    pub candle_count: usize,
+    /// Most recent candles, oldest first
+    pub recent: Vec<Candle>,
+    pub indicators: IndicatorSnapshot,
+    /// Detected swing points, oldest first
+    pub swings: Vec<SwingPoint>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndicatorSnapshot {
+    pub sma_20: Option<f64>,
+    pub ema_50: Option<f64>,
+    pub rsi_14: Option<f64>,
+    pub macd: Option<MacdValue>,
+    pub bollinger: Option<BollingerValue>,
+    pub ichimoku: Option<IchimokuValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwingKind {
+    High,
+    Low,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SwingPoint {
+    pub kind: SwingKind,
+    pub time: i64,
+    pub price: f64,
+}
+
+// =====================
+// Building
+// =====================
+
+/// Last value an indicator produced over `candles`.
+fn last_value<I: Indicator>(mut indicator: I, candles: &[Candle]) -> Option<I::Output> {
+    candles.iter().filter_map(|c| indicator.update(c)).last()
+}
+
+impl IndicatorSnapshot {
+    pub fn compute(candles: &[Candle]) -> Self {
+        IndicatorSnapshot {
+            sma_20: last_value(Sma::new(20), candles),
+            ema_50: last_value(Ema::new(50), candles),
+            rsi_14: last_value(Rsi::default(), candles),
+            macd: last_value(Macd::default(), candles),
+            bollinger: last_value(Bollinger::default(), candles),
+            ichimoku: last_value(Ichimoku::default(), candles),
+        }
+    }
+}
+
+/// Fractal swing detection: a candle is a swing high (low) when its high
+/// (low) beats the `strength` candles on both sides. Ties resolve to the
+/// earlier candle so a flat top is reported once.
+pub fn detect_swings(candles: &[Candle], strength: usize) -> Vec<SwingPoint> {
+    let mut swings = Vec::new();
+    if strength == 0 || candles.len() < 2 * strength + 1 {
+        return swings;
+    }
+
+    for i in strength..candles.len() - strength {
+        let candle = &candles[i];
+        let before = &candles[i - strength..i];
+        let after = &candles[i + 1..=i + strength];
+
+        if before.iter().all(|c| c.high < candle.high) && after.iter().all(|c| c.high <= candle.high) {
+            swings.push(SwingPoint {
+                kind: SwingKind::High,
+                time: candle.time,
+                price: candle.high,
+            });
+        }
+        if before.iter().all(|c| c.low > candle.low) && after.iter().all(|c| c.low >= candle.low) {
+            swings.push(SwingPoint {
+                kind: SwingKind::Low,
+                time: candle.time,
+                price: candle.low,
+            });
+        }
+    }
+
+    swings
+}
+
+impl MarketSnapshot {
+    /// Summarize a time-ordered candle history. `None` when empty.
+    pub fn build(symbol: &str, interval: &str, candles: &[Candle]) -> Option<Self> {
+        let first = candles.first()?;
+        let last = candles.last()?;
+
+        let (window_high, window_low) = candles
+            .iter()
+            .fold((f64::MIN, f64::MAX), |(h, l), c| (h.max(c.high), l.min(c.low)));
+        let change_pct = if first.close > 0.0 {
+            (last.close - first.close) / first.close * 100.0
+        } else {
+            0.0
+        };
+
+        let mut highs: Vec<SwingPoint> = Vec::new();
+        let mut lows: Vec<SwingPoint> = Vec::new();
+        for swing in detect_swings(candles, SWING_STRENGTH) {
+            match swing.kind {
+                SwingKind::High => highs.push(swing),
+                SwingKind::Low => lows.push(swing),
+            }
+        }
+        let mut swings: Vec<SwingPoint> = highs
+            .iter()
+            .rev()
+            .take(MAX_SWINGS)
+            .chain(lows.iter().rev().take(MAX_SWINGS))
+            .copied()
+            .collect();
+        swings.sort_by_key(|s| s.time);
+
+        Some(MarketSnapshot {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            last_price: last.close,
+            change_pct,
+            window_high,
+            window_low,
+            candle_count: candles.len(),
+            recent: candles[candles.len().saturating_sub(PROMPT_CANDLES)..].to_vec(),
+            indicators: IndicatorSnapshot::compute(candles),
+            swings,
+        })
+    }
+
+    /// Plain-text block for the prompt. Every number the model may quote
+    /// comes from here.
+    pub fn to_prompt(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Market snapshot ({} {}, {} candles, times UTC):",
+            self.symbol, self.interval, self.candle_count
+        );
+        let _ = writeln!(
+            out,
+            "Last price: {} | Window change: {:+.2}% | Window high: {} | Window low: {}",
+            fmt_price(self.last_price),
+            self.change_pct,
+            fmt_price(self.window_high),
+            fmt_price(self.window_low)
+        );
+
+        let ind = &self.indicators;
+        let _ = writeln!(out, "Indicators:");
+        let _ = writeln!(out, "- SMA(20): {}", fmt_opt(ind.sma_20));
+        let _ = writeln!(out, "- EMA(50): {}", fmt_opt(ind.ema_50));
+        let _ = writeln!(
+            out,
+            "- RSI(14): {}",
+            ind.rsi_14.map(|v| format!("{:.1}", v)).unwrap_or_else(|| "n/a".to_string())
+        );
+        match &ind.macd {
+            Some(m) => {
+                let _ = writeln!(
+                    out,
+                    "- MACD(12,26,9): macd {:.4}, signal {:.4}, histogram {:.4}",
+                    m.macd, m.signal, m.histogram
+                );
+            }
+            None => {
+                let _ = writeln!(out, "- MACD(12,26,9): n/a");
+            }
+        }
+        match &ind.bollinger {
+            Some(b) => {
+                let _ = writeln!(
+                    out,
+                    "- Bollinger(20,2): upper {}, middle {}, lower {}",
+                    fmt_price(b.upper),
+                    fmt_price(b.middle),
+                    fmt_price(b.lower)
+                );
+            }
+            None => {
+                let _ = writeln!(out, "- Bollinger(20,2): n/a");
+            }
+        }
+        match &ind.ichimoku {
+            Some(i) => {
+                let _ = writeln!(
+                    out,
+                    "- Ichimoku(9,26,52): tenkan {}, kijun {}, cloud A {}, cloud B {}",
+                    fmt_price(i.tenkan),
+                    fmt_price(i.kijun),
+                    fmt_opt(i.cloud_a),
+                    fmt_opt(i.cloud_b)
+                );
+            }
+            None => {
+                let _ = writeln!(out, "- Ichimoku(9,26,52): n/a");
+            }
+        }
+
+        let swings: Vec<String> = self
+            .swings
+            .iter()
+            .map(|s| {
+                let kind = match s.kind {
+                    SwingKind::High => "high",
+                    SwingKind::Low => "low",
+                };
+                format!("{} {} @ {}", kind, fmt_price(s.price), fmt_time(s.time))
+            })
+            .collect();
+        if swings.is_empty() {
+            let _ = writeln!(out, "Swing points: none detected");
+        } else {
+            let _ = writeln!(out, "Swing points: {}", swings.join("; "));
+        }
+
+        let _ = writeln!(out, "Recent candles (time, open, high, low, close, volume):");
+        for c in &self.recent {
+            let _ = writeln!(
+                out,
+                "{} {} {} {} {} {:.2}",
+                fmt_time(c.time),
+                fmt_price(c.open),
+                fmt_price(c.high),
+                fmt_price(c.low),
+                fmt_price(c.close),
+                c.volume
+            );
+        }
+
+        out
+    }
+}
+
+fn fmt_price(price: f64) -> String {
+    if price >= 100.0 {
+        format!("{:.2}", price)
+    } else if price >= 1.0 {
+        format!("{:.4}", price)
+    } else {
+        format!("{:.6}", price)
+    }
+}
+
+fn fmt_opt(value: Option<f64>) -> String {
+    value.map(fmt_price).unwrap_or_else(|| "n/a".to_string())
+}
+
+fn fmt_time(time_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(time_ms)
+        .map(|t| t.format("%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| time_ms.to_string())
+}
+
+// =====================
+// Live lookup
+// =====================
+
+/// Binance pair for a user-facing asset name: "BTC" -> "BTCUSDT".
+pub fn symbol_for_asset(asset: &str) -> String {
+    let asset = asset.trim().to_uppercase();
+    if asset.ends_with("USDT") {
+        asset
+    } else {
+        format!("{}USDT", asset)
+    }
+}
+
+/// Snapshot of `asset` from the running market data service, on its first
+/// configured interval. `None` when the service is not running or has no
+/// candles for the asset yet.
+pub async fn snapshot_for_asset(asset: &str) -> Option<MarketSnapshot> {
+    let market = market_data()?;
+    let symbol = symbol_for_asset(asset);
+    let interval = market.config().intervals.first()?.clone();
+    let candles = market.candles(&symbol, &interval).await;
+    MarketSnapshot::build(&symbol, &interval, &candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: i64 = 60_000;
+
+    fn candle(index: usize, high: f64, low: f64) -> Candle {
+        Candle {
+            time: index as i64 * MINUTE_MS,
+            open: (high + low) / 2.0,
+            high,
+            low,
+            close: (high + low) / 2.0,
+            volume: 1.0,
+        }
+    }
+
+    /// Candles with the given highs, each one unit tall.
+    fn from_highs(highs: &[f64]) -> Vec<Candle> {
+        highs.iter().enumerate().map(|(i, h)| candle(i, *h, h - 1.0)).collect()
+    }
+
+    /// Triangle wave with period 8: highs peak at `i % 8 == 4`, lows bottom
+    /// out at `i % 8 == 0`.
+    fn zigzag(len: usize) -> Vec<Candle> {
+        (0..len)
+            .map(|i| {
+                let high = 10.0 - ((i % 8) as f64 - 4.0).abs();
+                candle(i, high, high - 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_a_swing_high_and_low() {
+        let highs = from_highs(&[1.0, 2.0, 3.0, 9.0, 3.0, 2.0, 1.0]);
+        assert_eq!(
+            detect_swings(&highs, 3),
+            vec![SwingPoint {
+                kind: SwingKind::High,
+                time: 3 * MINUTE_MS,
+                price: 9.0
+            }]
+        );
+
+        let lows = from_highs(&[5.0, 4.0, 3.0, 1.0, 3.0, 4.0, 5.0]);
+        assert_eq!(
+            detect_swings(&lows, 3),
+            vec![SwingPoint {
+                kind: SwingKind::Low,
+                time: 3 * MINUTE_MS,
+                price: 0.0
+            }]
+        );
+    }
+
+    #[test]
+    fn flat_extremes_are_reported_once_at_the_first_candle() {
+        let top = from_highs(&[1.0, 2.0, 3.0, 9.0, 9.0, 2.0, 1.0, 0.0]);
+        let swings = detect_swings(&top, 3);
+        assert_eq!(swings.len(), 1);
+        assert_eq!((swings[0].kind, swings[0].time), (SwingKind::High, 3 * MINUTE_MS));
+
+        let bottom = from_highs(&[5.0, 4.0, 3.0, 1.0, 1.0, 4.0, 5.0, 6.0]);
+        let swings = detect_swings(&bottom, 3);
+        assert_eq!(swings.len(), 1);
+        assert_eq!((swings[0].kind, swings[0].time), (SwingKind::Low, 3 * MINUTE_MS));
+    }
+
+    #[test]
+    fn too_few_candles_have_no_swings() {
+        let peak = from_highs(&[1.0, 2.0, 9.0, 2.0, 1.0, 0.0]);
+        assert!(detect_swings(&peak, 3).is_empty());
+        assert!(detect_swings(&[], 3).is_empty());
+        assert!(detect_swings(&from_highs(&[1.0, 9.0, 1.0]), 0).is_empty());
+        // The same peak is found once it has enough neighbours
+        assert_eq!(detect_swings(&peak, 2).len(), 1);
+    }
+
+    #[test]
+    fn build_keeps_the_latest_swings() {
+        let candles = zigzag(80);
+        let all = detect_swings(&candles, SWING_STRENGTH);
+        assert!(all.len() > 2 * MAX_SWINGS);
+
+        let snapshot = MarketSnapshot::build("SOLUSDT", "1m", &candles).unwrap();
+        assert_eq!(snapshot.swings.len(), 2 * MAX_SWINGS);
+        assert!(snapshot.swings.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        for kind in [SwingKind::High, SwingKind::Low] {
+            let latest: Vec<i64> = all
+                .iter()
+                .filter(|s| s.kind == kind)
+                .rev()
+                .take(MAX_SWINGS)
+                .map(|s| s.time)
+                .collect();
+            let kept: Vec<i64> = snapshot
+                .swings
+                .iter()
+                .filter(|s| s.kind == kind)
+                .rev()
+                .map(|s| s.time)
+                .collect();
+            assert_eq!(kept, latest);
+        }
+
+        assert_eq!(snapshot.candle_count, 80);
+        assert_eq!(snapshot.recent.len(), PROMPT_CANDLES);
+        assert_eq!((snapshot.window_high, snapshot.window_low), (10.0, 5.0));
+        assert_eq!(snapshot.last_price, candles[79].close);
+    }
+
+    #[test]
+    fn build_needs_candles() {
+        assert!(MarketSnapshot::build("SOLUSDT", "1m", &[]).is_none());
+    }
+
+    #[test]
+    fn prompt_quotes_the_computed_indicators() {
+        let snapshot = MarketSnapshot::build("SOLUSDT", "1m", &zigzag(80)).unwrap();
+        let prompt = snapshot.to_prompt();
+
+        let rsi = snapshot.indicators.rsi_14.unwrap();
+        assert!(prompt.contains(&format!("- RSI(14): {:.1}", rsi)));
+        let macd = snapshot.indicators.macd.unwrap();
+        assert!(prompt.contains(&format!(
+            "- MACD(12,26,9): macd {:.4}, signal {:.4}, histogram {:.4}",
+            macd.macd, macd.signal, macd.histogram
+        )));
+        assert!(prompt.starts_with("Market snapshot (SOLUSDT 1m, 80 candles, times UTC):"));
+        assert!(prompt.contains("high 10.0000 @ "));
+        assert!(prompt.contains("low 5.0000 @ "));
+    }
+
+    #[test]
+    fn short_history_prompt_says_n_a() {
+        let snapshot = MarketSnapshot::build("SOLUSDT", "1m", &zigzag(5)).unwrap();
+        let prompt = snapshot.to_prompt();
+
+        for line in [
+            "- SMA(20): n/a",
+            "- EMA(50): n/a",
+            "- RSI(14): n/a",
+            "- MACD(12,26,9): n/a",
+            "- Bollinger(20,2): n/a",
+            "- Ichimoku(9,26,52): n/a",
+            "Swing points: none detected",
+        ] {
+            assert!(prompt.contains(line), "missing {:?} in\n{}", line, prompt);
+        }
+    }
+}
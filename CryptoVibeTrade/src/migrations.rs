@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+
+/// One numbered schema change. The SQL lives in `migrations/` as
+/// `NNNN_name.up.sql` / `NNNN_name.down.sql` and is embedded at compile
+/// time, so the binary can migrate a database without the source tree.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+macro_rules! migration {
+    ($version:expr, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!("../migrations/", $name, ".up.sql")),
+            down: include_str!(concat!("../migrations/", $name, ".down.sql")),
+        }
+    };
+}
+
+/// Every migration, in the order it must be applied. Add new files to
+/// `migrations/` and append them here with the next version number.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(1, "0001_initial_schema"),
+    migration!(2, "0002_alert_rules"),
+    migration!(3, "0003_boards"),
+    migration!(4, "0004_board_charts"),
+    migration!(5, "0005_wallet_auth"),
+    migration!(6, "0006_nft_collections"),
+    migration!(7, "0007_nft_entitlements"),
+    migration!(8, "0008_board_shares"),
+];
+
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+pub struct Migrator<'a> {
+    pool: &'a PgPool,
+    migrations: &'a [Migration],
+}
+
+impl<'a> Migrator<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Migrator {
+            pool,
+            migrations: MIGRATIONS,
+        }
+    }
+
+    async fn ensure_version_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+            )
+            "#,
+        )
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn applied(&self) -> Result<Vec<AppliedMigration>> {
+        self.ensure_version_table().await?;
+
+        let rows = sqlx::query(
+            "SELECT version, name, applied_at FROM schema_migrations ORDER BY version",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(AppliedMigration {
+                    version: row.try_get("version")?,
+                    name: row.try_get("name")?,
+                    applied_at: row.try_get("applied_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn pending(&self) -> Result<Vec<Migration>> {
+        let applied = self.applied().await?;
+        Ok(self
+            .migrations
+            .iter()
+            .filter(|m| !applied.iter().any(|a| a.version == m.version))
+            .copied()
+            .collect())
+    }
+
+    /// Apply pending migrations in version order, each in its own
+    /// transaction, up to and including `target` (all when `None`).
+    /// Returns the migrations that were applied.
+    pub async fn up(&self, target: Option<i64>) -> Result<Vec<Migration>> {
+        let mut done = Vec::new();
+
+        for migration in self.pending().await? {
+            if target.is_some_and(|t| migration.version > t) {
+                break;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            // Serialize concurrent migrators (e.g. two app instances booting)
+            sqlx::query("SELECT pg_advisory_xact_lock(7243520013)")
+                .execute(&mut *tx)
+                .await?;
+
+            let already = sqlx::query("SELECT 1 FROM schema_migrations WHERE version = $1")
+                .bind(migration.version)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if already.is_some() {
+                tx.rollback().await?;
+                continue;
+            }
+
+            sqlx::raw_sql(migration.up)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow!("migration {} failed: {}", migration.name, e))?;
+            sqlx::query("INSERT INTO schema_migrations (version, name) VALUES ($1, $2)")
+                .bind(migration.version)
+                .bind(migration.name)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            tracing::info!("Applied migration {}", migration.name);
+            done.push(migration);
+        }
+
+        Ok(done)
+    }
+
+    /// Roll back the `steps` most recently applied migrations, newest
+    /// first, each in its own transaction.
+    pub async fn down(&self, steps: usize) -> Result<Vec<Migration>> {
+        let mut applied = self.applied().await?;
+        applied.reverse();
+
+        let mut done = Vec::new();
+        for record in applied.into_iter().take(steps) {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|m| m.version == record.version)
+                .copied()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "migration {} ({}) is applied but unknown to this build",
+                        record.version,
+                        record.name
+                    )
+                })?;
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("SELECT pg_advisory_xact_lock(7243520013)")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::raw_sql(migration.down)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow!("rollback of {} failed: {}", migration.name, e))?;
+            sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+                .bind(migration.version)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            tracing::info!("Rolled back migration {}", migration.name);
+            done.push(migration);
+        }
+
+        Ok(done)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+    use sqlx::Executor;
+
+    /// Connect to a fresh database on the server at `TEST_DATABASE_URL`
+    /// (e.g. `postgresql://postgres@localhost/postgres`). Returns `None`,
+    /// skipping the test, when the variable is unset. Drop it with
+    /// `drop_scratch_database` when done.
+    pub(crate) async fn scratch_database() -> Option<(PgPool, PgPool, String)> {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL not set, skipping");
+            return None;
+        };
+        let admin = PgPoolOptions::new().max_connections(1).connect(&url).await.unwrap();
+        let name = format!("cvt_test_{}", uuid::Uuid::new_v4().simple());
+        admin.execute(format!("CREATE DATABASE {}", name).as_str()).await.unwrap();
+
+        let (base, _) = url.rsplit_once('/').unwrap();
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&format!("{}/{}", base, name))
+            .await
+            .unwrap();
+        Some((admin, pool, name))
+    }
+
+    pub(crate) async fn drop_scratch_database(admin: PgPool, pool: PgPool, name: String) {
+        pool.close().await;
+        // A closed connection's backend can still be exiting
+        admin.execute(format!("DROP DATABASE {} WITH (FORCE)", name).as_str()).await.unwrap();
+    }
+
+    async fn table_count(pool: &PgPool) -> i64 {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM information_schema.tables
+             WHERE table_schema = 'public' AND table_name <> 'schema_migrations'",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn versions_are_sequential() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1);
+            assert!(migration.name.starts_with(&format!("{:04}_", migration.version)));
+        }
+    }
+
+    #[tokio::test]
+    async fn up_down_up_on_fresh_database() {
+        let Some((admin, pool, name)) = scratch_database().await else {
+            return;
+        };
+        let migrator = Migrator::new(&pool);
+
+        let applied = migrator.up(None).await.unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert!(migrator.pending().await.unwrap().is_empty());
+        assert!(table_count(&pool).await > 0);
+
+        let rolled_back = migrator.down(MIGRATIONS.len()).await.unwrap();
+        assert_eq!(rolled_back.len(), MIGRATIONS.len());
+        assert!(migrator.applied().await.unwrap().is_empty());
+        assert_eq!(table_count(&pool).await, 0);
+
+        let reapplied = migrator.up(None).await.unwrap();
+        assert_eq!(reapplied.len(), MIGRATIONS.len());
+        assert!(migrator.up(None).await.unwrap().is_empty());
+
+        drop_scratch_database(admin, pool, name).await;
+    }
+}
@@ -0,0 +1,749 @@
+use crate::database::Database;
+use crate::solana_rpc::SolanaRpc;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Metaplex Token Metadata program; owns every metadata account.
+pub const TOKEN_METADATA_PROGRAM: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// SPL Token and Token-2022, the programs an NFT's token account can belong to.
+pub const TOKEN_PROGRAMS: [Pubkey; 2] = [
+    pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+    pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"),
+];
+
+/// `getMultipleAccounts` accepts at most this many keys per call.
+const MULTIPLE_ACCOUNTS_LIMIT: usize = 100;
+
+/// Longest collection label accepted (matches the column size).
+pub const MAX_COLLECTION_LABEL: usize = 100;
+
+/// How long a verified entitlement grants access without a fresh check.
+pub const ENTITLEMENT_HOURS: i64 = 24;
+
+/// Entitlements older than this are re-verified by the background task.
+const RECHECK_AFTER_HOURS: i64 = 6;
+
+/// How often the background task looks for entitlements to re-verify.
+const RECHECK_EVERY: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+// =====================
+// Allow-list
+// =====================
+
+/// Features an allow-listed collection can unlock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatedFeature {
+    /// Dragrok risk analysis (`grok_analyze`)
+    GrokAnalysis,
+}
+
+impl GatedFeature {
+    pub const ALL: [GatedFeature; 1] = [GatedFeature::GrokAnalysis];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GatedFeature::GrokAnalysis => "Grok analysis",
+        }
+    }
+}
+
+/// A verified Metaplex collection whose holders get `features`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NftCollection {
+    /// Collection NFT mint address
+    pub address: String,
+    pub label: String,
+    pub features: Vec<GatedFeature>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Allow-list entry as submitted by an admin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewNftCollection {
+    pub address: String,
+    pub label: String,
+    pub features: Vec<GatedFeature>,
+}
+
+impl NewNftCollection {
+    /// Canonical address, trimmed label and de-duplicated features.
+    pub fn validate(&self) -> Result<NewNftCollection> {
+        let address = Pubkey::from_str(self.address.trim())
+            .map_err(|_| anyhow!("invalid collection address"))?
+            .to_string();
+        let label = self.label.trim();
+        if label.is_empty() || label.chars().count() > MAX_COLLECTION_LABEL {
+            return Err(anyhow!("label must be 1 to {} characters", MAX_COLLECTION_LABEL));
+        }
+        let mut features = Vec::new();
+        for feature in &self.features {
+            if !features.contains(feature) {
+                features.push(*feature);
+            }
+        }
+        if features.is_empty() {
+            return Err(anyhow!("choose at least one feature"));
+        }
+        Ok(NewNftCollection {
+            address,
+            label: label.to_string(),
+            features,
+        })
+    }
+}
+
+/// What a wallet's NFTs unlock: the matching collections' labels and the
+/// union of their features.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NftAccess {
+    pub collections: Vec<String>,
+    pub features: Vec<GatedFeature>,
+}
+
+impl NftAccess {
+    pub fn grants(&self, feature: GatedFeature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+/// Match held NFTs against the allow-list. Only metadata whose collection
+/// is marked verified counts; anyone can claim an unverified collection.
+pub fn grant_access(nfts: &[NftMetadata], allow_list: &[NftCollection]) -> NftAccess {
+    let mut access = NftAccess::default();
+    for collection in allow_list {
+        let held = nfts.iter().any(|nft| {
+            nft.collection
+                .as_ref()
+                .is_some_and(|c| c.verified && c.key.to_string() == collection.address)
+        });
+        if !held {
+            continue;
+        }
+        access.collections.push(collection.label.clone());
+        for feature in &collection.features {
+            if !access.features.contains(feature) {
+                access.features.push(*feature);
+            }
+        }
+    }
+    access
+}
+
+/// Stored result of a successful verification for one of a user's wallets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NftEntitlement {
+    pub user_id: Uuid,
+    pub wallet: String,
+    pub collections: Vec<String>,
+    pub features: Vec<GatedFeature>,
+    pub verified_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// =====================
+// Token accounts
+// =====================
+
+/// Mint, raw amount and decimals of one token account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenHolding {
+    pub mint: String,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Read a `jsonParsed` token account, i.e.
+/// `{"parsed":{"info":{"mint":..,"tokenAmount":{"amount":"1","decimals":0,..}}}}`.
+pub fn token_holding(data: &serde_json::Value) -> Option<TokenHolding> {
+    let info = data.get("parsed")?.get("info")?;
+    let mint = info.get("mint")?.as_str()?.to_string();
+    let token_amount = info.get("tokenAmount")?;
+    let amount = token_amount.get("amount")?.as_str()?.parse().ok()?;
+    let decimals = token_amount.get("decimals")?.as_u64()?.try_into().ok()?;
+    Some(TokenHolding {
+        mint,
+        amount,
+        decimals,
+    })
+}
+
+/// Every non-empty token account of `wallet`, across both token programs.
+pub async fn token_holdings(rpc: &SolanaRpc, wallet: &Pubkey) -> Result<Vec<TokenHolding>> {
+    let mut holdings = Vec::new();
+    for program in TOKEN_PROGRAMS {
+        let owner = *wallet;
+        let accounts = rpc
+            .call("getTokenAccountsByOwner", |client| async move {
+                client
+                    .get_token_accounts_by_owner(&owner, TokenAccountsFilter::ProgramId(program))
+                    .await
+            })
+            .await?;
+        for account in accounts {
+            let data = serde_json::to_value(&account.account.data)?;
+            match token_holding(&data) {
+                Some(holding) if holding.amount > 0 => holdings.push(holding),
+                _ => {}
+            }
+        }
+    }
+    Ok(holdings)
+}
+
+// =====================
+// Metaplex metadata
+// =====================
+
+/// `Key::MetadataV1`, the first byte of a metadata account.
+const METADATA_V1: u8 = 4;
+
+/// Size of a borsh-encoded `Creator` (address, verified, share).
+const CREATOR_LEN: usize = 32 + 1 + 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionRef {
+    pub key: Pubkey,
+    pub verified: bool,
+}
+
+/// The parts of a Metaplex metadata account gating needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NftMetadata {
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub collection: Option<CollectionRef>,
+}
+
+/// Metadata PDA for `mint`: seeds `["metadata", program, mint]`.
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM,
+    )
+    .0
+}
+
+/// Borsh reader over account data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+        let end = end.ok_or_else(|| anyhow!("metadata truncated at byte {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(anyhow!("invalid bool {}", other)),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        Ok(Pubkey::try_from(self.take(32)?)?)
+    }
+
+    /// Metaplex pads names and symbols with NUL bytes.
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(len)?;
+        Ok(String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string())
+    }
+
+    /// Borsh `Option` tag. Older accounts end before the newer optional
+    /// fields, which reads as `None`.
+    fn option(&mut self) -> Result<bool> {
+        if self.pos >= self.data.len() {
+            return Ok(false);
+        }
+        self.bool()
+    }
+}
+
+/// Decode a `MetadataV1` account far enough to reach its collection.
+pub fn parse_metadata(data: &[u8]) -> Result<NftMetadata> {
+    let mut reader = Reader { data, pos: 0 };
+    let key = reader.u8()?;
+    if key != METADATA_V1 {
+        return Err(anyhow!("not a metadata account (key {})", key));
+    }
+    let _update_authority = reader.pubkey()?;
+    let mint = reader.pubkey()?;
+    let name = reader.string()?;
+    let symbol = reader.string()?;
+    let _uri = reader.string()?;
+    let _seller_fee_basis_points = reader.take(2)?;
+    if reader.option()? {
+        let creators = reader.u32()? as usize;
+        reader.take(creators.checked_mul(CREATOR_LEN).ok_or_else(|| anyhow!("creator count overflow"))?)?;
+    }
+    let _primary_sale_happened = reader.bool()?;
+    let _is_mutable = reader.bool()?;
+    if reader.option()? {
+        let _edition_nonce = reader.u8()?;
+    }
+    if reader.option()? {
+        let _token_standard = reader.u8()?;
+    }
+    let collection = if reader.option()? {
+        let verified = reader.bool()?;
+        let key = reader.pubkey()?;
+        Some(CollectionRef { key, verified })
+    } else {
+        None
+    };
+
+    Ok(NftMetadata {
+        mint,
+        name,
+        symbol,
+        collection,
+    })
+}
+
+// =====================
+// RPC lookup
+// =====================
+
+/// Check which allow-listed collections `wallet` holds a verified NFT
+/// from. Any RPC failure is returned as an error so callers fail closed.
+pub async fn wallet_nft_access(
+    rpc: &SolanaRpc,
+    wallet: &Pubkey,
+    allow_list: &[NftCollection],
+) -> Result<NftAccess> {
+    if allow_list.is_empty() {
+        return Ok(NftAccess::default());
+    }
+
+    // An NFT is a zero-decimal token; fungible balances cannot be one
+    let mints: Vec<Pubkey> = token_holdings(rpc, wallet)
+        .await?
+        .iter()
+        .filter(|holding| holding.decimals == 0)
+        .filter_map(|holding| Pubkey::from_str(&holding.mint).ok())
+        .collect();
+
+    let metadata_keys: Vec<Pubkey> = mints.iter().map(metadata_address).collect();
+    let mut nfts = Vec::new();
+    for chunk in metadata_keys.chunks(MULTIPLE_ACCOUNTS_LIMIT) {
+        let accounts = rpc
+            .call("getMultipleAccounts", |client| {
+                let keys = chunk.to_vec();
+                async move { client.get_multiple_accounts(&keys).await }
+            })
+            .await?;
+        for account in accounts.into_iter().flatten() {
+            if account.owner != TOKEN_METADATA_PROGRAM {
+                continue;
+            }
+            match parse_metadata(&account.data) {
+                Ok(metadata) => nfts.push(metadata),
+                Err(e) => tracing::debug!("Skipping unreadable NFT metadata: {}", e),
+            }
+        }
+    }
+
+    // Metadata names the mint it describes; ignore any that do not match a held mint
+    nfts.retain(|nft| mints.contains(&nft.mint));
+    Ok(grant_access(&nfts, allow_list))
+}
+
+// =====================
+// Entitlements
+// =====================
+
+/// Verify `wallet` and store the outcome: a fresh entitlement when it
+/// holds an allow-listed NFT, none when it does not. RPC errors leave the
+/// stored entitlement alone so it lapses at its expiry.
+pub async fn refresh_entitlement(
+    db: &Database,
+    rpc: &SolanaRpc,
+    user_id: &Uuid,
+    wallet: &Pubkey,
+    allow_list: &[NftCollection],
+) -> Result<NftAccess> {
+    let access = wallet_nft_access(rpc, wallet, allow_list).await?;
+    let wallet = wallet.to_string();
+    if access.features.is_empty() {
+        db.delete_nft_entitlement(user_id, &wallet).await?;
+    } else {
+        let expires_at = Utc::now() + Duration::hours(ENTITLEMENT_HOURS);
+        db.save_nft_entitlement(user_id, &wallet, &access, expires_at).await?;
+    }
+    Ok(access)
+}
+
+async fn recheck_entitlements(db: &Database, rpc: &SolanaRpc) -> Result<()> {
+    let stale = db
+        .stale_nft_entitlements(Utc::now() - Duration::hours(RECHECK_AFTER_HOURS))
+        .await?;
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    let allow_list = db.list_nft_collections().await?;
+    for entitlement in stale {
+        let Ok(wallet) = Pubkey::from_str(&entitlement.wallet) else {
+            continue;
+        };
+        if let Err(e) = refresh_entitlement(db, rpc, &entitlement.user_id, &wallet, &allow_list).await {
+            tracing::warn!("Re-verifying NFT entitlement for {} failed: {}", entitlement.wallet, e);
+        }
+    }
+    Ok(())
+}
+
+/// Periodically re-verify stored entitlements so a sold NFT stops granting
+/// access before the entitlement would expire on its own.
+pub fn start_entitlement_refresher(db: Arc<Database>, rpc: Arc<SolanaRpc>) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(RECHECK_EVERY);
+        loop {
+            tick.tick().await;
+            if let Err(e) = recheck_entitlements(&db, &rpc).await {
+                tracing::warn!("NFT entitlement re-check failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana_rpc::tests::{stub_endpoint, test_rpc, with_context, Reply};
+    use axum::http::StatusCode;
+    use base64::Engine;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// Borsh string the way Metaplex stores it: padded with NULs to `len`.
+    fn padded(out: &mut Vec<u8>, value: &str, len: usize) {
+        out.extend_from_slice(&(len as u32).to_le_bytes());
+        out.extend_from_slice(value.as_bytes());
+        out.resize(out.len() + len - value.len(), 0);
+    }
+
+    /// `MetadataV1` up to `is_mutable`, which is where accounts created
+    /// before the optional fields existed end.
+    fn legacy_metadata(mint: &Pubkey, creators: Option<u32>) -> Vec<u8> {
+        let mut out = vec![METADATA_V1];
+        out.extend_from_slice(Pubkey::new_unique().as_ref());
+        out.extend_from_slice(mint.as_ref());
+        padded(&mut out, "Dragon #7", 32);
+        padded(&mut out, "DRGN", 10);
+        padded(&mut out, "https://example.com/7.json", 200);
+        out.extend_from_slice(&500u16.to_le_bytes());
+        match creators {
+            Some(count) => {
+                out.push(1);
+                out.extend_from_slice(&count.to_le_bytes());
+                for _ in 0..count {
+                    out.extend_from_slice(Pubkey::new_unique().as_ref());
+                    out.extend_from_slice(&[1, 100]);
+                }
+            }
+            None => out.push(0),
+        }
+        out.extend_from_slice(&[1, 1]);
+        out
+    }
+
+    /// Current `MetadataV1` with edition nonce, token standard and an
+    /// optional collection.
+    fn metadata(mint: &Pubkey, collection: Option<(&Pubkey, bool)>) -> Vec<u8> {
+        let mut out = legacy_metadata(mint, Some(2));
+        out.extend_from_slice(&[1, 255]);
+        out.extend_from_slice(&[1, 0]);
+        match collection {
+            Some((key, verified)) => {
+                out.extend_from_slice(&[1, verified as u8]);
+                out.extend_from_slice(key.as_ref());
+            }
+            None => out.push(0),
+        }
+        // Uses, collection details and programmable config follow
+        out.extend_from_slice(&[0, 0, 0]);
+        out
+    }
+
+    fn nft(collection: &Pubkey, verified: bool) -> NftMetadata {
+        NftMetadata {
+            mint: Pubkey::new_unique(),
+            name: "Dragon #7".to_string(),
+            symbol: "DRGN".to_string(),
+            collection: Some(CollectionRef {
+                key: *collection,
+                verified,
+            }),
+        }
+    }
+
+    fn allow(address: &Pubkey, label: &str) -> NftCollection {
+        NftCollection {
+            address: address.to_string(),
+            label: label.to_string(),
+            features: vec![GatedFeature::GrokAnalysis],
+            created_at: Utc::now(),
+        }
+    }
+
+    fn token_account(mint: &Pubkey, amount: u64, decimals: u8) -> Value {
+        json!({
+            "pubkey": Pubkey::new_unique().to_string(),
+            "account": {
+                "data": {
+                    "program": "spl-token",
+                    "parsed": {
+                        "info": {
+                            "isNative": false,
+                            "mint": mint.to_string(),
+                            "owner": Pubkey::new_unique().to_string(),
+                            "state": "initialized",
+                            "tokenAmount": {
+                                "amount": amount.to_string(),
+                                "decimals": decimals,
+                                "uiAmountString": amount.to_string(),
+                            },
+                        },
+                        "type": "account",
+                    },
+                    "space": 165,
+                },
+                "executable": false,
+                "lamports": 2039280,
+                "owner": TOKEN_PROGRAMS[0].to_string(),
+                "rentEpoch": 0,
+                "space": 165,
+            },
+        })
+    }
+
+    fn metadata_account(data: &[u8]) -> Value {
+        json!({
+            "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+            "executable": false,
+            "lamports": 5616720,
+            "owner": TOKEN_METADATA_PROGRAM.to_string(),
+            "rentEpoch": 0,
+            "space": data.len(),
+        })
+    }
+
+    #[test]
+    fn parses_current_metadata() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+
+        let parsed = parse_metadata(&metadata(&mint, Some((&collection, true)))).unwrap();
+        assert_eq!(parsed.mint, mint);
+        assert_eq!(parsed.name, "Dragon #7");
+        assert_eq!(parsed.symbol, "DRGN");
+        assert_eq!(
+            parsed.collection,
+            Some(CollectionRef {
+                key: collection,
+                verified: true
+            })
+        );
+
+        let unverified = parse_metadata(&metadata(&mint, Some((&collection, false)))).unwrap();
+        assert!(!unverified.collection.unwrap().verified);
+        assert_eq!(parse_metadata(&metadata(&mint, None)).unwrap().collection, None);
+    }
+
+    #[test]
+    fn legacy_metadata_has_no_collection() {
+        let mint = Pubkey::new_unique();
+        for creators in [None, Some(0), Some(3)] {
+            let parsed = parse_metadata(&legacy_metadata(&mint, creators)).unwrap();
+            assert_eq!(parsed.mint, mint);
+            assert_eq!(parsed.collection, None);
+        }
+    }
+
+    #[test]
+    fn rejects_other_and_malformed_accounts() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let data = metadata(&mint, Some((&collection, true)));
+
+        // MasterEditionV2 shares the program but not the layout
+        let mut edition = data.clone();
+        edition[0] = 6;
+        assert!(parse_metadata(&edition).is_err());
+
+        assert!(parse_metadata(&[]).is_err());
+        assert!(parse_metadata(&data[..100]).is_err());
+        // Cut inside the collection key
+        assert!(parse_metadata(&data[..data.len() - 10]).is_err());
+
+        // A creator count that runs past the end of the account
+        let mut creators = legacy_metadata(&mint, Some(1));
+        let count_at = creators.len() - 2 - CREATOR_LEN - 4;
+        creators[count_at..count_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_metadata(&creators).is_err());
+
+        // The collection `verified` flag must be a borsh bool
+        let mut flag = data.clone();
+        let verified_at = flag.len() - 3 - 32 - 1;
+        flag[verified_at] = 2;
+        assert!(parse_metadata(&flag).is_err());
+    }
+
+    #[test]
+    fn only_verified_collections_grant_access() {
+        let dragons = Pubkey::new_unique();
+        let knights = Pubkey::new_unique();
+        let allow_list = vec![allow(&dragons, "Dragons"), allow(&knights, "Knights")];
+
+        let access = grant_access(&[nft(&dragons, true)], &allow_list);
+        assert_eq!(access.collections, vec!["Dragons".to_string()]);
+        assert!(access.grants(GatedFeature::GrokAnalysis));
+
+        // Anyone can mint into a collection without the collection authority's signature
+        assert_eq!(grant_access(&[nft(&dragons, false)], &allow_list), NftAccess::default());
+        assert_eq!(
+            grant_access(&[nft(&Pubkey::new_unique(), true)], &allow_list),
+            NftAccess::default()
+        );
+
+        let mut no_collection = nft(&dragons, true);
+        no_collection.collection = None;
+        assert_eq!(grant_access(&[no_collection], &allow_list), NftAccess::default());
+
+        // Features are the de-duplicated union over every held collection
+        let both = grant_access(&[nft(&knights, true), nft(&dragons, true)], &allow_list);
+        assert_eq!(both.collections, vec!["Dragons".to_string(), "Knights".to_string()]);
+        assert_eq!(both.features, vec![GatedFeature::GrokAnalysis]);
+    }
+
+    #[test]
+    fn reads_parsed_token_accounts() {
+        let mint = Pubkey::new_unique();
+        let account = token_account(&mint, 1, 0);
+        assert_eq!(
+            token_holding(&account["account"]["data"]),
+            Some(TokenHolding {
+                mint: mint.to_string(),
+                amount: 1,
+                decimals: 0
+            })
+        );
+
+        let fungible = token_account(&mint, 1_500_000, 6);
+        assert_eq!(token_holding(&fungible["account"]["data"]).unwrap().decimals, 6);
+
+        assert_eq!(token_holding(&json!({ "parsed": { "info": {} } })), None);
+        assert_eq!(token_holding(&json!(["AAAA", "base64"])), None);
+    }
+
+    #[tokio::test]
+    async fn grants_only_for_zero_decimal_verified_holdings() {
+        let collection = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        let fungible_mint = Pubkey::new_unique();
+        let empty_mint = Pubkey::new_unique();
+
+        let metadata_by_key: Vec<(Pubkey, Vec<u8>)> = [nft_mint, fungible_mint, empty_mint]
+            .iter()
+            .map(|mint| (metadata_address(mint), metadata(mint, Some((&collection, true)))))
+            .collect();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = requested.clone();
+        let url = stub_endpoint(move |method, params| match method {
+            "getTokenAccountsByOwner" if params[1]["programId"] == TOKEN_PROGRAMS[0].to_string() => {
+                Reply::Result(with_context(json!([
+                    token_account(&nft_mint, 1, 0),
+                    token_account(&fungible_mint, 2_000_000, 6),
+                    token_account(&empty_mint, 0, 0),
+                ])))
+            }
+            "getTokenAccountsByOwner" => Reply::Result(with_context(json!([]))),
+            "getMultipleAccounts" => {
+                let keys: Vec<String> = serde_json::from_value(params[0].clone()).unwrap();
+                seen.lock().unwrap().extend(keys.iter().cloned());
+                let accounts: Vec<Value> = keys
+                    .iter()
+                    .map(|key| {
+                        metadata_by_key
+                            .iter()
+                            .find(|(address, _)| address.to_string() == *key)
+                            .map_or(Value::Null, |(_, data)| metadata_account(data))
+                    })
+                    .collect();
+                Reply::Result(with_context(json!(accounts)))
+            }
+            _ => Reply::Error(-32601),
+        })
+        .await;
+
+        let rpc = test_rpc(vec![url], 0);
+        let access = wallet_nft_access(&rpc, &Pubkey::new_unique(), &[allow(&collection, "Dragons")])
+            .await
+            .unwrap();
+        assert!(access.grants(GatedFeature::GrokAnalysis));
+
+        // Only the NFT's metadata is looked up; fungible and empty accounts are not NFTs
+        assert_eq!(*requested.lock().unwrap(), vec![metadata_address(&nft_mint).to_string()]);
+    }
+
+    #[tokio::test]
+    async fn rpc_failures_fail_closed() {
+        let collection = Pubkey::new_unique();
+        let allow_list = [allow(&collection, "Dragons")];
+        let wallet = Pubkey::new_unique();
+
+        // Nothing listening: a port that was free a moment ago
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let rpc = test_rpc(vec![closed], 1);
+        assert!(wallet_nft_access(&rpc, &wallet, &allow_list).await.is_err());
+
+        // Holdings load but the metadata lookup keeps failing
+        let nft_mint = Pubkey::new_unique();
+        let url = stub_endpoint(move |method, _| match method {
+            "getTokenAccountsByOwner" => {
+                Reply::Result(with_context(json!([token_account(&nft_mint, 1, 0)])))
+            }
+            _ => Reply::Status(StatusCode::SERVICE_UNAVAILABLE),
+        })
+        .await;
+        let rpc = test_rpc(vec![url], 1);
+        assert!(wallet_nft_access(&rpc, &wallet, &allow_list).await.is_err());
+
+        // An empty allow-list needs no lookup at all
+        assert_eq!(
+            wallet_nft_access(&rpc, &wallet, &[]).await.unwrap(),
+            NftAccess::default()
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use crate::market_data::{interval_ms, MarketData};
+use crate::nft_gating::token_holdings;
+use crate::solana_rpc::SolanaRpc;
+use crate::types::Candle;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// Native SOL has 9 decimals (lamports).
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Market pair used to price native SOL.
+const SOL_PAIR: &str = "SOLUSDT";
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+// =====================
+// Mint registry
+// =====================
+
+/// How a token gets its USD price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pricing {
+    /// Pegged to one US dollar
+    Stable,
+    /// Last price of a Binance USDT pair from the market data feed
+    Market(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MintInfo {
+    pub mint: &'static str,
+    pub symbol: &'static str,
+    pub decimals: u8,
+    pub pricing: Pricing,
+}
+
+/// Well-known mainnet mints. Tokens not listed here are shown by mint
+/// address and left unpriced.
+pub const MINT_REGISTRY: &[MintInfo] = &[
+    MintInfo {
+        mint: "So11111111111111111111111111111111111111112",
+        symbol: "wSOL",
+        decimals: 9,
+        pricing: Pricing::Market(SOL_PAIR),
+    },
+    MintInfo {
+        mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+        symbol: "USDC",
+        decimals: 6,
+        pricing: Pricing::Stable,
+    },
+    MintInfo {
+        mint: "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+        symbol: "USDT",
+        decimals: 6,
+        pricing: Pricing::Stable,
+    },
+    MintInfo {
+        mint: "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263",
+        symbol: "BONK",
+        decimals: 5,
+        pricing: Pricing::Market("BONKUSDT"),
+    },
+    MintInfo {
+        mint: "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R",
+        symbol: "RAY",
+        decimals: 6,
+        pricing: Pricing::Market("RAYUSDT"),
+    },
+];
+
+pub fn lookup_mint(mint: &str) -> Option<&'static MintInfo> {
+    MINT_REGISTRY.iter().find(|info| info.mint == mint)
+}
+
+// =====================
+// Valuation
+// =====================
+
+/// Raw on-chain balance of native SOL (`mint: None`) or one SPL mint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawBalance {
+    pub mint: Option<String>,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// USD price now and 24 hours ago, if the candles reach that far back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub price: f64,
+    pub price_24h_ago: Option<f64>,
+}
+
+impl Quote {
+    fn stable() -> Self {
+        Quote {
+            price: 1.0,
+            price_24h_ago: Some(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioHolding {
+    /// `None` for native SOL
+    pub mint: Option<String>,
+    pub symbol: String,
+    pub amount: f64,
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+    /// Share of the priced total, in percent
+    pub allocation_pct: Option<f64>,
+    pub change_24h_pct: Option<f64>,
+    /// Value change over 24h at the current balance
+    pub pnl_24h_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub wallet: String,
+    /// Priced holdings by value, then unpriced ones
+    pub holdings: Vec<PortfolioHolding>,
+    pub total_usd: f64,
+    /// `None` when no priced holding has a 24h reference price
+    pub pnl_24h_usd: Option<f64>,
+    pub pnl_24h_pct: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Balance in whole tokens.
+pub fn ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+/// Market pair whose quote prices `balance`, or `None` for stables and
+/// unknown mints.
+pub fn market_pair(balance: &RawBalance) -> Option<&'static str> {
+    match balance.mint.as_deref() {
+        None => Some(SOL_PAIR),
+        Some(mint) => match lookup_mint(mint)?.pricing {
+            Pricing::Market(pair) => Some(pair),
+            Pricing::Stable => None,
+        },
+    }
+}
+
+fn short_mint(mint: &str) -> String {
+    let head: String = mint.chars().take(4).collect();
+    format!("{}…", head)
+}
+
+/// Current price and the close at or before 24 hours before the last
+/// candle. Candles are time-ordered with open times in milliseconds.
+pub fn quote_from_candles(candles: &[Candle]) -> Option<Quote> {
+    let last = candles.last()?;
+    let target = last.time - DAY_MS;
+    let price_24h_ago = if candles.first()?.time <= target {
+        candles.iter().rev().find(|c| c.time <= target).map(|c| c.close)
+    } else {
+        None
+    };
+    Some(Quote {
+        price: last.close,
+        price_24h_ago,
+    })
+}
+
+/// Value `balances` with `quotes` (keyed by market pair) and work out
+/// allocation and 24h PnL. Zero balances are dropped.
+pub fn build_portfolio(
+    wallet: &str,
+    balances: &[RawBalance],
+    quotes: &HashMap<String, Quote>,
+    now: DateTime<Utc>,
+) -> Portfolio {
+    let mut holdings: Vec<PortfolioHolding> = balances
+        .iter()
+        .filter(|balance| balance.amount > 0)
+        .map(|balance| {
+            let info = balance.mint.as_deref().and_then(lookup_mint);
+            let symbol = match (&balance.mint, info) {
+                (None, _) => "SOL".to_string(),
+                (Some(_), Some(info)) => info.symbol.to_string(),
+                (Some(mint), None) => short_mint(mint),
+            };
+            let quote = match info.map(|info| info.pricing) {
+                Some(Pricing::Stable) => Some(Quote::stable()),
+                _ => market_pair(balance).and_then(|pair| quotes.get(pair).copied()),
+            };
+
+            let decimals = info.map_or(balance.decimals, |info| info.decimals);
+            let amount = ui_amount(balance.amount, decimals);
+            let value = quote.map(|q| amount * q.price);
+            let change = quote.and_then(|q| {
+                let before = q.price_24h_ago.filter(|p| *p > 0.0)?;
+                Some((q.price - before) / before * 100.0)
+            });
+            let pnl = quote.and_then(|q| q.price_24h_ago.map(|before| amount * (q.price - before)));
+
+            PortfolioHolding {
+                mint: balance.mint.clone(),
+                symbol,
+                amount,
+                price_usd: quote.map(|q| q.price),
+                value_usd: value,
+                allocation_pct: None,
+                change_24h_pct: change,
+                pnl_24h_usd: pnl,
+            }
+        })
+        .collect();
+
+    let total_usd: f64 = holdings.iter().filter_map(|h| h.value_usd).sum();
+    if total_usd > 0.0 {
+        for holding in &mut holdings {
+            holding.allocation_pct = holding.value_usd.map(|v| v / total_usd * 100.0);
+        }
+    }
+    holdings.sort_by(|a, b| {
+        let value = |h: &PortfolioHolding| h.value_usd.unwrap_or(f64::NEG_INFINITY);
+        value(b).total_cmp(&value(a))
+    });
+
+    // Only holdings with a 24h reference take part in the PnL
+    let (pnl_sum, value_before, counted) = holdings
+        .iter()
+        .filter_map(|h| Some((h.pnl_24h_usd?, h.value_usd?)))
+        .fold((0.0, 0.0, 0), |(pnl, before, n), (p, v)| (pnl + p, before + v - p, n + 1));
+    let pnl_24h_usd = (counted > 0).then_some(pnl_sum);
+    let pnl_24h_pct = pnl_24h_usd
+        .filter(|_| value_before > 0.0)
+        .map(|pnl| pnl / value_before * 100.0);
+
+    Portfolio {
+        wallet: wallet.to_string(),
+        holdings,
+        total_usd,
+        pnl_24h_usd,
+        pnl_24h_pct,
+        updated_at: now,
+    }
+}
+
+// =====================
+// Live lookup
+// =====================
+
+/// Quote for `pair` from the market data feed. The price comes from the
+/// finest configured interval; the 24h reference from any interval whose
+/// window covers a full day.
+pub async fn market_quote(market: &MarketData, pair: &str) -> Option<Quote> {
+    // Finest first; intervals that do not parse come last
+    let mut intervals: Vec<&String> = market.config().intervals.iter().collect();
+    intervals.sort_by_key(|interval| interval_ms(interval).unwrap_or(i64::MAX));
+
+    let mut quote: Option<Quote> = None;
+    for interval in intervals {
+        let Some(found) = quote_from_candles(&market.candles(pair, interval).await) else {
+            continue;
+        };
+        quote = Some(match quote {
+            None => found,
+            Some(existing) => Quote {
+                price: existing.price,
+                price_24h_ago: existing.price_24h_ago.or(found.price_24h_ago),
+            },
+        });
+    }
+    quote
+}
+
+/// SOL and SPL balances of `wallet`, valued with the market data feed.
+pub async fn wallet_portfolio(
+    rpc: &SolanaRpc,
+    market: Option<&MarketData>,
+    wallet: &Pubkey,
+) -> Result<Portfolio> {
+    let owner = *wallet;
+    let lamports = rpc
+        .call("getBalance", |client| async move { client.get_balance(&owner).await })
+        .await?;
+
+    let mut balances = vec![RawBalance {
+        mint: None,
+        amount: lamports,
+        decimals: SOL_DECIMALS,
+    }];
+    // A wallet can hold several accounts of the same mint
+    for holding in token_holdings(rpc, wallet).await? {
+        match balances.iter_mut().find(|b| b.mint.as_deref() == Some(holding.mint.as_str())) {
+            Some(existing) => existing.amount = existing.amount.saturating_add(holding.amount),
+            None => balances.push(RawBalance {
+                mint: Some(holding.mint),
+                amount: holding.amount,
+                decimals: holding.decimals,
+            }),
+        }
+    }
+
+    let mut quotes = HashMap::new();
+    if let Some(market) = market {
+        for pair in balances.iter().filter_map(market_pair) {
+            if quotes.contains_key(pair) {
+                continue;
+            }
+            if !market.config().symbols.iter().any(|symbol| symbol == pair) {
+                tracing::debug!("{} is not in MARKET_SYMBOLS; leaving it unpriced", pair);
+                continue;
+            }
+            if let Some(quote) = market_quote(market, pair).await {
+                quotes.insert(pair.to_string(), quote);
+            }
+        }
+    }
+
+    Ok(build_portfolio(&wallet.to_string(), &balances, &quotes, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 60 * 60 * 1000;
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const RAY: &str = "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * (1.0 + a.abs().max(b.abs()))
+    }
+
+    /// Candles at the given hours, closing at `price(hour)`.
+    fn candles(hours: &[i64], price: impl Fn(i64) -> f64) -> Vec<Candle> {
+        hours
+            .iter()
+            .map(|hour| {
+                let close = price(*hour);
+                Candle {
+                    time: hour * HOUR_MS,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 1.0,
+                }
+            })
+            .collect()
+    }
+
+    fn balance(mint: Option<&str>, amount: u64, decimals: u8) -> RawBalance {
+        RawBalance {
+            mint: mint.map(str::to_string),
+            amount,
+            decimals,
+        }
+    }
+
+    fn holding<'a>(portfolio: &'a Portfolio, symbol: &str) -> &'a PortfolioHolding {
+        portfolio.holdings.iter().find(|h| h.symbol == symbol).unwrap()
+    }
+
+    #[test]
+    fn quote_uses_the_close_a_day_before_the_last_candle() {
+        let hourly: Vec<i64> = (0..=30).collect();
+        let quote = quote_from_candles(&candles(&hourly, |h| 100.0 + h as f64)).unwrap();
+        assert_eq!(quote.price, 130.0);
+        assert_eq!(quote.price_24h_ago, Some(106.0));
+
+        // Exactly one day of history reaches the first candle
+        let quote = quote_from_candles(&candles(&[0, 12, 24], |h| h as f64)).unwrap();
+        assert_eq!(quote.price_24h_ago, Some(0.0));
+
+        // A gap falls back to the last close at or before the target
+        let quote = quote_from_candles(&candles(&[0, 5, 30], |h| h as f64)).unwrap();
+        assert_eq!(quote.price_24h_ago, Some(5.0));
+    }
+
+    #[test]
+    fn quote_without_a_full_day_has_no_reference() {
+        assert_eq!(quote_from_candles(&[]), None);
+
+        let hours: Vec<i64> = (0..=23).collect();
+        let quote = quote_from_candles(&candles(&hours, |h| 50.0 + h as f64)).unwrap();
+        assert_eq!(quote.price, 73.0);
+        assert_eq!(quote.price_24h_ago, None);
+    }
+
+    #[test]
+    fn values_sorts_and_sums_holdings() {
+        let unknown = "AbCdEfGhJkLmNpQrStUvWxYz1234567890abcdefghi";
+        let balances = vec![
+            balance(None, 2_500_000_000, SOL_DECIMALS),
+            balance(Some(USDC), 100_000_000, 6),
+            balance(Some(BONK), 100_000_000_000, 5),
+            balance(Some(unknown), 42, 0),
+            balance(Some(RAY), 0, 6),
+        ];
+        let quotes = HashMap::from([
+            (
+                "SOLUSDT".to_string(),
+                Quote {
+                    price: 200.0,
+                    price_24h_ago: Some(160.0),
+                },
+            ),
+            (
+                "BONKUSDT".to_string(),
+                Quote {
+                    price: 0.00002,
+                    price_24h_ago: None,
+                },
+            ),
+        ]);
+        let now = Utc::now();
+        let portfolio = build_portfolio("wallet", &balances, &quotes, now);
+
+        // Priced by value, unpriced last; the empty RAY account is dropped
+        let symbols: Vec<&str> = portfolio.holdings.iter().map(|h| h.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["SOL", "USDC", "BONK", "AbCd…"]);
+        assert!(close(portfolio.total_usd, 620.0));
+        assert_eq!(portfolio.updated_at, now);
+
+        let sol = holding(&portfolio, "SOL");
+        assert!(close(sol.amount, 2.5));
+        assert!(close(sol.value_usd.unwrap(), 500.0));
+        assert!(close(sol.allocation_pct.unwrap(), 500.0 / 620.0 * 100.0));
+        assert!(close(sol.change_24h_pct.unwrap(), 25.0));
+        assert!(close(sol.pnl_24h_usd.unwrap(), 100.0));
+
+        let usdc = holding(&portfolio, "USDC");
+        assert_eq!(usdc.price_usd, Some(1.0));
+        assert_eq!(usdc.pnl_24h_usd, Some(0.0));
+
+        let bonk = holding(&portfolio, "BONK");
+        assert!(close(bonk.amount, 1_000_000.0));
+        assert!(close(bonk.value_usd.unwrap(), 20.0));
+        assert_eq!((bonk.change_24h_pct, bonk.pnl_24h_usd), (None, None));
+
+        let unpriced = holding(&portfolio, "AbCd…");
+        assert_eq!(unpriced.amount, 42.0);
+        assert_eq!((unpriced.value_usd, unpriced.allocation_pct), (None, None));
+
+        // PnL covers SOL and USDC only: +100 on the 500 they were worth a day ago
+        assert!(close(portfolio.pnl_24h_usd.unwrap(), 100.0));
+        assert!(close(portfolio.pnl_24h_pct.unwrap(), 20.0));
+    }
+
+    #[tokio::test]
+    async fn market_quote_prices_from_the_finest_interval() {
+        use crate::market_data::{KlineUpdate, MarketDataConfig, SeriesKey};
+
+        let market = MarketData::new(MarketDataConfig {
+            symbols: vec!["SOLUSDT".to_string()],
+            // Listed coarse first; the order must not matter
+            intervals: vec!["1h".to_string(), "1m".to_string()],
+            history: 100,
+            ws_url: String::new(),
+            rest_url: String::new(),
+        });
+        // A day of hourly candles closing at 100 + hour, then a fresher minute candle
+        let hourly = candles(&(0..=30).collect::<Vec<_>>(), |hour| 100.0 + hour as f64);
+        let mut minute = candles(&[30], |_| 131.5);
+        minute[0].time += 59 * 60 * 1000;
+        let series = hourly.into_iter().map(|c| ("1h", c)).chain(minute.into_iter().map(|c| ("1m", c)));
+        for (interval, candle) in series {
+            let key = SeriesKey::new("SOLUSDT", interval);
+            market.ingest(KlineUpdate { key, candle, closed: true }).await;
+        }
+
+        let quote = market_quote(&market, "SOLUSDT").await.unwrap();
+        assert_eq!(quote.price, 131.5);
+        // Only the hourly series spans a day
+        assert_eq!(quote.price_24h_ago, Some(106.0));
+        assert!(market_quote(&market, "BTCUSDT").await.is_none());
+    }
+
+    #[test]
+    fn registry_decimals_win_over_reported_ones() {
+        let balances = [balance(Some(RAY), 3_000_000, 0)];
+        let portfolio = build_portfolio("wallet", &balances, &HashMap::new(), Utc::now());
+        assert!(close(portfolio.holdings[0].amount, 3.0));
+    }
+
+    #[test]
+    fn unpriced_portfolio_has_no_totals() {
+        let balances = vec![balance(None, 1_000_000_000, SOL_DECIMALS), balance(Some(BONK), 10, 5)];
+        let portfolio = build_portfolio("wallet", &balances, &HashMap::new(), Utc::now());
+
+        assert_eq!(portfolio.holdings.len(), 2);
+        assert_eq!(portfolio.total_usd, 0.0);
+        assert!(portfolio.holdings.iter().all(|h| h.value_usd.is_none() && h.allocation_pct.is_none()));
+        assert_eq!((portfolio.pnl_24h_usd, portfolio.pnl_24h_pct), (None, None));
+    }
+}
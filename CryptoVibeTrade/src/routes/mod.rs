@@ -1,8 +1,34 @@
 use leptos::*;
+use leptos_router::Redirect;
 use crate::components::landing::LandingPage;
 use crate::components::dashboard::DashboardPage;
 use crate::components::auth::{RegisterPage, LoginPage};
 use crate::components::whiteboard::Whiteboard;
+use crate::server::current_user;
+
+/// Renders `children` only for a logged-in user (an admin when `admin` is
+/// set) and redirects to the login page otherwise. The server-side page
+/// guard covers full page loads; this covers client-side navigation.
+#[component]
+fn RequireAuth(
+    #[prop(optional)]
+    admin: bool,
+    children: ChildrenFn,
+) -> impl IntoView {
+    let user = create_resource(|| (), |_| current_user());
+    let login_page = if admin { "/admin" } else { "/auth/login" };
+    // Suspense re-runs its children, so they must stay callable
+    let children = store_value(children);
+
+    view! {
+        <Suspense fallback=|| ()>
+            {move || user.get().map(|result| match result {
+                Ok(Some(info)) if info.is_admin || !admin => children.with_value(|children| children()).into_view(),
+                _ => view! { <Redirect path=login_page/> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
 
 #[component]
 pub fn LandingRoute() -> impl IntoView {
@@ -16,7 +42,11 @@ pub fn AdminRoute() -> impl IntoView {
 
 #[component]
 pub fn AdminDashboardRoute() -> impl IntoView {
-    view! { <DashboardPage/> }
+    view! {
+        <RequireAuth admin=true>
+            <DashboardPage/>
+        </RequireAuth>
+    }
 }
 
 #[component]
@@ -31,5 +61,9 @@ pub fn LoginRoute() -> impl IntoView {
 
 #[component]
 pub fn WhiteboardRoute() -> impl IntoView {
-    view! { <Whiteboard width=1920 height=1080 /> }
+    view! {
+        <RequireAuth>
+            <Whiteboard width=1920 height=1080 />
+        </RequireAuth>
+    }
 }
@@ -0,0 +1,250 @@
+use crate::database::User;
+use crate::nft_gating::GatedFeature;
+use crate::server::database;
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use leptos::ServerFnError;
+
+/// Name of the HttpOnly cookie carrying the login session token.
+pub const SESSION_COOKIE: &str = "cvt_session";
+
+/// How long a login session (and its cookie) stays valid.
+pub const SESSION_HOURS: i64 = 24;
+
+// =====================
+// Cookie helpers
+// =====================
+
+fn secure_attribute() -> &'static str {
+    match std::env::var("COOKIE_SECURE").as_deref() {
+        Ok("1") | Ok("true") => "; Secure",
+        _ => "",
+    }
+}
+
+/// `Set-Cookie` value storing `token` for `SESSION_HOURS`.
+pub fn session_cookie(token: &str) -> String {
+    format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}{}",
+        SESSION_COOKIE,
+        token,
+        SESSION_HOURS * 3600,
+        secure_attribute()
+    )
+}
+
+/// `Set-Cookie` value that removes the session cookie.
+pub fn clear_session_cookie() -> String {
+    format!(
+        "{}=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0{}",
+        SESSION_COOKIE,
+        secure_attribute()
+    )
+}
+
+/// Read the session token from the request's `Cookie` headers.
+pub fn token_from_headers(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, token)| token.to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Resolve the logged-in user for a request, if any.
+pub async fn user_from_headers(headers: &HeaderMap) -> Option<User> {
+    let token = token_from_headers(headers)?;
+    let db = database()?;
+    match db.validate_session(&token).await {
+        Ok(user) => user,
+        Err(e) => {
+            tracing::warn!("Session lookup failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether the user holds an unexpired NFT entitlement for `feature`.
+/// Lookup failures deny access.
+pub async fn user_has_feature(user: &User, feature: GatedFeature) -> bool {
+    let Some(db) = database() else {
+        return false;
+    };
+    match db.user_has_feature(&user.id, feature).await {
+        Ok(granted) => granted,
+        Err(e) => {
+            tracing::warn!("Entitlement lookup failed: {}", e);
+            false
+        }
+    }
+}
+
+// =====================
+// Axum extractors
+// =====================
+
+/// Extractor for any logged-in user. Rejects with 401 otherwise.
+pub struct CurrentUser(pub User);
+
+/// Extractor for logged-in admins. Rejects with 401 when anonymous and
+/// 403 for non-admin users.
+pub struct AdminUser(pub User);
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CurrentUser {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        user_from_headers(&parts.headers)
+            .await
+            .map(CurrentUser)
+            .ok_or((StatusCode::UNAUTHORIZED, "Login required"))
+    }
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AdminUser {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let CurrentUser(user) = CurrentUser::from_request_parts(parts, state).await?;
+        if user.is_admin {
+            Ok(AdminUser(user))
+        } else {
+            Err((StatusCode::FORBIDDEN, "Admin access required"))
+        }
+    }
+}
+
+// =====================
+// Server function guards
+// =====================
+
+/// Current user inside a `#[server]` function.
+pub async fn require_user() -> Result<User, ServerFnError> {
+    let headers = leptos_axum::extract::<HeaderMap>().await?;
+    user_from_headers(&headers)
+        .await
+        .ok_or_else(|| ServerFnError::new("Login required"))
+}
+
+/// Current admin inside a `#[server]` function.
+pub async fn require_admin() -> Result<User, ServerFnError> {
+    let user = require_user().await?;
+    if user.is_admin {
+        Ok(user)
+    } else {
+        Err(ServerFnError::new("Admin access required"))
+    }
+}
+
+/// Fails unless `user` holds an NFT entitlement for `feature`.
+pub async fn require_feature(user: &User, feature: GatedFeature) -> Result<(), ServerFnError> {
+    if user_has_feature(user, feature).await {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "NFT verification required for {}",
+            feature.label()
+        )))
+    }
+}
+
+// =====================
+// Page guard middleware
+// =====================
+
+/// Pages that need a session, and whether they are admin-only.
+const PROTECTED_PAGES: &[(&str, bool)] = &[("/admin/dashboard", true), ("/whiteboard", false)];
+
+/// Redirects anonymous (or non-admin) visitors away from protected pages
+/// before they are server-rendered.
+pub async fn guard_protected_pages(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let protected = PROTECTED_PAGES
+        .iter()
+        .find(|(prefix, _)| path == *prefix || path.starts_with(&format!("{}/", prefix)));
+
+    if let Some((_, admin_only)) = protected {
+        let login_page = if *admin_only { "/admin" } else { "/auth/login" };
+        match user_from_headers(request.headers()).await {
+            Some(user) if user.is_admin || !admin_only => {}
+            _ => return Redirect::to(login_page).into_response(),
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn cookies(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(header::COOKIE, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn finds_the_session_among_other_cookies() {
+        assert_eq!(
+            token_from_headers(&cookies(&["theme=dark; cvt_session=abc123; lang=en"])),
+            Some("abc123".to_string())
+        );
+        // Browsers may split cookies over several headers
+        assert_eq!(
+            token_from_headers(&cookies(&["theme=dark", "cvt_session=xyz"])),
+            Some("xyz".to_string())
+        );
+        // Only an exact name match counts
+        assert_eq!(token_from_headers(&cookies(&["old_cvt_session=abc; cvt_session_x=def"])), None);
+    }
+
+    #[test]
+    fn missing_or_empty_session_cookie_is_none() {
+        assert_eq!(token_from_headers(&HeaderMap::new()), None);
+        assert_eq!(token_from_headers(&cookies(&["theme=dark"])), None);
+        assert_eq!(token_from_headers(&cookies(&["cvt_session=; theme=dark"])), None);
+        assert_eq!(token_from_headers(&cookies(&["cvt_session"])), None);
+    }
+
+    #[test]
+    fn cookie_attributes() {
+        // One test owns COOKIE_SECURE so parallel tests cannot race on it
+        std::env::remove_var("COOKIE_SECURE");
+        let set = session_cookie("abc123");
+        assert!(set.starts_with("cvt_session=abc123;"));
+        assert!(set.contains("; HttpOnly"));
+        assert!(set.contains("; SameSite=Lax"));
+        assert!(set.contains("; Path=/"));
+        assert!(set.contains(&format!("; Max-Age={}", SESSION_HOURS * 3600)));
+        assert!(!set.contains("Secure"));
+
+        let clear = clear_session_cookie();
+        assert!(clear.starts_with("cvt_session=;"));
+        assert!(clear.contains("; HttpOnly"));
+        assert!(clear.contains("; SameSite=Lax"));
+        assert!(clear.contains("; Max-Age=0"));
+        assert!(!clear.contains("Secure"));
+
+        for value in ["1", "true"] {
+            std::env::set_var("COOKIE_SECURE", value);
+            assert!(session_cookie("abc123").ends_with("; Secure"));
+            assert!(clear_session_cookie().ends_with("; Secure"));
+        }
+        std::env::set_var("COOKIE_SECURE", "false");
+        assert!(!session_cookie("abc123").contains("Secure"));
+        std::env::remove_var("COOKIE_SECURE");
+    }
+}
@@ -0,0 +1,522 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcError;
+use std::env;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Consecutive failures after which an endpoint is moved to the back of
+/// the queue.
+const UNHEALTHY_AFTER: u32 = 3;
+
+/// How long an unhealthy endpoint stays at the back before it is preferred again.
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Upper bound for the delay between retry rounds.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Used when neither `SOLANA_RPC_URLS` nor `SOLANA_RPC_URL` is set. The
+/// public endpoint is heavily rate limited; production should configure
+/// its own.
+pub const DEFAULT_ENDPOINT: &str = "https://api.mainnet-beta.solana.com";
+
+/// JSON-RPC error codes a node returns when it is behind or overloaded;
+/// another node may well answer.
+const TRANSIENT_RPC_CODES: [i64; 3] = [-32004, -32005, -32603];
+
+// =====================
+// Configuration
+// =====================
+
+#[derive(Debug, Clone)]
+pub struct SolanaRpcConfig {
+    /// RPC URLs in order of preference
+    pub endpoints: Vec<String>,
+    /// Limit for a single request to a single endpoint
+    pub timeout: Duration,
+    /// Extra rounds over all endpoints after the first one fails
+    pub retries: u32,
+    /// Delay before the first retry round; doubles each round
+    pub backoff: Duration,
+}
+
+impl SolanaRpcConfig {
+    pub fn from_env() -> Self {
+        let millis = |key: &str, default: u64| {
+            Duration::from_millis(env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default))
+        };
+
+        // SOLANA_RPC_URLS takes a comma-separated list; SOLANA_RPC_URL a single endpoint
+        let mut endpoints: Vec<String> = env::var("SOLANA_RPC_URLS")
+            .or_else(|_| env::var("SOLANA_RPC_URL"))
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if endpoints.is_empty() {
+            tracing::warn!("SOLANA_RPC_URLS not set, using the public mainnet endpoint {}", DEFAULT_ENDPOINT);
+            endpoints.push(DEFAULT_ENDPOINT.to_string());
+        }
+
+        SolanaRpcConfig {
+            endpoints,
+            timeout: millis("SOLANA_RPC_TIMEOUT_MS", 8_000),
+            retries: env::var("SOLANA_RPC_RETRIES")
+                .ok()
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(2),
+            backoff: millis("SOLANA_RPC_BACKOFF_MS", 250),
+        }
+    }
+
+    /// Delay before retry round `round` (1-based).
+    fn backoff_for(&self, round: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(round.saturating_sub(1)))
+            .min(MAX_BACKOFF)
+    }
+}
+
+// =====================
+// Endpoint health
+// =====================
+
+/// Health snapshot of one endpoint, for the admin API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    /// Redacted to `scheme://host`
+    pub url: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub requests: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    consecutive_failures: u32,
+    requests: u64,
+    failures: u64,
+    unhealthy_until: Option<Instant>,
+    last_error: Option<String>,
+}
+
+struct Endpoint {
+    url: String,
+    /// Redacted URL, safe to log
+    name: String,
+    client: Arc<RpcClient>,
+    health: Mutex<HealthState>,
+}
+
+impl Endpoint {
+    fn health(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.health.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// `Some(until)` while the endpoint is cooling down after repeated failures.
+    fn unhealthy_until(&self) -> Option<Instant> {
+        self.health().unhealthy_until.filter(|until| *until > Instant::now())
+    }
+
+    /// Transport errors quote the full request URL; replace it with `name`.
+    fn scrub(&self, error: &ClientError) -> String {
+        error.to_string().replace(&self.url, &self.name)
+    }
+
+    fn record_success(&self) {
+        let mut health = self.health();
+        health.requests += 1;
+        health.consecutive_failures = 0;
+        health.unhealthy_until = None;
+    }
+
+    fn record_failure(&self, error: String) {
+        let mut health = self.health();
+        health.requests += 1;
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= UNHEALTHY_AFTER {
+            if health.unhealthy_until.is_none() {
+                tracing::warn!(
+                    "Solana RPC {} marked unhealthy after {} failures: {}",
+                    self.name,
+                    health.consecutive_failures,
+                    error
+                );
+            }
+            health.unhealthy_until = Some(Instant::now() + UNHEALTHY_COOLDOWN);
+        }
+        health.last_error = Some(error);
+    }
+}
+
+/// `scheme://host` of an endpoint. Providers put API keys in the path or
+/// query, so only this form is logged or shown.
+pub fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match parsed.host_str() {
+            Some(host) => format!("{}://{}", parsed.scheme(), host),
+            None => parsed.scheme().to_string(),
+        },
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+/// Whether another attempt (possibly on another endpoint) could succeed.
+/// Errors about the request itself, such as an invalid parameter, are final.
+fn is_retryable(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            TRANSIENT_RPC_CODES.contains(code)
+        }
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        _ => false,
+    }
+}
+
+// =====================
+// Client
+// =====================
+
+/// Shared nonblocking Solana RPC client over several endpoints. Every
+/// request is bounded by the configured timeout, fails over to the next
+/// endpoint and is retried in rounds with exponential backoff.
+///
+/// main.rs builds one per process and hands it to server functions through
+/// the leptos context and to background tasks as an `Arc`.
+pub struct SolanaRpc {
+    config: SolanaRpcConfig,
+    endpoints: Vec<Endpoint>,
+}
+
+impl SolanaRpc {
+    pub fn new(config: SolanaRpcConfig) -> Self {
+        let endpoints = config
+            .endpoints
+            .iter()
+            .map(|url| Endpoint {
+                url: url.clone(),
+                name: redact_url(url),
+                client: Arc::new(RpcClient::new_with_timeout(url.clone(), config.timeout)),
+                health: Mutex::new(HealthState::default()),
+            })
+            .collect();
+        SolanaRpc { config, endpoints }
+    }
+
+    pub fn config(&self) -> &SolanaRpcConfig {
+        &self.config
+    }
+
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let healthy = endpoint.unhealthy_until().is_none();
+                let health = endpoint.health();
+                EndpointHealth {
+                    url: endpoint.name.clone(),
+                    healthy,
+                    consecutive_failures: health.consecutive_failures,
+                    requests: health.requests,
+                    failures: health.failures,
+                    last_error: health.last_error.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Healthy endpoints in preference order, then cooling-down ones,
+    /// soonest to recover first.
+    fn attempt_order(&self) -> Vec<usize> {
+        let mut healthy = Vec::new();
+        let mut cooling = Vec::new();
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            match endpoint.unhealthy_until() {
+                Some(until) => cooling.push((until, index)),
+                None => healthy.push(index),
+            }
+        }
+        cooling.sort();
+        healthy.extend(cooling.into_iter().map(|(_, index)| index));
+        healthy
+    }
+
+    /// Run `request` against the endpoints until one answers. `method` is
+    /// only used in logs and errors, e.g. `"getBalance"`.
+    pub async fn call<T, F, Fut>(&self, method: &str, request: F) -> Result<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut last_error = String::from("no endpoints configured");
+        for round in 0..=self.config.retries {
+            if round > 0 {
+                tokio::time::sleep(self.config.backoff_for(round)).await;
+            }
+
+            for index in self.attempt_order() {
+                let endpoint = &self.endpoints[index];
+                match tokio::time::timeout(self.config.timeout, request(endpoint.client.clone())).await {
+                    Ok(Ok(value)) => {
+                        endpoint.record_success();
+                        return Ok(value);
+                    }
+                    Ok(Err(e)) if !is_retryable(&e) => {
+                        // The endpoint answered; the request itself is bad
+                        endpoint.record_success();
+                        return Err(anyhow!("{} failed: {}", method, endpoint.scrub(&e)));
+                    }
+                    Ok(Err(e)) => {
+                        let error = endpoint.scrub(&e);
+                        last_error = format!("{}: {}", endpoint.name, error);
+                        endpoint.record_failure(error);
+                    }
+                    Err(_) => {
+                        last_error = format!("{}: timed out after {:?}", endpoint.name, self.config.timeout);
+                        endpoint.record_failure("timed out".to_string());
+                    }
+                }
+                tracing::debug!("Solana RPC {} attempt failed ({})", method, last_error);
+            }
+        }
+
+        Err(anyhow!(
+            "{} failed on every Solana RPC endpoint after {} rounds; last error {}",
+            method,
+            self.config.retries + 1,
+            last_error
+        ))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::{routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// How a stub endpoint answers one JSON-RPC call.
+    pub(crate) enum Reply {
+        Result(Value),
+        /// JSON-RPC error object with this code
+        Error(i64),
+        /// Bare HTTP status without a JSON-RPC body
+        Status(StatusCode),
+        /// No answer within any test timeout
+        Hang,
+    }
+
+    type Answer = Arc<dyn Fn(&str, &Value) -> Reply + Send + Sync>;
+
+    /// Serve JSON-RPC on a free local port. `answer` gets the method and
+    /// params of every call. Returns the endpoint URL.
+    pub(crate) async fn stub_endpoint(
+        answer: impl Fn(&str, &Value) -> Reply + Send + Sync + 'static,
+    ) -> String {
+        let answer: Answer = Arc::new(answer);
+        let app = Router::new().route("/", post(handle)).with_state(answer);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    async fn handle(State(answer): State<Answer>, Json(call): Json<Value>) -> Response {
+        let method = call["method"].as_str().unwrap_or_default();
+        match answer(method, &call["params"]) {
+            Reply::Result(result) => {
+                Json(json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })).into_response()
+            }
+            Reply::Error(code) => Json(json!({
+                "jsonrpc": "2.0",
+                "id": call["id"],
+                "error": { "code": code, "message": format!("stub error {}", code) },
+            }))
+            .into_response(),
+            Reply::Status(status) => status.into_response(),
+            Reply::Hang => {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                StatusCode::GATEWAY_TIMEOUT.into_response()
+            }
+        }
+    }
+
+    /// The `{"context":..,"value":..}` envelope most RPC methods answer with.
+    pub(crate) fn with_context(value: Value) -> Value {
+        json!({ "context": { "slot": 1 }, "value": value })
+    }
+
+    /// Client with short timeouts for stub endpoints.
+    pub(crate) fn test_rpc(endpoints: Vec<String>, retries: u32) -> SolanaRpc {
+        SolanaRpc::new(SolanaRpcConfig {
+            endpoints,
+            timeout: Duration::from_millis(500),
+            retries,
+            backoff: Duration::from_millis(10),
+        })
+    }
+
+    /// Stub answering `getBalance` with `reply(call_number)`, counting calls.
+    async fn counted(reply: impl Fn(usize) -> Reply + Send + Sync + 'static) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let seen = calls.clone();
+        let url = stub_endpoint(move |_, _| reply(seen.fetch_add(1, Ordering::SeqCst))).await;
+        (url, calls)
+    }
+
+    fn balance(lamports: u64) -> Reply {
+        Reply::Result(with_context(json!(lamports)))
+    }
+
+    async fn get_balance(rpc: &SolanaRpc) -> Result<u64> {
+        let wallet = solana_sdk::pubkey::Pubkey::new_unique();
+        rpc.call("getBalance", |client| async move { client.get_balance(&wallet).await })
+            .await
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let config = SolanaRpcConfig {
+            endpoints: Vec::new(),
+            timeout: Duration::from_secs(1),
+            retries: 10,
+            backoff: Duration::from_millis(250),
+        };
+        assert_eq!(config.backoff_for(1), Duration::from_millis(250));
+        assert_eq!(config.backoff_for(2), Duration::from_millis(500));
+        assert_eq!(config.backoff_for(3), Duration::from_millis(1000));
+        assert_eq!(config.backoff_for(6), MAX_BACKOFF);
+        assert_eq!(config.backoff_for(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn redaction_keeps_scheme_and_host() {
+        assert_eq!(
+            redact_url("https://mainnet.helius-rpc.com/?api-key=secret"),
+            "https://mainnet.helius-rpc.com"
+        );
+        assert_eq!(
+            redact_url("https://solana-mainnet.g.alchemy.com/v2/secret"),
+            "https://solana-mainnet.g.alchemy.com"
+        );
+        assert_eq!(redact_url("not a url"), "<invalid url>");
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_the_next_endpoint() {
+        let (down, down_calls) = counted(|_| Reply::Status(StatusCode::SERVICE_UNAVAILABLE)).await;
+        let (up, up_calls) = counted(|_| balance(42)).await;
+        let rpc = test_rpc(vec![down, up], 0);
+
+        assert_eq!(get_balance(&rpc).await.unwrap(), 42);
+        assert_eq!(down_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(up_calls.load(Ordering::SeqCst), 1);
+
+        let health = rpc.health();
+        assert_eq!((health[0].failures, health[0].consecutive_failures), (1, 1));
+        assert!(health[0].healthy);
+        assert_eq!((health[1].requests, health[1].failures), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn transient_rpc_errors_and_timeouts_fail_over() {
+        let (behind, _) = counted(|_| Reply::Error(-32005)).await;
+        let (hanging, _) = counted(|_| Reply::Hang).await;
+        let (up, _) = counted(|_| balance(7)).await;
+        let rpc = SolanaRpc::new(SolanaRpcConfig {
+            endpoints: vec![behind, hanging, up],
+            timeout: Duration::from_millis(100),
+            retries: 0,
+            backoff: Duration::from_millis(10),
+        });
+
+        assert_eq!(get_balance(&rpc).await.unwrap(), 7);
+        let health = rpc.health();
+        assert!(health[0].last_error.as_deref().unwrap().contains("-32005"));
+        // Either the client's own timeout or the outer one fires first
+        assert_eq!((health[1].failures, health[2].failures), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn retries_rounds_with_backoff() {
+        let (flaky, calls) = counted(|call| match call {
+            0 | 1 => Reply::Status(StatusCode::BAD_GATEWAY),
+            _ => balance(1),
+        })
+        .await;
+        let rpc = test_rpc(vec![flaky], 2);
+
+        let started = Instant::now();
+        assert_eq!(get_balance(&rpc).await.unwrap(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        let config = rpc.config();
+        assert!(started.elapsed() >= config.backoff_for(1) + config.backoff_for(2));
+
+        let (down, calls) = counted(|_| Reply::Status(StatusCode::BAD_GATEWAY)).await;
+        let rpc = test_rpc(vec![down], 1);
+        let error = get_balance(&rpc).await.unwrap_err().to_string();
+        assert!(error.contains("after 2 rounds"), "{}", error);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn invalid_requests_are_not_retried() {
+        let (first, first_calls) = counted(|_| Reply::Error(-32602)).await;
+        let (second, second_calls) = counted(|_| balance(1)).await;
+        let rpc = test_rpc(vec![first, second], 2);
+
+        let error = get_balance(&rpc).await.unwrap_err().to_string();
+        assert!(error.contains("-32602"), "{}", error);
+        assert_eq!(first_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second_calls.load(Ordering::SeqCst), 0);
+        // The endpoint answered, so it stays healthy
+        assert_eq!(rpc.health()[0].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn repeatedly_failing_endpoints_are_tried_last() {
+        let (down, down_calls) = counted(|_| Reply::Status(StatusCode::INTERNAL_SERVER_ERROR)).await;
+        let (up, up_calls) = counted(|_| balance(5)).await;
+        let rpc = test_rpc(vec![down, up], 0);
+
+        for _ in 0..UNHEALTHY_AFTER {
+            assert_eq!(get_balance(&rpc).await.unwrap(), 5);
+        }
+        let health = rpc.health();
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].consecutive_failures, UNHEALTHY_AFTER);
+        assert!(health[1].healthy);
+
+        // While cooling down the failing endpoint is no longer asked first
+        assert_eq!(get_balance(&rpc).await.unwrap(), 5);
+        assert_eq!(down_calls.load(Ordering::SeqCst), UNHEALTHY_AFTER as usize);
+        assert_eq!(up_calls.load(Ordering::SeqCst), UNHEALTHY_AFTER as usize + 1);
+    }
+
+    #[tokio::test]
+    async fn errors_do_not_leak_endpoint_secrets() {
+        // Nothing listening: a port that was free a moment ago
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}/v2/secret-key", listener.local_addr().unwrap());
+        drop(listener);
+        let rpc = test_rpc(vec![closed], 0);
+
+        let error = get_balance(&rpc).await.unwrap_err().to_string();
+        assert!(!error.contains("secret-key"), "{}", error);
+        let health = rpc.health();
+        assert_eq!(health[0].url, "http://127.0.0.1");
+        assert!(!health[0].last_error.as_deref().unwrap().contains("secret-key"));
+    }
+}
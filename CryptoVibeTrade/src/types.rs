@@ -13,6 +13,8 @@ pub enum Tool {
     Rectangle,
     Circle,
     Text,
+    /// Picks existing strokes; never stored on a stroke
+    Select,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -32,14 +34,112 @@ pub enum Color {
 pub struct StrokePoint {
     pub x: f64,
     pub y: f64,
+    /// Pen pressure in 0..=1; `DEFAULT_PRESSURE` when the device has none
+    #[serde(default = "default_pressure", skip_serializing_if = "is_default_pressure")]
+    pub pressure: f32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Pressure reported by devices without pressure sensing (per Pointer Events)
+pub const DEFAULT_PRESSURE: f32 = 0.5;
+
+fn default_pressure() -> f32 {
+    DEFAULT_PRESSURE
+}
+
+fn is_default_pressure(pressure: &f32) -> bool {
+    *pressure == DEFAULT_PRESSURE
+}
+
+impl StrokePoint {
+    pub fn new(x: f64, y: f64) -> Self {
+        StrokePoint {
+            x,
+            y,
+            pressure: DEFAULT_PRESSURE,
+        }
+    }
+
+    pub fn with_pressure(x: f64, y: f64, pressure: f32) -> Self {
+        StrokePoint {
+            x,
+            y,
+            pressure: pressure.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Line width at `pressure`: `thickness` at the default pressure, from a
+/// quarter of it at no pressure up to 1.75x at full pressure.
+pub fn pressure_width(thickness: f64, pressure: f32) -> f64 {
+    thickness * (0.25 + 1.5 * pressure as f64)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Stroke {
     pub points: Vec<StrokePoint>,
     pub color: Color,
     pub thickness: f64,
     pub tool: Tool,
+    /// Content of a `Tool::Text` stroke, anchored top-left at `points[0]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Font size per unit of stroke thickness
+pub const TEXT_SIZE_PER_THICKNESS: f64 = 8.0;
+/// Approximate advance of a monospace glyph, as a fraction of the font size
+pub const TEXT_CHAR_WIDTH: f64 = 0.6;
+/// Line height as a fraction of the font size
+pub const TEXT_LINE_HEIGHT: f64 = 1.25;
+
+/// Font size in board units for a text stroke of the given thickness.
+pub fn text_font_size(thickness: f64) -> f64 {
+    (thickness * TEXT_SIZE_PER_THICKNESS).max(8.0)
+}
+
+impl Stroke {
+    /// A text stroke at `at`; the font size follows `thickness`.
+    pub fn new_text(at: StrokePoint, text: String, color: Color, thickness: f64) -> Self {
+        Stroke {
+            points: vec![at],
+            color,
+            thickness,
+            tool: Tool::Text,
+            text: Some(text),
+        }
+    }
+
+    /// Whether any point carries real pressure, so width varies along the stroke.
+    pub fn has_pressure(&self) -> bool {
+        self.points.iter().any(|p| p.pressure != DEFAULT_PRESSURE)
+    }
+
+    pub fn font_size(&self) -> f64 {
+        text_font_size(self.thickness)
+    }
+
+    /// `(x, y, width, height)` of a text stroke, estimated from the
+    /// monospace glyph width so it works without a canvas.
+    pub fn text_bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let text = self.text.as_ref()?;
+        let origin = self.points.first()?;
+        let size = self.font_size();
+        let longest = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let lines = text.lines().count().max(1);
+        Some((
+            origin.x,
+            origin.y,
+            longest as f64 * size * TEXT_CHAR_WIDTH,
+            lines as f64 * size * TEXT_LINE_HEIGHT,
+        ))
+    }
+
+    /// Whether `(x, y)` falls on this text stroke, with `slop` board units of tolerance.
+    pub fn text_contains(&self, x: f64, y: f64, slop: f64) -> bool {
+        self.text_bounds().is_some_and(|(left, top, w, h)| {
+            x >= left - slop && x <= left + w + slop && y >= top - slop && y <= top + h + slop
+        })
+    }
 }
 
 // =====================
@@ -54,7 +154,7 @@ pub struct DragrokEvent {
     pub chart_data: Option<ChartInvocation>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DragrokEventType {
     #[serde(rename = "FIRE")]
     Fire,
@@ -68,7 +168,7 @@ pub enum DragrokEventType {
     Speak,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChartInvocation {
     pub symbol: String,           // "BTCUSDT", "SOLUSDT"
     pub timeframe: String,         // "1m", "5m", "1h"
@@ -76,7 +176,18 @@ pub struct ChartInvocation {
     pub position: ChartPosition,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ChartInvocation {
+    /// Header text: symbol, timeframe and any indicators.
+    pub fn title(&self) -> String {
+        if self.indicators.is_empty() {
+            format!("{} · {}", self.symbol, self.timeframe)
+        } else {
+            format!("{} · {} · {}", self.symbol, self.timeframe, self.indicators.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChartPosition {
     pub x: f64,  // posição no canvas (0-100%)
     pub y: f64,
@@ -98,6 +209,45 @@ pub struct Candle {
     pub volume: f64,
 }
 
+/// Messages pushed to the browser over `/ws/market`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketMessage {
+    /// Full rolling window, sent once when a client subscribes
+    Snapshot {
+        symbol: String,
+        interval: String,
+        candles: Vec<Candle>,
+    },
+    /// One kline update; `closed` is false while the candle is still forming
+    Candle {
+        symbol: String,
+        interval: String,
+        candle: Candle,
+        closed: bool,
+    },
+}
+
+/// Merge a kline update into a time-ordered candle list: replace the
+/// candle with the same open time, append newer ones, ignore stale ones,
+/// and keep at most `max_len` candles.
+pub fn upsert_candle(candles: &mut Vec<Candle>, candle: Candle, max_len: usize) {
+    match candles.last_mut() {
+        Some(last) if last.time == candle.time => *last = candle,
+        Some(last) if last.time > candle.time => {
+            if let Some(existing) = candles.iter_mut().rev().find(|c| c.time == candle.time) {
+                *existing = candle;
+            }
+        }
+        _ => candles.push(candle),
+    }
+
+    if candles.len() > max_len {
+        let excess = candles.len() - max_len;
+        candles.drain(..excess);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndicatorValue {
     pub name: String,
@@ -120,3 +270,34 @@ impl fmt::Display for Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_stroke_round_trips() {
+        let stroke = Stroke::new_text(StrokePoint::new(100.0, 50.0), "Buy\nzone".to_string(), Color::Yellow, 2.0);
+        let json = serde_json::to_string(&stroke).unwrap();
+        assert!(json.contains(r#""tool":"Text""#));
+        assert!(json.contains(r#""text":"Buy\nzone""#));
+        assert_eq!(serde_json::from_str::<Stroke>(&json).unwrap(), stroke);
+    }
+
+    #[test]
+    fn strokes_without_text_omit_it() {
+        let pen = Stroke {
+            points: vec![StrokePoint::new(0.0, 0.0)],
+            color: Color::White,
+            thickness: 2.0,
+            tool: Tool::Pen,
+            text: None,
+        };
+        let json = serde_json::to_string(&pen).unwrap();
+        assert!(!json.contains("text"));
+
+        // Boards saved before text strokes existed have no `text` field
+        let saved = r#"{"points":[{"x":0.0,"y":0.0}],"color":"White","thickness":2.0,"tool":"Pen"}"#;
+        assert_eq!(serde_json::from_str::<Stroke>(saved).unwrap(), pen);
+    }
+}
@@ -0,0 +1,300 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// How long a sign-in challenge can be signed and redeemed.
+pub const CHALLENGE_MINUTES: i64 = 5;
+/// Challenges one client address may request per `CHALLENGE_WINDOW`.
+pub const CHALLENGES_PER_CLIENT: usize = 10;
+pub const CHALLENGE_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+/// Unredeemed challenges kept per wallet, and in total.
+pub const MAX_OPEN_CHALLENGES_PER_WALLET: i64 = 5;
+pub const MAX_OPEN_CHALLENGES: i64 = 10_000;
+
+/// Username prefix and email domain of accounts created by a wallet
+/// sign-in. Password registrations may not use them, so nobody can take a
+/// wallet's account name before the wallet first signs in.
+pub const WALLET_USERNAME_PREFIX: &str = "sol_";
+pub const WALLET_EMAIL_DOMAIN: &str = "wallet.cvt.local";
+
+/// Statement shown by the wallet above the challenge details.
+const STATEMENT: &str =
+    "Sign in to Crypto Vibe Trade. This request will not trigger a transaction or cost any fees.";
+
+/// Message a wallet is asked to sign, modelled on Sign-In With Solana.
+/// The server keeps its own copy; the browser only relays the signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletChallenge {
+    pub wallet: String,
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WalletChallenge {
+    /// A fresh challenge for `wallet`, issued by `domain` at `now`.
+    pub fn new(domain: &str, wallet: &str, nonce: String, now: DateTime<Utc>) -> Self {
+        let expires_at = now + Duration::minutes(CHALLENGE_MINUTES);
+        let message = format!(
+            "{} wants you to sign in with your Solana account:\n{}\n\n{}\n\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+            domain,
+            wallet,
+            STATEMENT,
+            nonce,
+            now.to_rfc3339(),
+            expires_at.to_rfc3339()
+        );
+        WalletChallenge {
+            wallet: wallet.to_string(),
+            nonce,
+            message,
+            expires_at,
+        }
+    }
+}
+
+/// Domain named in challenges: the site's host as browsers send it in
+/// `Host`, e.g. `cryptovibetrade.com` or `localhost:3000`.
+pub fn configured_domain() -> Option<String> {
+    std::env::var("WALLET_AUTH_DOMAIN")
+        .ok()
+        .map(|domain| domain.trim().to_string())
+        .filter(|domain| !domain.is_empty())
+}
+
+/// The domain to name in a challenge requested with `host`. Challenges are
+/// only issued on the configured domain, never for whatever host the
+/// request claims.
+pub fn challenge_domain(configured: Option<&str>, host: Option<&str>) -> Result<String, String> {
+    let domain = configured.ok_or("Wallet sign-in is not configured: set WALLET_AUTH_DOMAIN")?;
+    if host.is_some_and(|host| host.eq_ignore_ascii_case(domain)) {
+        Ok(domain.to_string())
+    } else {
+        Err(format!("Wallet sign-in is only available at {}", domain))
+    }
+}
+
+/// Canonical base58 form of a wallet address, or an error message for the user.
+pub fn parse_wallet(address: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(address.trim()).map_err(|_| "Invalid wallet address".to_string())
+}
+
+/// Check that `signature` (base58, as returned by the wallet) is `wallet`'s
+/// ed25519 signature over `message`.
+pub fn verify_wallet_signature(wallet: &str, message: &str, signature: &str) -> Result<(), String> {
+    let pubkey = parse_wallet(wallet)?;
+    let signature =
+        Signature::from_str(signature.trim()).map_err(|_| "Malformed signature".to_string())?;
+    if signature.verify(pubkey.as_ref(), message.as_bytes()) {
+        Ok(())
+    } else {
+        Err("Signature does not match the wallet".to_string())
+    }
+}
+
+/// Usernames tried, in order, for an account created by a wallet's first
+/// sign-in: a short one, then the full address should two wallets share
+/// the same first characters.
+pub fn wallet_usernames(address: &str) -> [String; 2] {
+    [
+        format!("{}{}", WALLET_USERNAME_PREFIX, address.chars().take(12).collect::<String>()),
+        format!("{}{}", WALLET_USERNAME_PREFIX, address),
+    ]
+}
+
+pub fn wallet_email(address: &str) -> String {
+    format!("{}@{}", address, WALLET_EMAIL_DOMAIN)
+}
+
+/// Whether a password registration asks for a name or email kept for
+/// wallet accounts.
+pub fn is_reserved_identity(username: &str, email: &str) -> bool {
+    let username = username.trim().to_lowercase();
+    let email = email.trim().to_lowercase();
+    let wallet_domain = |domain: &str| {
+        domain == WALLET_EMAIL_DOMAIN || domain.ends_with(&format!(".{}", WALLET_EMAIL_DOMAIN))
+    };
+    username.starts_with(WALLET_USERNAME_PREFIX) || email.rsplit_once('@').is_some_and(|(_, domain)| wallet_domain(domain))
+}
+
+// =====================
+// Rate limiting
+// =====================
+
+/// Clients tracked before idle ones are forgotten
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Sliding-window limit on challenge requests per client address. Issuing a
+/// challenge needs no account, so this keeps one client from filling the
+/// nonce table.
+pub struct ChallengeLimiter {
+    limit: usize,
+    window: std::time::Duration,
+    requests: Mutex<HashMap<IpAddr, VecDeque<Instant>>>,
+}
+
+impl ChallengeLimiter {
+    pub fn new(limit: usize, window: std::time::Duration) -> Self {
+        ChallengeLimiter {
+            limit,
+            window,
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request from `client` at `now`; false when over the limit.
+    pub fn allow(&self, client: IpAddr, now: Instant) -> bool {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        if requests.len() >= MAX_TRACKED_CLIENTS {
+            let window = self.window;
+            requests.retain(|_, times| times.back().is_some_and(|t| now.duration_since(*t) < window));
+        }
+
+        let times = requests.entry(client).or_default();
+        while times.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+            times.pop_front();
+        }
+        if times.len() >= self.limit {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+static CHALLENGE_LIMITER: OnceLock<ChallengeLimiter> = OnceLock::new();
+
+pub fn challenge_limiter() -> &'static ChallengeLimiter {
+    CHALLENGE_LIMITER.get_or_init(|| ChallengeLimiter::new(CHALLENGES_PER_CLIENT, CHALLENGE_WINDOW))
+}
+
+/// Short form for display, e.g. `7xKX…gAsU`.
+pub fn short_address(address: &str) -> String {
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() <= 10 {
+        return address.to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn signed_challenge(keypair: &Keypair) -> (WalletChallenge, String) {
+        let wallet = keypair.pubkey().to_string();
+        let challenge = WalletChallenge::new("cvt.test", &wallet, "abc123".to_string(), Utc::now());
+        let signature = keypair.sign_message(challenge.message.as_bytes()).to_string();
+        (challenge, signature)
+    }
+
+    #[test]
+    fn challenge_names_wallet_nonce_and_expiry() {
+        let now = Utc::now();
+        let challenge = WalletChallenge::new("cvt.test", "Wallet111", "n0nce".to_string(), now);
+        assert!(challenge.message.starts_with("cvt.test wants you to sign in with your Solana account:\nWallet111\n"));
+        assert!(challenge.message.contains("\nNonce: n0nce\n"));
+        assert_eq!(challenge.expires_at, now + Duration::minutes(CHALLENGE_MINUTES));
+        assert!(challenge.message.ends_with(&challenge.expires_at.to_rfc3339()));
+    }
+
+    #[test]
+    fn accepts_the_wallets_own_signature() {
+        let keypair = Keypair::new();
+        let (challenge, signature) = signed_challenge(&keypair);
+        assert_eq!(verify_wallet_signature(&challenge.wallet, &challenge.message, &signature), Ok(()));
+        // Whitespace around pasted values is ignored
+        let padded = format!(" {} ", signature);
+        assert_eq!(verify_wallet_signature(&format!("{} ", challenge.wallet), &challenge.message, &padded), Ok(()));
+    }
+
+    #[test]
+    fn rejects_other_messages_wallets_and_garbage() {
+        let keypair = Keypair::new();
+        let (challenge, signature) = signed_challenge(&keypair);
+
+        let tampered = challenge.message.replace("abc123", "abc124");
+        assert_eq!(
+            verify_wallet_signature(&challenge.wallet, &tampered, &signature),
+            Err("Signature does not match the wallet".to_string())
+        );
+
+        let other = Keypair::new().pubkey().to_string();
+        assert_eq!(
+            verify_wallet_signature(&other, &challenge.message, &signature),
+            Err("Signature does not match the wallet".to_string())
+        );
+
+        assert_eq!(
+            verify_wallet_signature(&challenge.wallet, &challenge.message, "not-base58!"),
+            Err("Malformed signature".to_string())
+        );
+        assert_eq!(
+            verify_wallet_signature("nope", &challenge.message, &signature),
+            Err("Invalid wallet address".to_string())
+        );
+    }
+
+    #[test]
+    fn wallet_identities_are_reserved() {
+        let address = Keypair::new().pubkey().to_string();
+        let [short, full] = wallet_usernames(&address);
+        assert_eq!(short.len(), WALLET_USERNAME_PREFIX.len() + 12);
+        assert_eq!(full, format!("sol_{}", address));
+
+        for username in [short.as_str(), full.as_str(), "SOL_squatter"] {
+            assert!(is_reserved_identity(username, "someone@example.com"), "{}", username);
+        }
+        for email in [wallet_email(&address), "x@WALLET.cvt.local".to_string(), "x@a.wallet.cvt.local".to_string()] {
+            assert!(is_reserved_identity("trader", &email), "{}", email);
+        }
+        assert!(!is_reserved_identity("solana_fan", "fan@example.com"));
+        assert!(!is_reserved_identity("trader", "trader@notwallet.cvt.local"));
+    }
+
+    #[test]
+    fn limiter_caps_requests_per_client_and_window() {
+        let limiter = ChallengeLimiter::new(3, std::time::Duration::from_secs(60));
+        let start = Instant::now();
+        let (a, b): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+
+        for i in 0..3 {
+            assert!(limiter.allow(a, start + std::time::Duration::from_secs(i)));
+        }
+        assert!(!limiter.allow(a, start + std::time::Duration::from_secs(10)));
+        // Other clients have their own budget
+        assert!(limiter.allow(b, start + std::time::Duration::from_secs(10)));
+
+        // The first request leaves the window after 60s, freeing one slot
+        assert!(limiter.allow(a, start + std::time::Duration::from_secs(60)));
+        assert!(!limiter.allow(a, start + std::time::Duration::from_secs(60)));
+        assert!(limiter.allow(a, start + std::time::Duration::from_secs(122)));
+    }
+
+    #[test]
+    fn challenges_name_only_the_configured_domain() {
+        let domain = Some("cryptovibetrade.com");
+        assert_eq!(challenge_domain(domain, Some("cryptovibetrade.com")), Ok("cryptovibetrade.com".to_string()));
+        assert_eq!(challenge_domain(domain, Some("CryptoVibeTrade.com")), Ok("cryptovibetrade.com".to_string()));
+        for host in [Some("cryptovibetrade.com.evil.io"), Some("cryptovibetrade.com:8080"), Some(""), None] {
+            assert!(challenge_domain(domain, host).is_err(), "{:?}", host);
+        }
+        assert!(challenge_domain(Some("localhost:3000"), Some("localhost:3000")).is_ok());
+        assert!(challenge_domain(None, Some("cryptovibetrade.com")).is_err());
+    }
+
+    #[test]
+    fn short_address_keeps_ends() {
+        assert_eq!(short_address("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"), "7xKX…gAsU");
+        assert_eq!(short_address("short"), "short");
+    }
+}
@@ -11,3 +11,19 @@ echo "2. Testing with simple prompt:"
 curl -s -X POST http://127.0.0.1:3000/api/gemini \
   -H "Content-Type: application/json" \
   -d '{"prompt":"Analyze SOL","asset":"SOL","indicators":"MACD"}' | jq '.'
+echo ""
+echo ""
+echo "3. Mock provider error path (run server with AI_PROVIDER=mock):"
+curl -s -X POST http://127.0.0.1:3000/api/gemini \
+  -H "Content-Type: application/json" \
+  -d '{"prompt":"[mock:error] Analyze SOL","asset":"SOL","indicators":"MACD"}' | jq '.'
+echo ""
+echo ""
+echo "4. Mock provider timeout path (run server with AI_PROVIDER=mock AI_TIMEOUT_SECS=2):"
+curl -s -X POST http://127.0.0.1:3000/api/gemini \
+  -H "Content-Type: application/json" \
+  -d '{"prompt":"[mock:timeout] Analyze BTC","asset":"BTC","indicators":"RSI"}' | jq '.'
+echo ""
+echo ""
+echo "5. Streaming analysis:"
+curl -s -N "http://127.0.0.1:3000/api/analyze/stream?mode=technical&prompt=Trend%3F&asset=ZEC&indicators=RSI,%20MACD"